@@ -0,0 +1,65 @@
+//! Compares the bitmask solver against plain backtracking. Run with
+//! `cargo bench --bench solver`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use rudoku::BoardState;
+
+const PUZZLES: &[(&str, &str)] = &[
+    (
+        "easy",
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+    ),
+    (
+        "hard",
+        "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+    ),
+    (
+        "arto inkala",
+        "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+    ),
+    (
+        "empty",
+        ".................................................................................",
+    ),
+];
+
+/// Average time per run, running for at least `budget`.
+fn time(budget: Duration, mut run: impl FnMut()) -> Duration {
+    let started = Instant::now();
+    let mut runs = 0;
+    while started.elapsed() < budget {
+        run();
+        runs += 1;
+    }
+    started.elapsed() / runs
+}
+
+fn main() {
+    let budget = Duration::from_millis(500);
+    println!(
+        "{:<16} {:>12} {:>12} {:>8}",
+        "puzzle", "bitmask", "backtrack", "speedup"
+    );
+    for (name, code) in PUZZLES {
+        let Ok(board) = code.parse::<BoardState>() else {
+            continue;
+        };
+        let bitmask = time(budget, || {
+            black_box(black_box(board).solve());
+        });
+        let backtrack = time(budget, || {
+            let mut nodes = 0;
+            black_box(black_box(board).solve_counting(&mut nodes));
+        });
+        println!(
+            "{name:<16} {:>12} {:>12} {:>7.0}x",
+            format!("{bitmask:.1?}"),
+            format!("{backtrack:.1?}"),
+            backtrack.as_secs_f64() / bitmask.as_secs_f64()
+        );
+    }
+}
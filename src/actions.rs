@@ -0,0 +1,422 @@
+use std::mem::discriminant;
+
+use ratatui::crossterm::event::KeyCode;
+
+use crate::{
+    keymap::{Action, Mode},
+    rules::RuleSet,
+};
+
+/// Everything the app knows about an action besides what it does. The
+/// keymap, help overlay, footer, key editor and command palette all read
+/// from here, so an action is named and described in one place.
+pub struct ActionInfo {
+    /// one action of the kind, actions carrying a value share an entry
+    pub action: Action,
+    /// the name in the `[keys]` config table, none for actions carrying a
+    /// value since a key can only give one
+    pub id: Option<&'static str>,
+    pub description: &'static str,
+    /// bound by where the key sits rather than what it is labelled
+    pub movement: bool,
+    /// whether a game's rules let the player use it
+    pub available: fn(&RuleSet) -> bool,
+}
+
+const fn info(action: Action, id: &'static str, description: &'static str) -> ActionInfo {
+    ActionInfo {
+        action,
+        id: Some(id),
+        description,
+        movement: false,
+        available: always,
+    }
+}
+
+const fn unnamed(action: Action, description: &'static str) -> ActionInfo {
+    ActionInfo {
+        id: None,
+        ..info(action, "", description)
+    }
+}
+
+impl ActionInfo {
+    const fn movement(mut self) -> Self {
+        self.movement = true;
+        self
+    }
+
+    const fn when(mut self, available: fn(&RuleSet) -> bool) -> Self {
+        self.available = available;
+        self
+    }
+
+    /// The keys bound to it in `mode` before the player rebinds anything.
+    pub fn default_keys(&self, mode: Mode) -> impl Iterator<Item = KeyCode> + '_ {
+        mode.bindings()
+            .filter(|binding| discriminant(&binding.action) == discriminant(&self.action))
+            .map(|binding| binding.key)
+    }
+}
+
+fn always(_: &RuleSet) -> bool {
+    true
+}
+
+const ACTIONS: &[ActionInfo] = &[
+    info(Action::Quit, "quit", "quit"),
+    info(Action::MoveUp, "move_up", "move up").movement(),
+    info(Action::MoveDown, "move_down", "move down").movement(),
+    info(Action::MoveLeft, "move_left", "move left").movement(),
+    info(Action::MoveRight, "move_right", "move right").movement(),
+    info(Action::NextEmpty, "next_empty", "next empty cell"),
+    info(
+        Action::PreviousEmpty,
+        "previous_empty",
+        "previous empty cell",
+    ),
+    info(Action::NextConflict, "next_conflict", "next conflict")
+        .when(|rules| rules.conflict_highlighting),
+    info(Action::JumpBack, "jump_back", "jump back"),
+    info(
+        Action::ToggleSelect,
+        "toggle_select",
+        "add cell to selection",
+    ),
+    unnamed(Action::Write(1), "write number"),
+    info(Action::Erase, "erase", "erase cell"),
+    info(Action::Undo, "undo", "undo").when(|rules| rules.undo),
+    info(Action::Guess, "guess", "mark a guess").when(|rules| rules.undo),
+    info(Action::Rollback, "rollback", "back to the last guess").when(|rules| rules.undo),
+    info(Action::ToggleNotes, "toggle_notes", "toggle notes mode"),
+    unnamed(Action::ToggleNote(1), "toggle note"),
+    info(
+        Action::ToggleCenterNotes,
+        "toggle_center_notes",
+        "toggle center notes mode",
+    ),
+    unnamed(Action::ToggleCenter(1), "toggle center note"),
+    info(Action::ClearNotes, "clear_notes", "clear notes"),
+    info(Action::Check, "check", "check if solvable").when(|rules| rules.conflict_highlighting),
+    info(
+        Action::ToggleAutoCheck,
+        "toggle_auto_check",
+        "toggle auto check",
+    )
+    .when(|rules| rules.conflict_highlighting),
+    info(
+        Action::ToggleRapidEntry,
+        "toggle_rapid_entry",
+        "toggle rapid entry",
+    ),
+    info(
+        Action::ToggleConflicts,
+        "toggle_conflicts",
+        "show/hide conflicts",
+    )
+    .when(|rules| rules.conflict_highlighting),
+    info(Action::Solve, "solve", "solve if possible").when(|rules| rules.hints),
+    info(Action::Reveal, "reveal", "reveal cell").when(|rules| rules.hints),
+    info(Action::Preview, "preview", "peek at solution").when(|rules| rules.hints),
+    info(Action::Hint, "hint", "explain next step").when(|rules| rules.hints),
+    info(Action::Submit, "submit", "submit answer").when(|rules| rules.submit),
+    info(Action::CycleHighlight, "cycle_highlight", "color the cell"),
+    info(
+        Action::ClearHighlights,
+        "clear_highlights",
+        "clear all colors",
+    ),
+    info(Action::BugReport, "bug_report", "write a bug report"),
+    info(Action::Replay, "replay", "replay the finished game"),
+    info(Action::ReplayFaster, "replay_faster", "replay faster"),
+    info(Action::ReplaySlower, "replay_slower", "replay slower"),
+    info(
+        Action::PauseReplay,
+        "pause_replay",
+        "pause/resume the replay",
+    ),
+    info(Action::Clear, "clear", "clear all cells"),
+    info(Action::NewGame, "new_game", "new puzzle"),
+    info(Action::Carve, "carve", "carve puzzle from grid"),
+    info(Action::NextTheme, "next_theme", "next theme"),
+    info(Action::PreviousTheme, "previous_theme", "previous theme"),
+    info(Action::ToggleHelp, "toggle_help", "toggle help"),
+    info(Action::Search, "search", "search"),
+    info(Action::NextMatch, "next_match", "next match"),
+    info(Action::PreviousMatch, "previous_match", "previous match"),
+    info(Action::ScrollUp, "scroll_up", "scroll up").movement(),
+    info(Action::ScrollDown, "scroll_down", "scroll down").movement(),
+    info(Action::Accept, "accept", "yes"),
+    info(Action::Decline, "decline", "no"),
+    info(Action::Discard, "discard", "quit without saving"),
+    info(Action::CommandLine, "command_line", "command line"),
+    info(
+        Action::WatchSolve,
+        "watch_solve",
+        "solve one cell at a time",
+    ),
+    info(Action::TogglePause, "toggle_pause", "pause/resume"),
+    info(
+        Action::PlayGenerated,
+        "play_generated",
+        "play generated puzzle",
+    ),
+    info(Action::ToggleCoach, "toggle_coach", "toggle coach").when(|rules| rules.hints),
+    info(Action::CoachReport, "coach_report", "coach's analysis").when(|rules| rules.hints),
+    info(Action::ToggleHud, "toggle_hud", "toggle stats panel"),
+    info(Action::EditNote, "edit_note", "note on this puzzle"),
+    info(Action::Export, "export", "export board"),
+    info(Action::ToggleLibrary, "toggle_library", "puzzle library"),
+    info(Action::PlaySelected, "play_selected", "play puzzle"),
+    info(Action::ToggleMark, "toggle_mark", "mark puzzle"),
+    info(Action::TagMarked, "tag_marked", "tag marked puzzles"),
+    info(Action::ToggleArchive, "toggle_archive", "show archive"),
+    info(
+        Action::ArchiveMarked,
+        "archive_marked",
+        "archive/restore marked",
+    ),
+    info(
+        Action::PurgeMarked,
+        "purge_marked",
+        "purge archived for good",
+    ),
+    info(
+        Action::ImportCollection,
+        "import_collection",
+        "import collection",
+    ),
+    info(Action::Retry, "retry", "retry"),
+    info(Action::Continue, "continue", "continue anyway"),
+    info(Action::ToggleThemes, "toggle_themes", "theme picker"),
+    info(Action::ApplyTheme, "apply_theme", "use theme"),
+    info(Action::MainMenu, "main_menu", "main menu"),
+    info(Action::ToggleRace, "toggle_race", "race the bot"),
+    info(Action::EditKeys, "edit_keys", "edit key bindings"),
+    info(Action::Rebind, "rebind", "rebind"),
+    info(Action::ResetBinding, "reset_binding", "reset to default"),
+    info(Action::NextPreset, "next_preset", "next key preset"),
+];
+
+/// Lookup into the table of [`ActionInfo`]s.
+pub struct ActionRegistry;
+
+impl ActionRegistry {
+    pub fn all() -> &'static [ActionInfo] {
+        ACTIONS
+    }
+
+    pub fn get(action: Action) -> &'static ActionInfo {
+        ACTIONS
+            .iter()
+            .find(|info| discriminant(&info.action) == discriminant(&action))
+            .expect("every action is registered")
+    }
+
+    pub fn by_id(id: &str) -> Option<&'static ActionInfo> {
+        ACTIONS.iter().find(|info| info.id == Some(id))
+    }
+
+    /// Actions with a key in `mode` that the rules allow, in table order.
+    pub fn available(mode: Mode, rules: &RuleSet) -> impl Iterator<Item = &'static ActionInfo> {
+        ACTIONS
+            .iter()
+            .filter(move |info| (info.available)(rules) && info.default_keys(mode).next().is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One of every action. The match has no catch-all, so adding an
+    /// action doesn't compile until it's listed here too.
+    fn every_action() -> Vec<Action> {
+        let all = vec![
+            Action::Quit,
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::MoveLeft,
+            Action::MoveRight,
+            Action::NextEmpty,
+            Action::PreviousEmpty,
+            Action::NextConflict,
+            Action::JumpBack,
+            Action::ToggleSelect,
+            Action::Write(1),
+            Action::Erase,
+            Action::ToggleNotes,
+            Action::ToggleNote(1),
+            Action::ToggleCenterNotes,
+            Action::ToggleCenter(1),
+            Action::ClearNotes,
+            Action::Check,
+            Action::ToggleAutoCheck,
+            Action::ToggleRapidEntry,
+            Action::Solve,
+            Action::Reveal,
+            Action::Clear,
+            Action::NewGame,
+            Action::Carve,
+            Action::ToggleHud,
+            Action::ToggleConflicts,
+            Action::NextTheme,
+            Action::PreviousTheme,
+            Action::ToggleHelp,
+            Action::Search,
+            Action::NextMatch,
+            Action::PreviousMatch,
+            Action::ScrollUp,
+            Action::ScrollDown,
+            Action::Accept,
+            Action::Decline,
+            Action::WatchSolve,
+            Action::Discard,
+            Action::CommandLine,
+            Action::TogglePause,
+            Action::PlayGenerated,
+            Action::ToggleCoach,
+            Action::CoachReport,
+            Action::Undo,
+            Action::Guess,
+            Action::Rollback,
+            Action::Preview,
+            Action::Hint,
+            Action::Submit,
+            Action::CycleHighlight,
+            Action::ClearHighlights,
+            Action::BugReport,
+            Action::Replay,
+            Action::ReplayFaster,
+            Action::ReplaySlower,
+            Action::PauseReplay,
+            Action::EditNote,
+            Action::Export,
+            Action::ToggleLibrary,
+            Action::PlaySelected,
+            Action::ToggleMark,
+            Action::TagMarked,
+            Action::ToggleArchive,
+            Action::ArchiveMarked,
+            Action::PurgeMarked,
+            Action::ImportCollection,
+            Action::Retry,
+            Action::Continue,
+            Action::ToggleThemes,
+            Action::ApplyTheme,
+            Action::MainMenu,
+            Action::ToggleRace,
+            Action::EditKeys,
+            Action::Rebind,
+            Action::ResetBinding,
+            Action::NextPreset,
+        ];
+        for action in &all {
+            match action {
+                Action::Quit
+                | Action::MoveUp
+                | Action::MoveDown
+                | Action::MoveLeft
+                | Action::MoveRight
+                | Action::NextEmpty
+                | Action::PreviousEmpty
+                | Action::NextConflict
+                | Action::JumpBack
+                | Action::ToggleSelect
+                | Action::Write(_)
+                | Action::Erase
+                | Action::ToggleNotes
+                | Action::ToggleNote(_)
+                | Action::ToggleCenterNotes
+                | Action::ToggleCenter(_)
+                | Action::ClearNotes
+                | Action::Check
+                | Action::ToggleAutoCheck
+                | Action::ToggleRapidEntry
+                | Action::Solve
+                | Action::Reveal
+                | Action::Clear
+                | Action::NewGame
+                | Action::Carve
+                | Action::ToggleHud
+                | Action::ToggleConflicts
+                | Action::NextTheme
+                | Action::PreviousTheme
+                | Action::ToggleHelp
+                | Action::Search
+                | Action::NextMatch
+                | Action::PreviousMatch
+                | Action::ScrollUp
+                | Action::ScrollDown
+                | Action::Accept
+                | Action::Decline
+                | Action::WatchSolve
+                | Action::Discard
+                | Action::CommandLine
+                | Action::TogglePause
+                | Action::PlayGenerated
+                | Action::ToggleCoach
+                | Action::CoachReport
+                | Action::Undo
+                | Action::Guess
+                | Action::Rollback
+                | Action::Preview
+                | Action::Hint
+                | Action::Submit
+                | Action::CycleHighlight
+                | Action::ClearHighlights
+                | Action::BugReport
+                | Action::Replay
+                | Action::ReplayFaster
+                | Action::ReplaySlower
+                | Action::PauseReplay
+                | Action::EditNote
+                | Action::Export
+                | Action::ToggleLibrary
+                | Action::PlaySelected
+                | Action::ToggleMark
+                | Action::TagMarked
+                | Action::ToggleArchive
+                | Action::ArchiveMarked
+                | Action::PurgeMarked
+                | Action::ImportCollection
+                | Action::Retry
+                | Action::Continue
+                | Action::ToggleThemes
+                | Action::ApplyTheme
+                | Action::MainMenu
+                | Action::ToggleRace
+                | Action::EditKeys
+                | Action::Rebind
+                | Action::ResetBinding
+                | Action::NextPreset => {}
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn every_action_is_registered_once() {
+        let all = every_action();
+        assert_eq!(ACTIONS.len(), all.len());
+        for action in all {
+            assert_eq!(
+                discriminant(&ActionRegistry::get(action).action),
+                discriminant(&action)
+            );
+        }
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        for info in ACTIONS {
+            if let Some(id) = info.id {
+                let found = ActionRegistry::by_id(id).map(|found| found.action);
+                assert_eq!(
+                    found.map(|a| discriminant(&a)),
+                    Some(discriminant(&info.action))
+                );
+            }
+        }
+    }
+}
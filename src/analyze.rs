@@ -0,0 +1,217 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Instant,
+};
+
+use clap::ValueEnum;
+use color_eyre::eyre::WrapErr;
+use serde::Serialize;
+
+use crate::{
+    rating::{self, Grade},
+    soduko::BoardState,
+};
+
+const BAR_WIDTH: usize = 50;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PuzzleReport {
+    /// line number in the input file
+    pub line: usize,
+    pub clues: usize,
+    pub solvable: bool,
+    /// search nodes plain backtracking needed
+    pub nodes: u64,
+    /// the [`rating`] of those nodes
+    pub node_rating: u32,
+    /// the hardest technique solving it by logic takes, guessing when the
+    /// hints don't get all the way, none without exactly one solution
+    pub rating: Option<Grade>,
+    /// steps each technique took, by name
+    pub techniques: BTreeMap<&'static str, usize>,
+    /// how long the game's own solver took
+    pub solve_ms: f64,
+}
+
+/// log2 of the search nodes, a rough score of how hard a puzzle is for
+/// the solver, which isn't how hard it is for a person.
+pub fn rating(nodes: u64) -> u32 {
+    nodes.max(1).ilog2()
+}
+
+/// The [`rating`] of `board`, at most `max + 1`, so telling that a puzzle
+/// is rated past `max` doesn't take the solver long.
+pub fn rating_up_to(board: &BoardState, max: u32) -> u32 {
+    let cap = 1u64.checked_shl(max.saturating_add(1)).unwrap_or(u64::MAX);
+    rating(board.nodes_up_to(cap))
+}
+
+fn analyze_one(line: usize, board: BoardState) -> PuzzleReport {
+    let started = Instant::now();
+    let solvable = board.solve().is_some();
+    let solve_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let mut nodes = 0;
+    board.solve_counting(&mut nodes);
+    let breakdown = rating::breakdown(&board);
+    PuzzleReport {
+        line,
+        clues: board.clue_count(),
+        solvable,
+        nodes,
+        node_rating: rating(nodes),
+        rating: breakdown
+            .as_ref()
+            .map(|(used, solved)| rating::grade(used, *solved)),
+        techniques: breakdown
+            .into_iter()
+            .flat_map(|(used, _)| used)
+            .map(|(technique, steps)| (technique.name(), steps))
+            .collect(),
+        solve_ms,
+    }
+}
+
+/// Analyzes every puzzle on all cores. Workers pull the next unclaimed
+/// puzzle from a shared counter, so one slow puzzle never holds up a batch.
+pub fn analyze_all(puzzles: &[(usize, BoardState)]) -> Vec<PuzzleReport> {
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut reports: Vec<PuzzleReport> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(&(line, board)) = puzzles.get(i) else {
+                            break done;
+                        };
+                        done.push(analyze_one(line, board));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("analyze worker panicked"))
+            .collect()
+    });
+    reports.sort_by_key(|report| report.line);
+    reports
+}
+
+/// Puzzles that parsed and lines that didn't, each tagged with its line number.
+type Parsed = (Vec<(usize, BoardState)>, Vec<(usize, String)>);
+
+/// One puzzle per line, blank lines and `#` comments skipped.
+/// Lines that don't parse are returned separately as (line, error).
+pub fn read_puzzles(text: &str) -> Parsed {
+    let mut puzzles = Vec::new();
+    let mut rejected = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.parse() {
+            Ok(board) => puzzles.push((i + 1, board)),
+            Err(err) => rejected.push((i + 1, format!("{err}"))),
+        }
+    }
+    (puzzles, rejected)
+}
+
+fn to_csv(reports: &[PuzzleReport]) -> String {
+    let mut csv =
+        String::from("line,clues,solvable,nodes,node_rating,rating,techniques,solve_ms\n");
+    for r in reports {
+        let techniques: Vec<String> = r
+            .techniques
+            .iter()
+            .map(|(name, steps)| format!("{name}:{steps}"))
+            .collect();
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{:.3}",
+            r.line,
+            r.clues,
+            r.solvable,
+            r.nodes,
+            r.node_rating,
+            r.rating.map_or("", Grade::label),
+            techniques.join(";"),
+            r.solve_ms
+        );
+    }
+    csv
+}
+
+pub fn histogram(reports: &[PuzzleReport]) -> String {
+    let mut counts: BTreeMap<Grade, usize> = BTreeMap::new();
+    for rating in reports.iter().filter_map(|r| r.rating) {
+        *counts.entry(rating).or_default() += 1;
+    }
+    let max = counts.values().copied().max().unwrap_or(0).max(1);
+    let mut out = String::from("rating\n");
+    for (rating, count) in counts {
+        let bar = "█".repeat((count * BAR_WIDTH).div_ceil(max));
+        let _ = writeln!(out, "{:>8} | {bar} {count}", rating.label());
+    }
+    out
+}
+
+/// `rudoku analyze`: writes the report next to the input unless an output
+/// path is given, and prints a histogram of the ratings.
+pub fn run(path: &Path, format: ReportFormat, output: Option<PathBuf>) -> crate::Result {
+    let text = fs::read_to_string(path).wrap_err_with(|| format!("reading {}", path.display()))?;
+    let (puzzles, rejected) = read_puzzles(&text);
+    for (line, err) in &rejected {
+        eprintln!("{}:{line}: skipped, {err}", path.display());
+    }
+
+    let started = Instant::now();
+    let reports = analyze_all(&puzzles);
+    let elapsed = started.elapsed();
+
+    let report = match format {
+        ReportFormat::Csv => to_csv(&reports),
+        ReportFormat::Json => serde_json::to_string_pretty(&reports)?,
+    };
+    let output =
+        output.unwrap_or_else(|| path.with_extension(format!("report.{}", format.extension())));
+    fs::write(&output, report).wrap_err_with(|| format!("writing {}", output.display()))?;
+
+    let unsolvable = reports.iter().filter(|r| !r.solvable).count();
+    println!(
+        "analyzed {} puzzles in {:.2}s ({} unsolvable, {} skipped)",
+        reports.len(),
+        elapsed.as_secs_f64(),
+        unsolvable,
+        rejected.len()
+    );
+    println!("report written to {}", output.display());
+    println!();
+    print!("{}", histogram(&reports));
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use std::time::{Duration, Instant};
+
+use ratatui::style::Color;
+
+use crate::{
+    color,
+    soduko::{BoardState, CellState},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseInOut,
+    /// up and back down again
+    Pulse,
+}
+
+impl Easing {
+    /// Strength of the effect at progress `t` in 0.0..=1.0.
+    fn intensity(self, t: f32) -> f32 {
+        match self {
+            // effects start at full strength and fade out
+            Easing::Linear => 1.0 - t,
+            Easing::EaseInOut => 1.0 - color::ease_in_out(t),
+            Easing::Pulse => 1.0 - (2.0 * t - 1.0).abs(),
+        }
+    }
+}
+
+/// A transient tint on one cell's background.
+#[derive(Debug, Clone, Copy)]
+pub struct Effect {
+    pub row: usize,
+    pub col: usize,
+    pub start: Instant,
+    pub duration: Duration,
+    pub easing: Easing,
+    pub color: Color,
+}
+
+impl Effect {
+    pub fn new(row: usize, col: usize, duration: Duration, easing: Easing, color: Color) -> Self {
+        Self {
+            row,
+            col,
+            start: Instant::now(),
+            duration,
+            easing,
+            color,
+        }
+    }
+
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.start += delay;
+        self
+    }
+
+    fn progress(&self, now: Instant) -> Option<f32> {
+        let elapsed = now.checked_duration_since(self.start)?;
+        Some(elapsed.as_secs_f32() / self.duration.as_secs_f32())
+    }
+
+    fn finished(&self, now: Instant) -> bool {
+        self.progress(now).is_some_and(|t| t >= 1.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct Animations {
+    effects: Vec<Effect>,
+    pub enabled: bool,
+}
+
+impl Animations {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            effects: Vec::new(),
+            enabled,
+        }
+    }
+
+    pub fn push(&mut self, effect: Effect) {
+        if self.enabled {
+            self.effects.push(effect);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.effects.is_empty()
+    }
+
+    /// Drops finished effects, called once per frame.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        self.effects.retain(|effect| !effect.finished(now));
+    }
+
+    /// `base` blended with every running effect on the cell.
+    pub fn bg(&self, row: usize, col: usize, base: Color) -> Color {
+        let now = Instant::now();
+        self.effects
+            .iter()
+            .filter(|effect| effect.row == row && effect.col == col)
+            .fold(base, |bg, effect| match effect.progress(now) {
+                Some(t) if t < 1.0 => color::lerp(bg, effect.color, effect.easing.intensity(t)),
+                _ => bg,
+            })
+    }
+}
+
+/// A solution written into the board one cell at a time, in reading order,
+/// to watch it unfold. Dropping it stops it where it is.
+#[derive(Debug)]
+pub struct Unfold {
+    /// cells still to write with what goes in them, the next one last
+    cells: Vec<(usize, usize, CellState)>,
+    interval: Duration,
+    due: Instant,
+}
+
+impl Unfold {
+    /// Fills the empty cells of `board` from `solution`, one every
+    /// `interval`.
+    pub fn new(board: &BoardState, solution: &BoardState, interval: Duration) -> Self {
+        let mut cells: Vec<_> = (0..81)
+            .map(|i| (i / 9, i % 9))
+            .filter(|&(r, col)| board[r][col].is_none())
+            .map(|(r, col)| (r, col, solution[r][col]))
+            .collect();
+        cells.reverse();
+        Self {
+            cells,
+            interval,
+            due: Instant::now(),
+        }
+    }
+
+    /// The next cell to write, once it's due.
+    pub fn step(&mut self) -> Option<(usize, usize, CellState)> {
+        if Instant::now() < self.due {
+            return None;
+        }
+        self.due += self.interval;
+        self.cells.pop()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
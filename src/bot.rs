@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::{
+    hints::{self, Technique},
+    rng::Rng,
+    soduko::BoardState,
+};
+
+/// Seconds into a game by which each tenth of the empty cells was filled,
+/// the player's progress curve.
+pub type Curve = [u32; 10];
+
+/// Average effort of a placement, for turning the player's time per cell
+/// into the bot's time per naked single.
+const AVERAGE_EFFORT: f64 = 1.2;
+
+/// How good the bot racing the player is, the `bot` config option. The
+/// last three go by the player's own past games, once there are some.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Skill {
+    Beginner,
+    #[default]
+    Club,
+    Expert,
+    Master,
+    /// the player's own pace
+    Even,
+    /// a little ahead of the player
+    Faster,
+    /// a little behind the player
+    Slower,
+}
+
+/// How quickly the bot places.
+#[derive(Debug, Clone, Copy)]
+struct Pace {
+    /// seconds for a naked single, in each tenth of the puzzle
+    seconds: [f64; 10],
+    /// one in how many placements the bot stops to think first, never when
+    /// the pace comes from the player's games, which have the thinking in
+    thinks_every: Option<usize>,
+}
+
+impl Skill {
+    pub fn label(self) -> &'static str {
+        match self {
+            Skill::Beginner => "beginner",
+            Skill::Club => "club",
+            Skill::Expert => "expert",
+            Skill::Master => "master",
+            Skill::Even => "matched",
+            Skill::Faster => "faster",
+            Skill::Slower => "slower",
+        }
+    }
+
+    /// The pace for a puzzle with `to_fill` empty cells, following `curve`
+    /// for the skills that go by the player. Those play at club pace until
+    /// the player has a curve.
+    fn pace(self, curve: Option<Curve>, to_fill: usize) -> Pace {
+        let fixed = |seconds, thinks_every| Pace {
+            seconds: [seconds; 10],
+            thinks_every: Some(thinks_every),
+        };
+        let factor = match self {
+            Skill::Beginner => return fixed(20.0, 5),
+            Skill::Club => return fixed(10.0, 8),
+            Skill::Expert => return fixed(5.0, 12),
+            Skill::Master => return fixed(2.5, 20),
+            Skill::Even => 1.0,
+            Skill::Faster => 0.9,
+            Skill::Slower => 1.1,
+        };
+        let Some(curve) = curve else {
+            return Skill::Club.pace(None, to_fill);
+        };
+        let cells = (to_fill as f64 / 10.0).max(1.0);
+        let mut seconds = [0.0; 10];
+        let mut before = 0;
+        for (tenth, &at) in seconds.iter_mut().zip(&curve) {
+            *tenth = at.saturating_sub(before) as f64 / cells / AVERAGE_EFFORT * factor;
+            before = at;
+        }
+        Pace {
+            seconds,
+            thinks_every: None,
+        }
+    }
+}
+
+/// A solver racing the player through the same puzzle on a board of its
+/// own, one logical step at a time at a person's pace. It goes by game
+/// time, so it waits while the game is paused.
+#[derive(Debug, Clone)]
+pub struct Bot {
+    skill: Skill,
+    pace: Pace,
+    board: BoardState,
+    /// for when logic runs out and a person would start guessing
+    solution: Option<BoardState>,
+    rng: Rng,
+    /// the placement being worked out and the game time it lands at
+    next: Option<((usize, usize, u8), Duration)>,
+    to_fill: usize,
+    finished: Option<Duration>,
+}
+
+impl Bot {
+    /// Starts on `puzzle` at game time `now`, at the pace of `curve` if the
+    /// skill goes by the player.
+    pub fn new(
+        puzzle: BoardState,
+        skill: Skill,
+        curve: Option<Curve>,
+        rng: Rng,
+        now: Duration,
+    ) -> Self {
+        let to_fill = 81 - puzzle.clue_count();
+        let mut bot = Self {
+            skill,
+            pace: skill.pace(curve, to_fill),
+            board: puzzle,
+            solution: puzzle.solve(),
+            rng,
+            next: None,
+            to_fill,
+            finished: None,
+        };
+        bot.plan(now);
+        bot
+    }
+
+    pub fn skill(&self) -> Skill {
+        self.skill
+    }
+
+    /// Cells filled, out of how many the puzzle left empty.
+    pub fn progress(&self) -> (usize, usize) {
+        let left = (0..81)
+            .filter(|&pos| self.board[pos / 9][pos % 9].is_none())
+            .count();
+        (self.to_fill - left, self.to_fill)
+    }
+
+    /// The game time the bot filled its last cell.
+    pub fn finished(&self) -> Option<Duration> {
+        self.finished
+    }
+
+    /// Makes the placements due by game time `now`. `true` when that
+    /// finished the board.
+    pub fn advance(&mut self, now: Duration) -> bool {
+        while let Some(((row, col, digit), due)) = self.next {
+            if due > now {
+                return false;
+            }
+            self.board.set(row as u8, col as u8, digit.into());
+            self.next = None;
+            self.plan(due);
+        }
+        if self.finished.is_none() && self.progress().0 == self.to_fill {
+            self.finished = Some(now);
+            return true;
+        }
+        false
+    }
+
+    /// Picks the next placement and how long it takes from `from`: longer
+    /// for harder techniques and eliminations needed first, with some
+    /// jitter, now and then a pause to think, and a long one when logic
+    /// runs out and the bot has to try things.
+    fn plan(&mut self, from: Duration) {
+        let (placement, effort) = match hints::next_step(&self.board) {
+            Some(step) => {
+                let effort = match step.technique {
+                    Technique::NakedSingle => 1.0,
+                    Technique::HiddenSingle => 1.3,
+                    _ => 2.0,
+                } + 1.5 * step.after.len() as f64;
+                ((step.row, step.col, step.digit), effort)
+            }
+            None => {
+                let Some(solution) = &self.solution else {
+                    return;
+                };
+                let Some(pos) = (0..81).find(|&pos| self.board[pos / 9][pos % 9].is_none()) else {
+                    return;
+                };
+                let (row, col) = (pos / 9, pos % 9);
+                let digit = solution[row][col].map_or(0, |d| d.get());
+                ((row, col, digit), 6.0)
+            }
+        };
+        let (filled, _) = self.progress();
+        let pace = self.pace.seconds[(filled * 10 / self.to_fill.max(1)).min(9)];
+        let jitter = 0.5 + self.rng.below(101) as f64 / 100.0;
+        let mut seconds = pace * effort * jitter;
+        if let Some(every) = self.pace.thinks_every
+            && self.rng.below(every) == 0
+        {
+            seconds += pace * (2 + self.rng.below(3)) as f64;
+        }
+        self.next = Some((placement, from + Duration::from_secs_f64(seconds)));
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use serde::Deserialize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    notes::{self, NoteLayout},
+    soduko::CellState,
+    symbols::Symbols,
+};
+
+/// How a cell's value is drawn inside its `width` x `height` box.
+pub trait CellRenderer {
+    /// Exactly `height` lines, each `width` columns wide.
+    fn render(&self, cell: CellState, width: usize, height: usize) -> Vec<String>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigitStyle {
+    /// pick from the cell size
+    #[default]
+    Auto,
+    Compact,
+    Normal,
+    Large,
+}
+
+impl DigitStyle {
+    pub fn renderer<'a>(
+        self,
+        glyphs: &'a Glyphs,
+        digits: &'static [[&'static str; 3]; 10],
+        symbols: &'a Symbols,
+        width: u16,
+        height: u16,
+    ) -> Box<dyn CellRenderer + 'a> {
+        let style = match self {
+            DigitStyle::Auto if width >= 4 && height >= 3 => DigitStyle::Large,
+            DigitStyle::Auto if width < 3 => DigitStyle::Compact,
+            DigitStyle::Auto => DigitStyle::Normal,
+            style => style,
+        };
+        match style {
+            DigitStyle::Compact => Box::new(Compact(symbols)),
+            DigitStyle::Large => Box::new(Large(glyphs, digits, symbols)),
+            _ => Box::new(Normal(symbols)),
+        }
+    }
+}
+
+/// The longest start of `text` that is at most `width` columns wide.
+/// Zero width characters stay with the character before them, so accents
+/// and variation selectors are never split off.
+fn fit(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// Pads by display width, so double width symbols stay centered. Text
+/// wider than the cell is cut rather than pushing the box border out.
+fn center(text: &str, width: usize) -> String {
+    let text = fit(text, width);
+    let space = width - text.width().min(width);
+    let left = space / 2;
+    format!("{}{text}{}", " ".repeat(left), " ".repeat(space - left))
+}
+
+fn pad_lines(mut lines: Vec<String>, width: usize, height: usize) -> Vec<String> {
+    let top = height.saturating_sub(lines.len()) / 2;
+    for _ in 0..top {
+        lines.insert(0, String::new());
+    }
+    lines.resize(height, String::new());
+    lines.into_iter().map(|line| center(&line, width)).collect()
+}
+
+/// Symbol on the first line, no padding around it.
+pub struct Compact<'a>(&'a Symbols);
+
+impl CellRenderer for Compact<'_> {
+    fn render(&self, cell: CellState, width: usize, height: usize) -> Vec<String> {
+        let symbol = fit(self.0.cell_within(cell, width), width);
+        let right = width - symbol.width().min(width);
+        let mut lines = vec![format!("{symbol}{}", " ".repeat(right))];
+        lines.resize(height, " ".repeat(width));
+        lines
+    }
+}
+
+/// Symbol centered in the cell.
+pub struct Normal<'a>(&'a Symbols);
+
+impl CellRenderer for Normal<'_> {
+    fn render(&self, cell: CellState, width: usize, height: usize) -> Vec<String> {
+        pad_lines(
+            vec![self.0.cell_within(cell, width).to_string()],
+            width,
+            height,
+        )
+    }
+}
+
+/// 3x3 block glyphs, readable from across the room. Symbols without a glyph
+/// are drawn like [`Normal`].
+pub struct Large<'a>(&'a Glyphs, &'static [[&'static str; 3]; 10], &'a Symbols);
+
+impl CellRenderer for Large<'_> {
+    fn render(&self, cell: CellState, width: usize, height: usize) -> Vec<String> {
+        let key = match *cell {
+            Some(d) => self.2.symbol(d.get()),
+            None => "0",
+        };
+        match self.0.glyph(key, self.1) {
+            Some(glyph) => pad_lines(glyph.map(str::to_string).to_vec(), width, height),
+            None => Normal(self.2).render(cell, width, height),
+        }
+    }
+}
+
+/// The pencil marks of one cell, as digit bitmasks.
+pub struct Marks {
+    pub corner: u16,
+    pub center: u16,
+    /// marks of either kind drawn in the flag style
+    pub flagged: u16,
+}
+
+/// Pencil marks, corner marks laid out as `layout` asks and center marks on
+/// the middle line, when the cell fits three of the widest symbol across
+/// and three lines down. Smaller cells get one row of all the marks cut to
+/// the cell width. Flagged values get `flag_style`.
+pub fn render_notes(
+    marks: Marks,
+    layout: NoteLayout,
+    symbols: &Symbols,
+    width: usize,
+    height: usize,
+    style: Style,
+    flag_style: Style,
+) -> Vec<Line<'static>> {
+    let Marks {
+        corner,
+        center,
+        flagged,
+    } = marks;
+    let span = |d: u8| {
+        let style = match flagged & (1 << d) != 0 {
+            true => flag_style,
+            false => style,
+        };
+        Span::styled(symbols.symbol(d).to_string(), style)
+    };
+    let padded = |spans: Vec<Span<'static>>| {
+        let used: usize = spans.iter().map(Span::width).sum();
+        let left = width.saturating_sub(used) / 2;
+        let right = width.saturating_sub(used + left);
+        let mut line = vec![Span::raw(" ".repeat(left))];
+        line.extend(spans);
+        line.push(Span::raw(" ".repeat(right)));
+        Line::from(line)
+    };
+    let blank = || Line::from(" ".repeat(width));
+    // every slot is as wide as the widest symbol so columns line up
+    let slot = symbols.width();
+    let slotted = |d: Option<u8>| match d {
+        Some(d) => {
+            let mut span = span(d);
+            let pad = slot - span.width();
+            span.content = format!("{}{}", span.content, " ".repeat(pad)).into();
+            span
+        }
+        None => Span::raw(" ".repeat(slot)),
+    };
+    let fits = |used: &mut usize, span: &Span| {
+        *used += span.width();
+        *used <= width
+    };
+    if width < 3 * slot || height < 3 {
+        let mut used = 0;
+        let row: Vec<Span> = notes::digits(corner | center)
+            .map(span)
+            .take_while(|span| fits(&mut used, span))
+            .collect();
+        let top = height.saturating_sub(1) / 2;
+        let mut lines = vec![blank(); top];
+        lines.push(padded(row));
+        lines.resize(height, blank());
+        return lines;
+    }
+    // a keypad has no room left for center marks
+    if layout == NoteLayout::Keypad && center == 0 {
+        let rows: Vec<Line> = (0..3)
+            .map(|row| {
+                let spans = (1..=3)
+                    .map(|c| row * 3 + c)
+                    .map(|d| slotted((corner & (1 << d) != 0).then_some(d)))
+                    .collect();
+                padded(spans)
+            })
+            .collect();
+        let top = (height - 3) / 2;
+        let mut lines = vec![blank(); top];
+        lines.extend(rows);
+        lines.resize(height, blank());
+        return lines;
+    }
+    // corners first, then the middle of each edge
+    let mut slots = [None; 9];
+    for (slot, d) in [0, 2, 6, 8, 1, 7, 3, 5, 4]
+        .into_iter()
+        .zip(notes::digits(corner))
+    {
+        slots[slot] = Some(d);
+    }
+    // a row of three slots spread over the cell width, `middle` in between
+    let spread = |left: Option<u8>, middle: Vec<Span<'static>>, right: Option<u8>| {
+        let used: usize = middle.iter().map(Span::width).sum();
+        let space = width.saturating_sub(2 * slot + used);
+        let mut line = vec![slotted(left), Span::raw(" ".repeat(space / 2))];
+        line.extend(middle);
+        line.push(Span::raw(" ".repeat(space - space / 2)));
+        line.push(slotted(right));
+        Line::from(line)
+    };
+    let middle = match center {
+        0 => vec![slotted(slots[4])],
+        _ => {
+            let mut used = 2 * slot;
+            notes::digits(center)
+                .map(span)
+                .take_while(|span| fits(&mut used, span))
+                .collect()
+        }
+    };
+    let mut lines = vec![blank(); height];
+    lines[0] = spread(slots[0], vec![slotted(slots[1])], slots[2]);
+    lines[height / 2] = spread(slots[3], middle, slots[5]);
+    lines[height - 1] = spread(slots[6], vec![slotted(slots[7])], slots[8]);
+    lines
+}
+
+/// Large glyphs, keyed by symbol with `"0"` for the empty cell. Anything
+/// not overridden in the config falls back to the built in `digits`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Glyphs(BTreeMap<String, [String; 3]>);
+
+impl Glyphs {
+    pub fn glyph<'a>(&'a self, symbol: &str, digits: &[[&'a str; 3]; 10]) -> Option<[&'a str; 3]> {
+        match self.0.get(symbol) {
+            Some([a, b, c]) => Some([a, b, c]),
+            None => symbol
+                .parse::<usize>()
+                .ok()
+                .and_then(|digit| digits.get(digit).copied()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbols::SymbolSet;
+
+    fn widths(lines: &[String]) -> Vec<usize> {
+        lines.iter().map(|line| line.width()).collect()
+    }
+
+    #[test]
+    fn wide_characters_are_never_split() {
+        assert_eq!(fit("一二", 3), "一");
+        assert_eq!(fit("一", 1), "");
+        // the combining accent stays with its letter
+        assert_eq!(fit("e\u{301}x", 1), "e\u{301}");
+        assert_eq!(center("一", 5), " 一  ");
+        assert_eq!(center("一二", 3), "一 ");
+    }
+
+    #[test]
+    fn double_width_symbols_fill_their_cells_exactly() {
+        let symbols = SymbolSet::Kanji.symbols();
+        let cell = CellState::from(4);
+        for (width, height) in [(1, 1), (2, 1), (3, 1), (5, 3)] {
+            for lines in [
+                Compact(&symbols).render(cell, width, height),
+                Normal(&symbols).render(cell, width, height),
+            ] {
+                assert_eq!(widths(&lines), vec![width; height], "{lines:?}");
+            }
+        }
+        // too narrow for 四, so the plain digit stands in
+        assert_eq!(Compact(&symbols).render(cell, 1, 1), ["4"]);
+        assert_eq!(Normal(&symbols).render(cell, 3, 1), ["四 "]);
+    }
+
+    #[test]
+    fn double_width_notes_line_up() {
+        let symbols = SymbolSet::Kanji.symbols();
+        let marks = |corner, center| Marks {
+            corner,
+            center,
+            flagged: 0,
+        };
+        let style = Style::new();
+        for layout in [NoteLayout::Keypad, NoteLayout::Corner] {
+            for (width, height) in [(3, 1), (5, 3), (6, 3), (9, 5)] {
+                for (corner, center) in [(0b11_1111_1110, 0), (0b1010, 0b1_0000)] {
+                    let lines = render_notes(
+                        marks(corner, center),
+                        layout,
+                        &symbols,
+                        width,
+                        height,
+                        style,
+                        style,
+                    );
+                    assert_eq!(lines.len(), height);
+                    for line in &lines {
+                        assert_eq!(line.width(), width, "{layout:?} {width}x{height}");
+                    }
+                }
+            }
+        }
+        let keypad = render_notes(
+            marks(0b10_0010_0010, 0),
+            NoteLayout::Keypad,
+            &symbols,
+            6,
+            3,
+            style,
+            style,
+        );
+        let text: Vec<String> = keypad.iter().map(Line::to_string).collect();
+        assert_eq!(text, ["一    ", "  五  ", "    九"]);
+    }
+}
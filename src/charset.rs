@@ -0,0 +1,188 @@
+use std::env;
+
+use ratatui::{
+    symbols::{border, line, scrollbar},
+    widgets::{Block, BorderType},
+};
+use serde::Deserialize;
+
+/// Which characters the terminal can show, the `charset` config option.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Charset {
+    /// unicode unless the locale or `TERM` says otherwise
+    #[default]
+    Auto,
+    Unicode,
+    /// plain ASCII, for serial consoles and old SSH clients
+    Ascii,
+}
+
+impl Charset {
+    pub fn glyphs(self) -> GlyphSet {
+        match self {
+            Charset::Unicode => GlyphSet::UNICODE,
+            Charset::Ascii => GlyphSet::ASCII,
+            Charset::Auto if unicode_terminal() => GlyphSet::UNICODE,
+            Charset::Auto => GlyphSet::ASCII,
+        }
+    }
+}
+
+/// Terminals that only ever had ASCII, and locales that don't say UTF-8,
+/// which an unset locale over SSH or a serial line usually means. Windows
+/// consoles have no locale variables and handle unicode.
+fn unicode_terminal() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    if matches!(term.as_str(), "dumb" | "vt52" | "vt100" | "vt102" | "vt220") {
+        return false;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()));
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => cfg!(windows),
+    }
+}
+
+const BIG_DIGITS: [[&str; 3]; 10] = [
+    ["   ", " · ", "   "],
+    ["▀█ ", " █ ", "▄█▄"],
+    ["▀▀█", "█▀▀", "▀▀▀"],
+    ["▀▀█", " ▀█", "▀▀▀"],
+    ["█ █", "▀▀█", "  ▀"],
+    ["█▀▀", "▀▀█", "▀▀▀"],
+    ["█▀▀", "█▀█", "▀▀▀"],
+    ["▀▀█", "  █", "  ▀"],
+    ["█▀█", "█▀█", "▀▀▀"],
+    ["█▀█", "▀▀█", "▀▀▀"],
+];
+
+/// Seven segment digits as calculators drew them in ASCII.
+const ASCII_DIGITS: [[&str; 3]; 10] = [
+    ["   ", " . ", "   "],
+    ["   ", "  |", "  |"],
+    [" _ ", " _|", "|_ "],
+    [" _ ", " _|", " _|"],
+    ["   ", "|_|", "  |"],
+    [" _ ", "|_ ", " _|"],
+    [" _ ", "|_ ", "|_|"],
+    [" _ ", "  |", "  |"],
+    [" _ ", "|_|", "|_|"],
+    [" _ ", "|_|", " _|"],
+];
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// Popups keep standing out from the rest with `=` for their double lines.
+const ASCII_DOUBLE_BORDER: border::Set = border::Set {
+    horizontal_top: "=",
+    horizontal_bottom: "=",
+    ..ASCII_BORDER
+};
+
+const ASCII_LINE: line::Set = line::Set {
+    vertical: "|",
+    horizontal: "-",
+    top_right: "+",
+    top_left: "+",
+    bottom_right: "+",
+    bottom_left: "+",
+    vertical_left: "+",
+    vertical_right: "+",
+    horizontal_down: "+",
+    horizontal_up: "+",
+    cross: "+",
+};
+
+/// Every character the app draws beyond what symbols and text bring, in a
+/// unicode and an ASCII set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphSet {
+    ascii: bool,
+    /// an empty cell
+    pub dot: &'static str,
+    /// between the parts of the status line
+    pub separator: &'static str,
+    /// the arrow keys: up, down, left, right
+    pub arrows: [&'static str; 4],
+    /// the large digits, the empty cell first
+    pub digits: &'static [[&'static str; 3]; 10],
+}
+
+impl GlyphSet {
+    pub const UNICODE: Self = Self {
+        ascii: false,
+        dot: "·",
+        separator: "│",
+        arrows: ["↑", "↓", "←", "→"],
+        digits: &BIG_DIGITS,
+    };
+
+    pub const ASCII: Self = Self {
+        ascii: true,
+        dot: ".",
+        separator: "|",
+        arrows: ["Up", "Down", "Left", "Right"],
+        digits: &ASCII_DIGITS,
+    };
+
+    pub fn is_ascii(&self) -> bool {
+        self.ascii
+    }
+
+    /// One of two characters for something only drawn in one place.
+    pub fn pick(&self, unicode: &'static str, ascii: &'static str) -> &'static str {
+        match self.ascii {
+            true => ascii,
+            false => unicode,
+        }
+    }
+
+    pub fn border(&self, kind: BorderType) -> border::Set<'static> {
+        match (self.ascii, kind) {
+            (false, kind) => kind.to_border_set(),
+            (true, BorderType::Double | BorderType::Thick) => ASCII_DOUBLE_BORDER,
+            (true, _) => ASCII_BORDER,
+        }
+    }
+
+    /// Lines drawn through the middle of something, like the dividers
+    /// between the boxes of the board.
+    pub fn line(&self) -> line::Set<'static> {
+        match self.ascii {
+            true => ASCII_LINE,
+            false => line::NORMAL,
+        }
+    }
+
+    pub fn scrollbar(&self) -> scrollbar::Set<'static> {
+        match self.ascii {
+            true => scrollbar::Set {
+                track: "|",
+                thumb: "#",
+                begin: "^",
+                end: "v",
+            },
+            false => scrollbar::VERTICAL,
+        }
+    }
+
+    /// A block bordered all around with `kind` lines.
+    pub fn block(&self, kind: BorderType) -> Block<'static> {
+        Block::bordered().border_set(self.border(kind))
+    }
+}
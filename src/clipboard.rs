@@ -0,0 +1,43 @@
+use color_eyre::eyre::Result;
+
+/// The system clipboard, opened on first use. It's kept for as long as the
+/// app runs since on X11 and Wayland copied text only stays available while
+/// its owner is around.
+#[derive(Default)]
+pub struct Clipboard {
+    #[cfg(not(target_arch = "wasm32"))]
+    inner: Option<arboard::Clipboard>,
+    /// a remote player's, the system clipboard is the host's
+    disabled: bool,
+}
+
+impl Clipboard {
+    pub fn disabled() -> Self {
+        Self {
+            disabled: true,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clipboard {
+    pub fn copy(&mut self, text: String) -> Result<()> {
+        if self.disabled {
+            color_eyre::eyre::bail!("no clipboard in remote games");
+        }
+        let clipboard = match &mut self.inner {
+            Some(clipboard) => clipboard,
+            None => self.inner.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Clipboard {
+    pub fn copy(&mut self, _text: String) -> Result<()> {
+        color_eyre::eyre::bail!("no clipboard in this build")
+    }
+}
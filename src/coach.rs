@@ -0,0 +1,134 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{charset::GlyphSet, hints, soduko::BoardState};
+
+/// How a placement could have been found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveQuality {
+    /// the only value left for the cell, or the only cell left for the value
+    /// in a house
+    Forced,
+    /// follows from filling in forced values one after another
+    Logical,
+    /// right, but nothing on the board pointed to it yet
+    Guess,
+    /// leaves the board without a solution
+    Wrong,
+}
+
+impl MoveQuality {
+    pub fn label(self, glyphs: GlyphSet) -> String {
+        let (mark, word) = match self {
+            MoveQuality::Forced => (glyphs.pick("●", "*"), "forced"),
+            MoveQuality::Logical => (glyphs.pick("◐", "~"), "logical"),
+            MoveQuality::Guess => ("?", "guess"),
+            MoveQuality::Wrong => (glyphs.pick("✗", "x"), "wrong"),
+        };
+        format!("{mark} {word}")
+    }
+}
+
+/// Classifies writing `d` at (row, col) on the board as it was before.
+pub fn classify(before: &BoardState, row: usize, col: usize, d: u8) -> MoveQuality {
+    let mut after = *before;
+    after[row][col] = d.into();
+    if !after.solvable() {
+        return MoveQuality::Wrong;
+    }
+    let mut empty = *before;
+    empty[row][col] = 0.into();
+    if is_forced(&empty, row, col, d) {
+        MoveQuality::Forced
+    } else if singles(empty)[row][col].is_some_and(|v| v.get() == d) {
+        MoveQuality::Logical
+    } else {
+        MoveQuality::Guess
+    }
+}
+
+/// A naked single for the cell or a hidden single in one of its houses.
+/// (row, col) has to be empty.
+fn is_forced(board: &BoardState, row: usize, col: usize, d: u8) -> bool {
+    hints::singles(board)
+        .iter()
+        .any(|step| (step.row, step.col, step.digit) == (row, col, d))
+}
+
+/// Fills naked and hidden singles until there are none left.
+fn singles(mut board: BoardState) -> BoardState {
+    loop {
+        let steps = hints::singles(&board);
+        if steps.is_empty() {
+            return board;
+        }
+        for step in steps {
+            if board[step.row][step.col].is_none() {
+                board[step.row][step.col] = step.digit.into();
+            }
+        }
+        if board.is_dead_end() {
+            return board;
+        }
+    }
+}
+
+/// Placements of one game by quality, kept with the game's stats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoachSummary {
+    pub forced: u32,
+    pub logical: u32,
+    pub guesses: u32,
+    pub wrong: u32,
+}
+
+/// A placement as the coach rated it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RatedMove {
+    pub row: usize,
+    pub col: usize,
+    pub digit: u8,
+    pub quality: MoveQuality,
+}
+
+/// The coach's analysis of a game so far, a line per placement under the
+/// totals.
+pub fn analysis(summary: &CoachSummary, moves: &[RatedMove], glyphs: GlyphSet) -> Vec<String> {
+    let mut lines = vec![summary.to_string(), String::new()];
+    if moves.is_empty() {
+        lines.push("no placements rated yet".to_string());
+    }
+    lines.extend(moves.iter().enumerate().map(|(i, m)| {
+        format!(
+            "{:>3}. {} at r{}c{}  {}",
+            i + 1,
+            m.digit,
+            m.row + 1,
+            m.col + 1,
+            m.quality.label(glyphs)
+        )
+    }));
+    lines
+}
+
+impl CoachSummary {
+    pub fn record(&mut self, quality: MoveQuality) {
+        match quality {
+            MoveQuality::Forced => self.forced += 1,
+            MoveQuality::Logical => self.logical += 1,
+            MoveQuality::Guess => self.guesses += 1,
+            MoveQuality::Wrong => self.wrong += 1,
+        }
+    }
+}
+
+impl Display for CoachSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} forced, {} logical, {} guesses, {} wrong",
+            self.forced, self.logical, self.guesses, self.wrong
+        )
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    text::Line,
+    widgets::{Gauge, Paragraph, Widget},
+};
+
+use crate::{daily::Date, formats, soduko::BoardState};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportProgress {
+    /// puzzles in the file, known once it's read
+    pub total: usize,
+    pub accepted: usize,
+    /// lines that aren't a puzzle or don't have exactly one solution
+    pub rejected: usize,
+}
+
+impl ImportProgress {
+    pub fn done(&self) -> usize {
+        self.accepted + self.rejected
+    }
+}
+
+enum ImportEvent {
+    Total(usize),
+    Accepted(String, Option<u64>),
+    Rejected,
+    Failed(String),
+}
+
+pub enum ImportOutcome {
+    /// the accepted puzzles as 81 character codes, with when the event
+    /// of each that came with one ends
+    Done(Vec<(String, Option<u64>)>),
+    Failed(String),
+}
+
+/// A puzzle collection read on its own thread, one 81 character puzzle per
+/// line, keeping the ones with exactly one solution. A puzzle for an event
+/// is followed by `until YYYY-MM-DD`, the last day of the event in UTC.
+/// Dropping this stops it after the puzzle being checked.
+pub struct CollectionImport {
+    pub path: PathBuf,
+    events: Receiver<ImportEvent>,
+    stop: Arc<AtomicBool>,
+    pub progress: ImportProgress,
+    accepted: Vec<(String, Option<u64>)>,
+    read: bool,
+}
+
+impl CollectionImport {
+    pub fn spawn(path: PathBuf) -> Self {
+        let (tx, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let file = path.clone();
+        thread::spawn(move || {
+            let path = file;
+            let text = match fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(err) => {
+                    let _ = tx.send(ImportEvent::Failed(format!(
+                        "reading {}: {err}",
+                        path.display()
+                    )));
+                    return;
+                }
+            };
+            let lines: Vec<&str> = text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .collect();
+            let _ = tx.send(ImportEvent::Total(lines.len()));
+            for line in lines {
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                let event = match parse_line(line) {
+                    Some((board, expires)) if board.check() && board.count_solutions(2) == 1 => {
+                        ImportEvent::Accepted(formats::code(&board), expires)
+                    }
+                    _ => ImportEvent::Rejected,
+                };
+                if tx.send(event).is_err() {
+                    return;
+                }
+            }
+        });
+        Self {
+            path,
+            events,
+            stop,
+            progress: ImportProgress::default(),
+            accepted: Vec::new(),
+            read: false,
+        }
+    }
+
+    /// Takes in what the thread found so far, without waiting. Gives the
+    /// outcome once every puzzle is checked.
+    pub fn poll(&mut self) -> Option<ImportOutcome> {
+        for event in self.events.try_iter() {
+            match event {
+                ImportEvent::Total(total) => {
+                    self.progress.total = total;
+                    self.read = true;
+                }
+                ImportEvent::Accepted(puzzle, expires) => {
+                    self.accepted.push((puzzle, expires));
+                    self.progress.accepted += 1;
+                }
+                ImportEvent::Rejected => self.progress.rejected += 1,
+                ImportEvent::Failed(err) => return Some(ImportOutcome::Failed(err)),
+            }
+        }
+        (self.read && self.progress.done() == self.progress.total)
+            .then(|| ImportOutcome::Done(std::mem::take(&mut self.accepted)))
+    }
+}
+
+/// A puzzle and the end of its event, if the line gives one.
+fn parse_line(line: &str) -> Option<(BoardState, Option<u64>)> {
+    let mut words = line.split_whitespace();
+    let board = words.next()?.replace(['x', 'X'], ".").parse().ok()?;
+    let expires = match (words.next(), words.next(), words.next()) {
+        (None, ..) => None,
+        (Some("until"), Some(date), None) => Some(date.parse::<Date>().ok()?.end()),
+        _ => return None,
+    };
+    Some((board, expires))
+}
+
+impl Drop for CollectionImport {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+pub struct ImportView<'a> {
+    pub import: &'a CollectionImport,
+    pub style: Style,
+    pub gauge_style: Style,
+}
+
+impl Widget for ImportView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let progress = self.import.progress;
+        let [gauge, counts] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+        let ratio = match progress.total {
+            0 => 0.0,
+            total => progress.done() as f64 / total as f64,
+        };
+        Gauge::default()
+            .gauge_style(self.gauge_style)
+            .ratio(ratio)
+            .label(format!("{}/{}", progress.done(), progress.total))
+            .render(gauge, buf);
+        let lines = vec![
+            Line::from(format!(" accepted {}", progress.accepted)),
+            Line::from(format!(" rejected {}", progress.rejected)),
+            Line::from(" Esc cancel"),
+        ];
+        Paragraph::new(lines).style(self.style).render(counts, buf);
+    }
+}
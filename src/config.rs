@@ -0,0 +1,227 @@
+use std::{cell::RefCell, env, fs, path::PathBuf};
+
+use color_eyre::eyre::WrapErr;
+use serde::Deserialize;
+use toml_edit::DocumentMut;
+
+use crate::{
+    bot::Skill,
+    cell_render::{DigitStyle, Glyphs},
+    charset::Charset,
+    difficulty::{self, Tier},
+    duel::ClockConfig,
+    generator::Symmetry,
+    keymap::{KeyboardLayout, Keymap},
+    layout::LayoutConfig,
+    locale::Locale,
+    notes::{NoteConflicts, NoteLayout},
+    pause::PauseCover,
+    rules::Completion,
+    shading::Shading,
+    soduko,
+    statusline::StatusLineConfig,
+    symbols::SymbolSet,
+    theme::ThemeConfig,
+    touch::{Profile, TouchConfig},
+    variant::Variant,
+};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub cell: CellConfig,
+    pub layout: LayoutConfig,
+    pub statusline: StatusLineConfig,
+    pub shading: Shading,
+    pub digits: DigitStyle,
+    /// what the values look like, typing stays 1-9
+    pub symbols: SymbolSet,
+    pub glyphs: Glyphs,
+    /// box drawing and dots, or ASCII for terminals without unicode
+    pub charset: Charset,
+    pub note_conflicts: NoteConflicts,
+    pub note_layout: NoteLayout,
+    /// warn as soon as a placement makes the board unsolvable
+    pub warn_dead_ends: bool,
+    /// rate every placement as forced, logical, guess or wrong
+    pub coach: bool,
+    /// end games on a full board or only once the answer is submitted
+    pub completion: Completion,
+    /// extra houses new games are played with, see [`Variant`]
+    pub variant: Variant,
+    /// how good the bot is when racing it
+    pub bot: Skill,
+    /// time each player gets in hot-seat games in turns
+    pub chess_clock: ClockConfig,
+    /// how the clues of a puzzle carved from a typed in grid are laid out
+    pub symmetry: Symmetry,
+    pub pause: PauseCover,
+    pub profile: Profile,
+    pub touch: TouchConfig,
+    /// click cells and footer hints and scroll to change a value, at the
+    /// cost of the terminal's own text selection
+    pub mouse: bool,
+    /// skip animations such as theme transitions
+    pub reduced_motion: bool,
+    /// keeps hjkl movement in place on keyboards other than QWERTY
+    pub keyboard_layout: KeyboardLayout,
+    /// how times of day, dates and large counts are written, see [`Locale`]
+    pub locale: Locale,
+    /// rebound keys, see [`Keymap`]
+    pub keys: Keymap,
+    /// themes of the player's own, see [`ThemeConfig`]
+    pub themes: Vec<ThemeConfig>,
+    /// difficulty tiers of the player's own, see [`Tier`]
+    pub difficulties: Vec<Tier>,
+}
+
+/// Size of a single board cell in terminal columns/rows.
+///
+/// Terminal glyphs are roughly twice as tall as they are wide, so the
+/// default of 4x1 with no padding gives a board that looks close to square
+/// on most fonts.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct CellConfig {
+    pub width: u16,
+    pub height: u16,
+    /// blank columns between two neighbouring cells
+    pub padding: u16,
+}
+
+impl Default for CellConfig {
+    fn default() -> Self {
+        Self {
+            width: 4,
+            height: 1,
+            padding: 0,
+        }
+    }
+}
+
+impl Config {
+    /// Whether the app needs mouse events from the terminal.
+    pub fn captures_mouse(&self) -> bool {
+        self.mouse || self.profile == Profile::Touch
+    }
+}
+
+impl CellConfig {
+    /// includes the two divider lines between the 3x3 boxes
+    pub fn board_width(&self) -> u16 {
+        self.board_width_of(9)
+    }
+
+    /// of a board `size` cells across, with the dividers between its boxes
+    pub fn board_width_of(&self, size: usize) -> u16 {
+        let stacks = (size / soduko::box_dims(size).1) as u16;
+        let size = size as u16;
+        size * self.width + (size - 1) * self.padding + stacks - 1
+    }
+
+    pub fn scaled(&self, factor: u16) -> Self {
+        Self {
+            width: self.width * factor,
+            height: self.height * factor,
+            padding: self.padding * factor,
+        }
+    }
+
+    /// includes the two divider lines between the 3x3 boxes
+    pub fn board_height(&self) -> u16 {
+        self.board_height_of(9)
+    }
+
+    /// of a board `size` cells down, with the dividers between its boxes
+    pub fn board_height_of(&self, size: usize) -> u16 {
+        let bands = (size / soduko::box_dims(size).0) as u16;
+        size as u16 * self.height + bands - 1
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        Some(
+            xdg_home("XDG_CONFIG_HOME", ".config")?
+                .join("rudoku")
+                .join("config.toml"),
+        )
+    }
+
+    /// Reads the config file, and takes its difficulty tiers into use.
+    pub fn load() -> color_eyre::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            difficulty::configure(&[]);
+            return Ok(Self::default());
+        }
+        let text =
+            fs::read_to_string(&path).wrap_err_with(|| format!("reading {}", path.display()))?;
+        let mut config: Self =
+            toml::from_str(&text).wrap_err_with(|| format!("parsing {}", path.display()))?;
+        config.cell.width = config.cell.width.max(2);
+        config.cell.height = config.cell.height.max(1);
+        difficulty::configure(&config.difficulties);
+        Ok(config)
+    }
+
+    /// Edits the config file as a TOML document, for settings changed
+    /// inside the app. Keys the app doesn't know, comments and layout are
+    /// kept.
+    pub fn edit_file(edit: impl FnOnce(&mut DocumentMut)) -> color_eyre::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        let mut document = match path.exists() {
+            true => fs::read_to_string(&path)
+                .wrap_err_with(|| format!("reading {}", path.display()))?
+                .parse()
+                .wrap_err_with(|| format!("parsing {}", path.display()))?,
+            false => DocumentMut::new(),
+        };
+        edit(&mut document);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+        }
+        fs::write(&path, document.to_string())
+            .wrap_err_with(|| format!("writing {}", path.display()))
+    }
+}
+
+/// A table for [`Config::edit_file`] that only gets a `[header]` of its
+/// own once it holds keys, not for the tables under it.
+pub(crate) fn subtable() -> toml_edit::Item {
+    let mut table = toml_edit::Table::new();
+    table.set_implicit(true);
+    table.into()
+}
+
+/// `$var` if set, otherwise `$HOME/fallback`.
+pub(crate) fn xdg_home(var: &str, fallback: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+}
+
+thread_local! {
+    /// The player whose files this thread reads and writes, set on the
+    /// threads serving remote players.
+    static PROFILE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Keeps the prefs, stats and saves of this thread's app under
+/// `profiles/<name>`, apart from everyone else's.
+pub(crate) fn set_profile(name: &str) {
+    PROFILE.set(Some(name.to_owned()));
+}
+
+/// Where the app keeps its own files: prefs, stats and saves.
+pub(crate) fn state_dir() -> Option<PathBuf> {
+    let dir = xdg_home("XDG_STATE_HOME", ".local/state")?.join("rudoku");
+    Some(PROFILE.with_borrow(|profile| match profile {
+        Some(name) => dir.join("profiles").join(name),
+        None => dir,
+    }))
+}
@@ -0,0 +1,136 @@
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    collections::VecDeque,
+    env, fs, io,
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+};
+
+use color_eyre::eyre::{WrapErr, eyre};
+
+use crate::{Config, Prefs, SavedGame, config::state_dir, daily::unix_seconds};
+
+/// keys kept for the report, the latest last
+const KEPT_KEYS: usize = 64;
+
+thread_local! {
+    /// What this thread's app was last doing, for a report written when it
+    /// panics.
+    static CONTEXT: RefCell<Context> = RefCell::new(Context::default());
+}
+
+#[derive(Debug, Default)]
+struct Context {
+    board: Option<String>,
+    keys: VecDeque<String>,
+}
+
+/// Keeps the board as it is for a report.
+pub(crate) fn note_board(board: String) {
+    CONTEXT.with_borrow_mut(|context| context.board = Some(board));
+}
+
+/// Keeps `key` among the last keys pressed for a report.
+pub(crate) fn note_key(key: String) {
+    CONTEXT.with_borrow_mut(|context| {
+        if context.keys.len() == KEPT_KEYS {
+            context.keys.pop_front();
+        }
+        context.keys.push_back(key);
+    });
+}
+
+/// Where reports go, one directory each.
+pub(crate) fn reports_dir() -> Option<PathBuf> {
+    Some(state_dir()?.join("crash-reports"))
+}
+
+/// Writes a report whenever a thread panics, and says where, before the
+/// panic is reported as before.
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info: &PanicHookInfo| {
+        let reason = format!("{info}\n\n{}", Backtrace::force_capture());
+        match Bundle::gather(reason).write() {
+            Ok(dir) => eprintln!("A crash report was written to {}", dir.display()),
+            Err(err) => eprintln!("Couldn't write a crash report: {err}"),
+        }
+        previous(info);
+    }));
+}
+
+/// Everything worth attaching to an issue: what went wrong, the version and
+/// platform, the config and prefs, the board and the last keys pressed.
+pub struct Bundle {
+    reason: String,
+    board: Option<String>,
+    keys: Vec<String>,
+}
+
+impl Bundle {
+    /// A report of `reason` with what this thread's app was last doing.
+    pub fn gather(reason: impl Into<String>) -> Self {
+        let (board, keys) = CONTEXT.with_borrow(|context| {
+            (
+                context.board.clone(),
+                context.keys.iter().cloned().collect(),
+            )
+        });
+        Self {
+            reason: reason.into(),
+            board,
+            keys,
+        }
+    }
+
+    /// Writes the report to a directory of its own under the state
+    /// directory, and returns it.
+    pub fn write(&self) -> color_eyre::Result<PathBuf> {
+        let reports =
+            reports_dir().ok_or_else(|| eyre!("no home directory to write the report to"))?;
+        fs::create_dir_all(&reports).wrap_err_with(|| format!("creating {}", reports.display()))?;
+        // a suffix keeps reports written in the same second apart
+        let seconds = unix_seconds();
+        let mut dir = reports.join(seconds.to_string());
+        for n in 2.. {
+            match fs::create_dir(&dir) {
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    dir = reports.join(format!("{seconds}-{n}"));
+                }
+                result => {
+                    result.wrap_err_with(|| format!("creating {}", dir.display()))?;
+                    break;
+                }
+            }
+        }
+        let write = |name: &str, text: &str| {
+            let path = dir.join(name);
+            fs::write(&path, text).wrap_err_with(|| format!("writing {}", path.display()))
+        };
+        write("report.txt", &self.summary())?;
+        if let Some(board) = &self.board {
+            write("board.txt", board)?;
+        }
+        write("keys.txt", &(self.keys.join("\n") + "\n"))?;
+        // copied as they are, a file that won't parse may be the problem
+        let files = [Config::path(), Prefs::path(), SavedGame::path()];
+        for path in files.into_iter().flatten() {
+            if let (Ok(text), Some(name)) = (fs::read_to_string(&path), path.file_name()) {
+                write(&name.to_string_lossy(), &text)?;
+            }
+        }
+        Ok(dir)
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "rudoku {}\n{} {}\nTERM={}\n\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            env::consts::OS,
+            env::consts::ARCH,
+            env::var("TERM").unwrap_or_default(),
+            self.reason,
+        )
+    }
+}
@@ -0,0 +1,145 @@
+/// The selected cell. Rows wrap around the board, columns stop at its edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    row: usize,
+    col: usize,
+    /// cells across the board
+    size: usize,
+    /// where the last jump came from, to jump back to
+    before_jump: Option<(usize, usize)>,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self::sized(9)
+    }
+}
+
+impl Cursor {
+    /// On a board `size` cells across, 9 by default.
+    pub fn sized(size: usize) -> Self {
+        Self {
+            row: 0,
+            col: 0,
+            size,
+            before_jump: None,
+        }
+    }
+
+    pub fn cell(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn select(&mut self, row: usize, col: usize) {
+        self.row = row.min(self.size - 1);
+        self.col = col.min(self.size - 1);
+    }
+
+    pub fn next_row(&mut self) {
+        self.row = (self.row + 1) % self.size;
+    }
+
+    pub fn previous_row(&mut self) {
+        self.row = (self.row + self.size - 1) % self.size;
+    }
+
+    pub fn next_column(&mut self) {
+        self.col = (self.col + 1).min(self.size - 1);
+    }
+
+    pub fn previous_column(&mut self) {
+        self.col = self.col.saturating_sub(1);
+    }
+
+    /// Jumps to the next cell in reading order that `pick` takes, or the
+    /// previous one, wrapping around the board. Stays put and returns
+    /// `false` when no other cell will do.
+    pub fn jump(&mut self, forward: bool, pick: impl Fn(usize, usize) -> bool) -> bool {
+        let cells = self.size * self.size;
+        let here = self.row * self.size + self.col;
+        let found = (1..cells)
+            .map(|step| match forward {
+                true => (here + step) % cells,
+                false => (here + cells - step) % cells,
+            })
+            .find(|&pos| pick(pos / self.size, pos % self.size));
+        let Some(pos) = found else {
+            return false;
+        };
+        self.before_jump = Some(self.cell());
+        (self.row, self.col) = (pos / self.size, pos % self.size);
+        true
+    }
+
+    /// Goes back to where the last jump came from. Going back is a jump
+    /// too, so doing it again returns.
+    pub fn jump_back(&mut self) -> bool {
+        let Some((row, col)) = self.before_jump else {
+            return false;
+        };
+        self.before_jump = Some(self.cell());
+        (self.row, self.col) = (row, col);
+        true
+    }
+}
+
+/// Cells picked out for a change to all of them at once: a rectangle
+/// stretched from where it was started to the cursor, on top of cells
+/// toggled in one at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Selection {
+    /// the toggled cells, and rectangles already finished
+    cells: [[bool; 9]; 9],
+    /// the corner the rectangle being stretched started from, and the
+    /// corner the cursor is at
+    stretch: Option<((usize, usize), (usize, usize))>,
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.cells().next().is_none()
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.cells[row][col]
+            || self.stretch.is_some_and(|((r0, c0), (r1, c1))| {
+                (r0.min(r1)..=r0.max(r1)).contains(&row) && (c0.min(c1)..=c0.max(c1)).contains(&col)
+            })
+    }
+
+    /// Every selected cell, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..81)
+            .map(|i| (i / 9, i % 9))
+            .filter(|&(row, col)| self.contains(row, col))
+    }
+
+    /// Stretches the rectangle to `to`, starting one at `from` if none is
+    /// being stretched.
+    pub fn stretch(&mut self, from: (usize, usize), to: (usize, usize)) {
+        let start = self.stretch.map_or(from, |(start, _)| start);
+        self.stretch = Some((start, to));
+    }
+
+    /// Finishes the rectangle being stretched, keeping its cells.
+    pub fn settle(&mut self) {
+        for (row, col) in self.cells().collect::<Vec<_>>() {
+            self.cells[row][col] = true;
+        }
+        self.stretch = None;
+    }
+
+    /// Adds the cell, or takes it out if it's in.
+    pub fn toggle(&mut self, row: usize, col: usize) {
+        self.settle();
+        self.cells[row][col] = !self.cells[row][col];
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
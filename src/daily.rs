@@ -0,0 +1,255 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre::{OptionExt, eyre};
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+
+use crate::{
+    charset::GlyphSet,
+    difficulty::Difficulty,
+    formats,
+    generator::{self, Symmetry},
+    locale::{Locale, format_date, format_elapsed},
+    rng::Rng,
+    soduko::BoardState,
+    stats::Stats,
+};
+
+/// Days back the archive goes, today included.
+const ARCHIVE_DAYS: i64 = 30;
+
+/// Seconds since the Unix epoch.
+pub fn unix_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// A day in UTC, counted from the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date(i64);
+
+impl Date {
+    pub fn today() -> Self {
+        Self::of(unix_seconds())
+    }
+
+    /// The first second after the day, in seconds since the Unix epoch.
+    pub fn end(self) -> u64 {
+        (self.0 + 1).max(0) as u64 * 86_400
+    }
+
+    /// The day `unix` seconds since the Unix epoch fall on.
+    pub fn of(unix: u64) -> Self {
+        Self((unix / 86_400) as i64)
+    }
+
+    fn days_before(self, days: i64) -> Self {
+        Self(self.0 - days)
+    }
+
+    /// Year, month and day, by Howard Hinnant's `civil_from_days`.
+    pub(crate) fn civil(self) -> (i64, u32, u32) {
+        let z = self.0 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        (year, month, day)
+    }
+
+    /// The day's puzzle, the same for everyone: the generator is seeded
+    /// with the date alone and digs to the built in medium tier, whatever
+    /// the config says.
+    pub fn puzzle(self) -> BoardState {
+        // spread neighbouring days apart before xorshift gets them
+        let seed = (self.0 as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let mut rng = Rng::new(seed);
+        let solution = generator::solved_grid(&mut rng);
+        let tier = Difficulty::default().builtin_tier();
+        generator::carve(solution, tier, Symmetry::None, &mut rng)
+    }
+}
+
+/// Year, month and day as `YYYY-MM-DD`, by Howard Hinnant's
+/// `days_from_civil`.
+impl FromStr for Date {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, '-').map(str::parse::<i64>);
+        let mut next = || {
+            parts
+                .next()
+                .ok_or_eyre("expected a date as YYYY-MM-DD")?
+                .map_err(|_| eyre!("expected a date as YYYY-MM-DD, got {s}"))
+        };
+        let (year, month, day) = (next()?, next()?, next()?);
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(eyre!("no such date {s}"));
+        }
+        let shifted = year - i64::from(month <= 2);
+        let era = shifted.div_euclid(400);
+        let yoe = shifted.rem_euclid(400);
+        let mp = (month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let date = Self(era * 146_097 + doe - 719_468);
+        // a day past the end of its month lands in the next one
+        if date.civil() != (year, month as u32, day as u32) {
+            return Err(eyre!("no such date {s}"));
+        }
+        Ok(date)
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.civil();
+        write!(f, "{year:04}-{month:02}-{day:02}")
+    }
+}
+
+pub struct ArchiveEntry {
+    pub date: Date,
+    pub puzzle: BoardState,
+    /// the player's best time on it, in seconds
+    pub best: Option<u64>,
+}
+
+/// The daily puzzles of the last month, today's first, to play the ones
+/// missed or go again at the ones solved.
+pub struct Archive {
+    pub entries: Vec<ArchiveEntry>,
+    selected: usize,
+}
+
+impl Archive {
+    /// The puzzles of the month up to `today`, today's first, which take a
+    /// while to dig. `None` once `stop` is set.
+    pub fn puzzles(today: Date, stop: &AtomicBool) -> Option<Vec<(Date, BoardState)>> {
+        (0..ARCHIVE_DAYS)
+            .map(|days| {
+                let date = today.days_before(days);
+                (!stop.load(Ordering::Relaxed)).then(|| (date, date.puzzle()))
+            })
+            .collect()
+    }
+
+    /// The archive of the [`Archive::puzzles`], with the player's best
+    /// times on them.
+    pub fn new(puzzles: Vec<(Date, BoardState)>, stats: &Stats) -> Self {
+        let entries = puzzles
+            .into_iter()
+            .map(|(date, puzzle)| {
+                let code = formats::code(&puzzle);
+                let best = stats
+                    .games
+                    .iter()
+                    .filter(|game| game.won && game.puzzle.as_deref() == Some(code.as_str()))
+                    .map(|game| game.seconds)
+                    .min();
+                ArchiveEntry { date, puzzle, best }
+            })
+            .collect();
+        Self {
+            entries,
+            selected: 0,
+        }
+    }
+
+    /// `Some` with the day picked to play, `None` while browsing. Going back
+    /// is the caller's, on Esc.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<&ArchiveEntry> {
+        let last = self.entries.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::Enter | KeyCode::Char(' ') => return self.entries.get(self.selected),
+            _ => {}
+        }
+        None
+    }
+}
+
+pub struct ArchiveView<'a> {
+    pub archive: &'a Archive,
+    pub locale: &'a Locale,
+    pub style: Style,
+    pub selected_style: Style,
+    pub glyphs: GlyphSet,
+}
+
+impl Widget for ArchiveView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let archive = self.archive;
+        let [up, down, ..] = self.glyphs.arrows;
+        let rows = area.height.saturating_sub(2) as usize;
+        // keeps the selected day in view
+        let skip = (archive.selected + 1).saturating_sub(rows);
+        let mut lines: Vec<Line> = archive
+            .entries
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .map(|(i, entry)| {
+                let day = match i {
+                    0 => "today",
+                    1 => "yesterday",
+                    _ => "",
+                };
+                let best = entry.best.map_or(String::new(), |seconds| {
+                    format!("solved {}", format_elapsed(Duration::from_secs(seconds)))
+                });
+                let text = format!(
+                    " {}  {day:<9}  {best}",
+                    format_date(self.locale, entry.date)
+                );
+                let style = match i == archive.selected {
+                    true => self.selected_style,
+                    false => self.style,
+                };
+                Line::styled(
+                    format!("{text:<width$}", width = area.width as usize),
+                    style,
+                )
+            })
+            .collect();
+        lines.push(Line::default());
+        lines.push(Line::from(format!(
+            " {up}/{down} move  Enter play  Esc back"
+        )));
+        Paragraph::new(lines).style(self.style).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_past_the_end_of_the_month_are_no_dates() {
+        assert!("2025-02-29".parse::<Date>().is_err());
+        assert!("2025-04-31".parse::<Date>().is_err());
+        let leap: Date = "2024-02-29".parse().unwrap();
+        assert_eq!(leap.civil(), (2024, 2, 29));
+        assert_eq!(leap.to_string(), "2024-02-29");
+    }
+}
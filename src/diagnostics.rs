@@ -0,0 +1,92 @@
+use std::{fs, path::Path, time::Duration};
+
+use color_eyre::eyre::WrapErr;
+use serde::Serialize;
+
+/// Timings collected over a session: handling each input event, drawing
+/// each frame, and from an input arriving to the frame that shows it.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    handle: Vec<Duration>,
+    draw: Vec<Duration>,
+    input_to_render: Vec<Duration>,
+}
+
+/// Percentiles in microseconds.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Summary {
+    pub samples: usize,
+    pub p50_us: u128,
+    pub p95_us: u128,
+    pub max_us: u128,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub handle: Option<Summary>,
+    pub draw: Option<Summary>,
+    pub input_to_render: Option<Summary>,
+}
+
+impl Summary {
+    fn of(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let at = |p: usize| sorted[(sorted.len() - 1) * p / 100].as_micros();
+        Some(Self {
+            samples: sorted.len(),
+            p50_us: at(50),
+            p95_us: at(95),
+            max_us: at(100),
+        })
+    }
+}
+
+impl Diagnostics {
+    pub fn handled(&mut self, took: Duration) {
+        self.handle.push(took);
+    }
+
+    pub fn drew(&mut self, took: Duration) {
+        self.draw.push(took);
+    }
+
+    pub fn rendered(&mut self, since_input: Duration) {
+        self.input_to_render.push(since_input);
+    }
+
+    pub fn report(&self) -> Report {
+        Report {
+            handle: Summary::of(&self.handle),
+            draw: Summary::of(&self.draw),
+            input_to_render: Summary::of(&self.input_to_render),
+        }
+    }
+
+    /// One line for the header: p50/p95 in milliseconds.
+    pub fn line(&self) -> String {
+        let report = self.report();
+        let part = |name: &str, summary: Option<Summary>| match summary {
+            Some(s) => format!(
+                "{name} {:.1}/{:.1}ms",
+                s.p50_us as f64 / 1000.0,
+                s.p95_us as f64 / 1000.0
+            ),
+            None => format!("{name} -"),
+        };
+        [
+            part("input", report.handle),
+            part("draw", report.draw),
+            part("latency", report.input_to_render),
+        ]
+        .join(" ")
+    }
+
+    pub fn save(&self, path: &Path) -> color_eyre::Result<()> {
+        let json = serde_json::to_string_pretty(&self.report())?;
+        fs::write(path, json).wrap_err_with(|| format!("writing {}", path.display()))
+    }
+}
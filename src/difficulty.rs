@@ -0,0 +1,253 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    fmt::{self, Debug, Display, Formatter},
+    ops::RangeInclusive,
+    sync::{Mutex, RwLock},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{analyze, rating::Grade, soduko::BoardState};
+
+/// What makes a puzzle one difficulty rather than another. The config's
+/// `[[difficulties]]` change the built in tiers by name or add tiers of
+/// their own after them:
+///
+/// ```toml
+/// [[difficulties]]
+/// name = "diabolical"
+/// min_clues = 22
+/// min_techniques = "fish"
+/// techniques = "guessing"
+/// max_rating = 12
+/// target_minutes = 60
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tier {
+    pub name: String,
+    /// clues to dig down to, as few as a single solution allows at 0
+    #[serde(default)]
+    pub min_clues: usize,
+    /// clues a puzzle may keep at most, for when digging stops early
+    #[serde(default = "all_clues")]
+    pub max_clues: usize,
+    /// the easiest techniques a puzzle may get by with, so a harder tier
+    /// doesn't hand out a puzzle of an easier one
+    #[serde(default = "no_technique")]
+    pub min_techniques: Grade,
+    /// the hardest techniques a puzzle may take, see [`Grade`]
+    #[serde(default = "any_technique")]
+    pub techniques: Grade,
+    /// the highest `rudoku analyze` rating a puzzle may have
+    #[serde(default)]
+    pub max_rating: Option<u32>,
+    /// solve time a comfortable player should beat
+    #[serde(default = "half_an_hour")]
+    pub target_minutes: u64,
+}
+
+fn all_clues() -> usize {
+    81
+}
+
+fn no_technique() -> Grade {
+    Grade::Singles
+}
+
+fn any_technique() -> Grade {
+    Grade::Guessing
+}
+
+fn half_an_hour() -> u64 {
+    30
+}
+
+impl Tier {
+    fn builtin(
+        name: &str,
+        min_clues: usize,
+        techniques: RangeInclusive<Grade>,
+        target_minutes: u64,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            min_clues,
+            max_clues: all_clues(),
+            min_techniques: *techniques.start(),
+            techniques: *techniques.end(),
+            max_rating: None,
+            target_minutes,
+        }
+    }
+
+    /// Whether `puzzle` is what this tier promises.
+    pub fn admits(&self, puzzle: &BoardState) -> bool {
+        if puzzle.clue_count() > self.max_clues {
+            return false;
+        }
+        if crate::rating::rate(puzzle)
+            .is_none_or(|grade| !(self.min_techniques..=self.techniques).contains(&grade))
+        {
+            return false;
+        }
+        self.max_rating
+            .is_none_or(|max| analyze::rating_up_to(puzzle, max) <= max)
+    }
+}
+
+/// The tiers in the config, kept for every thread since puzzles are
+/// generated in the background and for remote players too.
+static CONFIGURED: RwLock<Vec<Tier>> = RwLock::new(Vec::new());
+
+/// Names of difficulties ever seen, each leaked once so [`Difficulty`] can
+/// stay `Copy`.
+static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+fn intern(name: &str) -> &'static str {
+    let mut names = NAMES.lock().unwrap_or_else(|err| err.into_inner());
+    match names.get(name) {
+        Some(name) => name,
+        None => {
+            let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+            names.insert(name);
+            name
+        }
+    }
+}
+
+/// Uses the tiers of a newly loaded config.
+pub(crate) fn configure(tiers: &[Tier]) {
+    *CONFIGURED.write().unwrap_or_else(|err| err.into_inner()) = tiers.to_vec();
+}
+
+fn builtin_tiers() -> Vec<Tier> {
+    vec![
+        Tier::builtin("easy", 40, Grade::Singles..=Grade::Singles, 5),
+        Tier::builtin("medium", 27, Grade::Pairs..=Grade::Pairs, 10),
+        Tier::builtin("hard", 22, Grade::Pairs..=Grade::Fish, 20),
+        Tier::builtin("expert", 0, Grade::Guessing..=Grade::Guessing, 40),
+    ]
+}
+
+/// The built in tiers with the configured ones over them, easiest first.
+pub fn tiers() -> Vec<Tier> {
+    let mut tiers = builtin_tiers();
+    for tier in CONFIGURED
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .iter()
+    {
+        match tiers.iter_mut().find(|builtin| builtin.name == tier.name) {
+            Some(builtin) => *builtin = tier.clone(),
+            None => tiers.push(tier.clone()),
+        }
+    }
+    tiers
+}
+
+/// A difficulty tier, by name. Games keep the name even once the tier is
+/// gone from the config, and then play as medium.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Difficulty(&'static str);
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self("medium")
+    }
+}
+
+impl Difficulty {
+    /// Every tier, easiest first.
+    pub fn all() -> Vec<Difficulty> {
+        tiers()
+            .iter()
+            .map(|tier| Difficulty(intern(&tier.name)))
+            .collect()
+    }
+
+    /// The tier called `name`, if there is one.
+    pub fn named(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|d| d.0 == name)
+    }
+
+    pub fn label(self) -> &'static str {
+        self.0
+    }
+
+    /// What puzzles of this difficulty are like.
+    pub fn tier(self) -> Tier {
+        let tiers = tiers();
+        let position = self.position().unwrap_or(1);
+        tiers[position].clone()
+    }
+
+    /// The tier as built in, whatever the config makes of it, for puzzles
+    /// that have to come out the same for everyone. Medium for a tier only
+    /// the config has.
+    pub fn builtin_tier(self) -> Tier {
+        let mut tiers = builtin_tiers();
+        let position = tiers
+            .iter()
+            .position(|tier| tier.name == self.0)
+            .unwrap_or(1);
+        tiers.swap_remove(position)
+    }
+
+    fn position(self) -> Option<usize> {
+        Self::all().iter().position(|&d| d == self)
+    }
+
+    pub fn harder(self) -> Option<Self> {
+        Self::all().get(self.position()? + 1).copied()
+    }
+
+    pub fn easier(self) -> Option<Self> {
+        let position = self.position()?.checked_sub(1)?;
+        Self::all().get(position).copied()
+    }
+
+    /// Solve time a comfortable player should beat, in seconds.
+    pub fn target_seconds(self) -> u64 {
+        self.tier().target_minutes * 60
+    }
+}
+
+/// Easiest first, with tiers no longer configured after the rest.
+impl Ord for Difficulty {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |d: &Difficulty| (d.position().unwrap_or(usize::MAX), d.0);
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for Difficulty {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Debug for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Difficulty({:?})", self.0)
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl Serialize for Difficulty {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Difficulty {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Self(intern(&name)))
+    }
+}
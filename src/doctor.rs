@@ -0,0 +1,246 @@
+use std::{
+    fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::WrapErr;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    Result, config::Config, crash::reports_dir, job::GenerationJob, persist::SavedGame,
+    prefs::Prefs, stats::Stats,
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Json,
+    Toml,
+}
+
+/// A repair that loses something, made only with `--fix` once the player
+/// agrees to it.
+enum Repair {
+    /// moves a file that won't load out of the way, so the app starts over
+    /// without it
+    SetAside(PathBuf),
+    /// the stats without the games and library puzzles that don't read
+    DropUnreadable(Stats, usize),
+}
+
+impl Repair {
+    fn describe(&self) -> String {
+        match self {
+            Repair::SetAside(path) => {
+                format!("move {} to {}", path.display(), broken(path).display())
+            }
+            Repair::DropUnreadable(_, n) => {
+                format!("drop {n} games and library puzzles that don't read from the stats")
+            }
+        }
+    }
+
+    fn apply(self) -> Result {
+        match self {
+            Repair::SetAside(path) => {
+                let to = broken(&path);
+                fs::rename(&path, &to).wrap_err_with(|| format!("moving {}", path.display()))
+            }
+            Repair::DropUnreadable(stats, _) => stats.save(),
+        }
+    }
+}
+
+/// Checks the config and the files the app keeps, rewriting the ones in an
+/// older format and rebuilding the library, and says how big they are.
+/// Repairs that lose something are listed, and made with `fix` after
+/// asking.
+pub fn run(fix: bool) -> Result {
+    let mut repairs = Vec::new();
+    check_config();
+    if let Some((prefs, outdated)) = load::<Prefs>(Prefs::path(), Format::Toml, &mut repairs) {
+        migrate(Prefs::path(), outdated, || prefs.save())?;
+    }
+    if let Some((mut stats, outdated)) = load::<Stats>(Stats::path(), Format::Json, &mut repairs) {
+        let merged = stats.rebuild_library();
+        if merged > 0 {
+            stats.save()?;
+            report(Stats::path(), &format!("library puzzles merged: {merged}"));
+        } else {
+            migrate(Stats::path(), outdated, || stats.save())?;
+        }
+        let unreadable = stats.unreadable();
+        if unreadable > 0 {
+            report(
+                Stats::path(),
+                &format!("puzzles that don't read: {unreadable}"),
+            );
+            stats.drop_unreadable();
+            repairs.push(Repair::DropUnreadable(stats, unreadable));
+        }
+    }
+    if let Some((saved, outdated)) =
+        load::<SavedGame>(SavedGame::path(), Format::Json, &mut repairs)
+    {
+        if saved.puzzle().is_err() || saved.board().is_err() {
+            report(SavedGame::path(), "the board doesn't read");
+            repairs.extend(SavedGame::path().map(Repair::SetAside));
+        } else {
+            migrate(SavedGame::path(), outdated, || saved.save())?;
+        }
+    }
+    let job = GenerationJob::path();
+    if let Some((job, outdated)) = load::<GenerationJob>(job, Format::Json, &mut repairs) {
+        migrate(GenerationJob::path(), outdated, || job.save())?;
+    }
+    check_reports();
+
+    if repairs.is_empty() {
+        println!("\nnothing needs repairing");
+        return Ok(());
+    }
+    println!("\n{} repairs would lose something:", repairs.len());
+    for repair in &repairs {
+        println!("  {}", repair.describe());
+    }
+    if !fix {
+        println!("run `rudoku doctor --fix` to make them");
+        return Ok(());
+    }
+    if !confirm("make them?")? {
+        return Ok(());
+    }
+    for repair in repairs {
+        repair.apply()?;
+    }
+    println!("done");
+    Ok(())
+}
+
+/// The config is the player's own writing, so it's only ever read.
+fn check_config() {
+    let path = Config::path();
+    match Config::load() {
+        Ok(_) => report(path, "ok"),
+        Err(err) => report(path, &format!("{err:#}, to fix by hand")),
+    }
+}
+
+/// Loads the file at `path`, and whether it's in an older format than the
+/// one it would be saved in. A file that won't load gets a repair setting
+/// it aside.
+fn load<T: Serialize + DeserializeOwned>(
+    path: Option<PathBuf>,
+    format: Format,
+    repairs: &mut Vec<Repair>,
+) -> Option<(T, bool)> {
+    let path = path?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) => {
+            report(Some(path), &format!("can't be read: {err}"));
+            return None;
+        }
+    };
+    let parsed = match format {
+        Format::Json => serde_json::from_str::<T>(&text).map_err(|err| err.to_string()),
+        Format::Toml => toml::from_str::<T>(&text).map_err(|err| err.to_string()),
+    };
+    match parsed {
+        Ok(value) => {
+            let outdated = match format {
+                Format::Json => {
+                    serde_json::from_str::<serde_json::Value>(&text).ok()
+                        != serde_json::to_value(&value).ok()
+                }
+                Format::Toml => {
+                    toml::from_str::<toml::Table>(&text).ok() != toml::Table::try_from(&value).ok()
+                }
+            };
+            Some((value, outdated))
+        }
+        Err(err) => {
+            let first_line = err.lines().next().unwrap_or_default().to_string();
+            report(Some(path.clone()), &format!("doesn't load: {first_line}"));
+            repairs.push(Repair::SetAside(path));
+            None
+        }
+    }
+}
+
+/// Saves a file in an older format over itself, which loses nothing.
+fn migrate(path: Option<PathBuf>, outdated: bool, save: impl FnOnce() -> Result) -> Result {
+    if outdated {
+        save()?;
+        report(path, "moved to the current format");
+    } else {
+        report(path, "ok");
+    }
+    Ok(())
+}
+
+fn check_reports() {
+    let Some(dir) = reports_dir() else {
+        return;
+    };
+    let reports: Vec<PathBuf> = fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    if reports.is_empty() {
+        return;
+    }
+    let size = reports
+        .iter()
+        .flat_map(|report| fs::read_dir(report).into_iter().flatten().flatten())
+        .filter_map(|file| file.metadata().ok())
+        .map(|meta| meta.len())
+        .sum();
+    println!(
+        "{:<40} {:>9}  {} crash reports",
+        dir.display(),
+        format_size(size),
+        reports.len()
+    );
+}
+
+/// One line for the file: where it is, how big and how it is.
+fn report(path: Option<PathBuf>, status: &str) {
+    let Some(path) = path else {
+        return;
+    };
+    let size = match fs::metadata(&path) {
+        Ok(meta) => format_size(meta.len()),
+        Err(_) => "-".to_string(),
+    };
+    let status = match path.exists() {
+        true => status,
+        false => "none yet",
+    };
+    println!("{:<40} {size:>9}  {status}", path.display());
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Where a file that won't load is set aside to.
+fn broken(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".broken");
+    path.with_file_name(name)
+}
+
+fn confirm(question: &str) -> color_eyre::Result<bool> {
+    print!("{question} [y/N] ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
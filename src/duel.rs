@@ -0,0 +1,279 @@
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use serde::{Deserialize, Deserializer};
+
+use crate::{cursor::Cursor, difficulty::Difficulty, soduko::BoardState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Play {
+    Up,
+    Down,
+    Left,
+    Right,
+    Write(u8),
+    Erase,
+}
+
+/// The left player's keys: WASD to move and the number row to write.
+const LEFT: &[(KeyCode, Play)] = &[
+    (KeyCode::Char('w'), Play::Up),
+    (KeyCode::Char('s'), Play::Down),
+    (KeyCode::Char('a'), Play::Left),
+    (KeyCode::Char('d'), Play::Right),
+    (KeyCode::Char('1'), Play::Write(1)),
+    (KeyCode::Char('2'), Play::Write(2)),
+    (KeyCode::Char('3'), Play::Write(3)),
+    (KeyCode::Char('4'), Play::Write(4)),
+    (KeyCode::Char('5'), Play::Write(5)),
+    (KeyCode::Char('6'), Play::Write(6)),
+    (KeyCode::Char('7'), Play::Write(7)),
+    (KeyCode::Char('8'), Play::Write(8)),
+    (KeyCode::Char('9'), Play::Write(9)),
+    (KeyCode::Char('x'), Play::Erase),
+    (KeyCode::Char('0'), Play::Erase),
+];
+
+/// The right player's keys: the arrows to move, and the letters under the
+/// right hand laid out like a number pad to write, since the number row
+/// belongs to the left player.
+const RIGHT: &[(KeyCode, Play)] = &[
+    (KeyCode::Up, Play::Up),
+    (KeyCode::Down, Play::Down),
+    (KeyCode::Left, Play::Left),
+    (KeyCode::Right, Play::Right),
+    (KeyCode::Char('m'), Play::Write(1)),
+    (KeyCode::Char(','), Play::Write(2)),
+    (KeyCode::Char('.'), Play::Write(3)),
+    (KeyCode::Char('j'), Play::Write(4)),
+    (KeyCode::Char('k'), Play::Write(5)),
+    (KeyCode::Char('l'), Play::Write(6)),
+    (KeyCode::Char('u'), Play::Write(7)),
+    (KeyCode::Char('i'), Play::Write(8)),
+    (KeyCode::Char('o'), Play::Write(9)),
+    (KeyCode::Backspace, Play::Erase),
+    (KeyCode::Delete, Play::Erase),
+];
+
+/// What the keys of each seat are, for drawing under its board.
+pub const KEY_HINTS: [&str; 2] = [
+    "wasd move  1-9 write  x erase",
+    "arrows move  uio/jkl/m,. write",
+];
+
+/// Most minutes a chess clock can be set to, a day.
+const MAX_MINUTES: f64 = 24.0 * 60.0;
+
+/// The `[chess_clock]` config table, for hot-seat games played in turns.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    /// minutes on each player's clock, left then right, so a stronger
+    /// player can give a weaker one time
+    #[serde(deserialize_with = "minutes")]
+    pub minutes: [f64; 2],
+}
+
+fn minutes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[f64; 2], D::Error> {
+    let minutes = <[f64; 2]>::deserialize(deserializer)?;
+    match minutes.iter().find(|m| !(0.0..=MAX_MINUTES).contains(*m)) {
+        Some(m) => Err(serde::de::Error::custom(format!(
+            "{m} minutes on a clock, expected 0 to {MAX_MINUTES}"
+        ))),
+        None => Ok(minutes),
+    }
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            minutes: [10.0, 10.0],
+        }
+    }
+}
+
+/// A chess clock: the players take turns placing one digit each, and only
+/// the clock of the player whose turn it is runs.
+#[derive(Debug, Clone)]
+pub struct ChessClock {
+    /// the seat whose turn it is
+    pub turn: usize,
+    /// what each clock showed when it was last stopped
+    left: [Duration; 2],
+    /// when the running clock was started, `None` once the game is over
+    since: Option<Instant>,
+}
+
+impl ChessClock {
+    pub fn new(config: ClockConfig) -> Self {
+        Self {
+            turn: 0,
+            left: config.minutes.map(|minutes| {
+                Duration::try_from_secs_f64(minutes.clamp(0.0, MAX_MINUTES) * 60.0)
+                    .unwrap_or_default()
+            }),
+            since: Some(Instant::now()),
+        }
+    }
+
+    /// Time left on `seat`'s clock.
+    pub fn left(&self, seat: usize) -> Duration {
+        match self.since {
+            Some(since) if seat == self.turn => self.left[seat].saturating_sub(since.elapsed()),
+            _ => self.left[seat],
+        }
+    }
+
+    /// Whether the clock runs, that is the game is still on.
+    pub fn running(&self) -> bool {
+        self.since.is_some()
+    }
+
+    fn stop(&mut self) {
+        self.left[self.turn] = self.left(self.turn);
+        self.since = None;
+    }
+
+    /// Stops the running clock and starts the other.
+    fn pass(&mut self) {
+        self.stop();
+        self.turn = 1 - self.turn;
+        self.since = Some(Instant::now());
+    }
+}
+
+/// One player's side of the race.
+pub struct Seat {
+    pub board: BoardState,
+    pub cursor: Cursor,
+}
+
+impl Seat {
+    /// Cells filled, out of how many the puzzle left empty.
+    pub fn progress(&self, puzzle: &BoardState) -> (usize, usize) {
+        let clues = puzzle.clue_count();
+        (self.board.clue_count() - clues, 81 - clues)
+    }
+
+    /// `true` when it placed a digit, which ends a turn on a chess clock.
+    fn play(&mut self, puzzle: &BoardState, play: Play) -> bool {
+        let (row, col) = self.cursor.cell();
+        match play {
+            Play::Up => self.cursor.previous_row(),
+            Play::Down => self.cursor.next_row(),
+            Play::Left => self.cursor.previous_column(),
+            Play::Right => self.cursor.next_column(),
+            // the givens are the same on both boards and stay put
+            _ if puzzle[row][col].is_some() => {}
+            Play::Write(digit) => {
+                self.board.set(row as u8, col as u8, digit.into());
+                return true;
+            }
+            Play::Erase => self.board.set(row as u8, col as u8, 0.into()),
+        }
+        false
+    }
+
+    fn solved(&self) -> bool {
+        self.board.clue_count() == 81 && self.board.check()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuelEvent {
+    /// the seat that filled in its board first, and how long it took
+    Won(usize, Duration),
+    /// the seat whose clock ran out, which loses
+    OutOfTime(usize),
+    Leave,
+}
+
+/// Two players at one keyboard racing through the same puzzle, each on a
+/// board of their own with keys of their own. The first to fill theirs in
+/// correctly wins. On a chess clock they take turns instead, and running out
+/// of time loses.
+pub struct Duel {
+    pub puzzle: BoardState,
+    pub difficulty: Difficulty,
+    pub seats: [Seat; 2],
+    started: Instant,
+    /// the winner and their time, once there is one
+    pub winner: Option<(usize, Duration)>,
+    pub clock: Option<ChessClock>,
+}
+
+impl Duel {
+    /// A game in turns when there's a `clock`, or else a race.
+    pub fn new(puzzle: BoardState, difficulty: Difficulty, clock: Option<ClockConfig>) -> Self {
+        let seat = || Seat {
+            board: puzzle,
+            cursor: Cursor::default(),
+        };
+        Self {
+            puzzle,
+            difficulty,
+            seats: [seat(), seat()],
+            started: Instant::now(),
+            winner: None,
+            clock: clock.map(ChessClock::new),
+        }
+    }
+
+    /// Calls the game for the other player once a clock runs out.
+    pub fn tick(&mut self) -> Option<DuelEvent> {
+        let clock = self.clock.as_mut().filter(|clock| clock.running())?;
+        let seat = clock.turn;
+        if !clock.left(seat).is_zero() {
+            return None;
+        }
+        clock.stop();
+        self.winner = Some((1 - seat, self.started.elapsed()));
+        Some(DuelEvent::OutOfTime(seat))
+    }
+
+    /// Time since the race started, stopped at the winner's.
+    pub fn elapsed(&self) -> Duration {
+        match self.winner {
+            Some((_, time)) => time,
+            None => self.started.elapsed(),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<DuelEvent> {
+        if key.code == KeyCode::Esc {
+            return Some(DuelEvent::Leave);
+        }
+        if self.winner.is_some() {
+            return None;
+        }
+        let code = match key.code {
+            KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        let (seat, play) = [LEFT, RIGHT].iter().enumerate().find_map(|(seat, keys)| {
+            keys.iter()
+                .find(|(key, _)| *key == code)
+                .map(|&(_, play)| (seat, play))
+        })?;
+        if self.clock.as_ref().is_some_and(|clock| clock.turn != seat) {
+            return None;
+        }
+        if let Some(event) = self.tick() {
+            return Some(event);
+        }
+        let puzzle = self.puzzle;
+        let placed = self.seats[seat].play(&puzzle, play);
+        if self.seats[seat].solved() {
+            let time = self.started.elapsed();
+            self.winner = Some((seat, time));
+            if let Some(clock) = &mut self.clock {
+                clock.stop();
+            }
+            return Some(DuelEvent::Won(seat, time));
+        }
+        if placed && let Some(clock) = &mut self.clock {
+            clock.pass();
+        }
+        None
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use color_eyre::Report;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::keymap::Mode;
+
+/// Something the app does on its own that can fail and be tried again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    LoadPrefs,
+    LoadStats,
+    SavePrefs,
+    SaveStats,
+    SaveGame,
+    /// saving on the way out, the app quits once it works or is skipped
+    Quit,
+    ReloadConfig,
+    ImportCollection(PathBuf),
+}
+
+impl Operation {
+    pub fn label(&self) -> String {
+        match self {
+            Operation::LoadPrefs => "loading preferences".into(),
+            Operation::LoadStats => "loading statistics".into(),
+            Operation::SavePrefs => "saving preferences".into(),
+            Operation::SaveStats => "saving statistics".into(),
+            Operation::SaveGame => "saving the game".into(),
+            Operation::Quit => "saving before quitting".into(),
+            Operation::ReloadConfig => "reloading the config".into(),
+            Operation::ImportCollection(path) => format!("importing {}", path.display()),
+        }
+    }
+}
+
+/// A failure shown over the board with the whole error report, until the
+/// player retries or carries on.
+pub struct ErrorScreen {
+    pub operation: Operation,
+    /// the mode to go back to
+    pub resume: Mode,
+    lines: Vec<String>,
+    scroll: usize,
+}
+
+impl ErrorScreen {
+    pub fn new(operation: Operation, report: &Report, resume: Mode) -> Self {
+        let mut lines = vec![format!("{} failed:", operation.label()), String::new()];
+        let mut chain = report.chain();
+        if let Some(error) = chain.next() {
+            lines.push(error.to_string());
+        }
+        let causes: Vec<String> = chain.map(|cause| cause.to_string()).collect();
+        if !causes.is_empty() {
+            lines.push(String::new());
+            lines.push("caused by:".into());
+            for (i, cause) in causes.iter().enumerate() {
+                lines.push(format!("  {i}: {cause}"));
+            }
+        }
+        Self {
+            operation,
+            resume,
+            lines,
+            scroll: 0,
+        }
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta)
+            .min(self.lines.len().saturating_sub(1));
+    }
+}
+
+pub struct ErrorView<'a> {
+    pub screen: &'a ErrorScreen,
+    pub style: Style,
+}
+
+impl Widget for ErrorView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines: Vec<Line> = self
+            .screen
+            .lines
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        Paragraph::new(lines)
+            .style(self.style)
+            .wrap(Wrap { trim: false })
+            .scroll((self.screen.scroll as u16, 0))
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    io::{self, stdout},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+use ratatui::crossterm::{
+    event::{
+        self, DisableBracketedPaste, EnableBracketedPaste, Event as TermEvent, KeyEvent,
+        KeyEventKind, MouseEvent,
+    },
+    execute,
+};
+
+use crate::worker::{Done, Work};
+
+/// How often the input thread looks up from waiting to see if it should stop.
+const POLL: Duration = Duration::from_millis(50);
+
+/// Something for the app to react to.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// a key press, releases and repeats are left out
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    /// no input for a while, time to move the timer and animations on
+    Tick,
+    Resize(u16, u16),
+    Paste(String),
+    /// what a [`Worker`] finished, with the id it was started with
+    ///
+    /// [`Worker`]: crate::worker::Worker
+    Worked(u64, Work, Done),
+}
+
+/// Terminal input read on its own thread and handed over a channel, so the
+/// app never blocks on the terminal. Dropping this stops the thread.
+pub struct Events {
+    events: Receiver<io::Result<Event>>,
+    stop: Arc<AtomicBool>,
+    /// what workers finished, on a channel of its own so the input one
+    /// still disconnects when its senders go
+    worked: Receiver<Event>,
+    workers: Sender<Event>,
+}
+
+impl Events {
+    pub fn spawn() -> Self {
+        let (tx, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                let event = match event::poll(POLL) {
+                    Ok(false) => continue,
+                    Ok(true) => event::read(),
+                    Err(err) => Err(err),
+                };
+                let event = match event {
+                    Ok(TermEvent::Key(key)) if key.kind == KeyEventKind::Press => Event::Key(key),
+                    Ok(TermEvent::Mouse(mouse)) => Event::Mouse(mouse),
+                    Ok(TermEvent::Resize(width, height)) => Event::Resize(width, height),
+                    Ok(TermEvent::Paste(text)) => Event::Paste(text),
+                    Ok(_) => continue,
+                    Err(err) => {
+                        let _ = tx.send(Err(err));
+                        return;
+                    }
+                };
+                if tx.send(Ok(event)).is_err() {
+                    return;
+                }
+            }
+        });
+        Self::new(events, stop)
+    }
+
+    fn new(events: Receiver<io::Result<Event>>, stop: Arc<AtomicBool>) -> Self {
+        let (workers, worked) = mpsc::channel();
+        Self {
+            events,
+            stop,
+            worked,
+            workers,
+        }
+    }
+
+    /// Where workers send what they finish.
+    pub fn workers(&self) -> Sender<Event> {
+        self.workers.clone()
+    }
+
+    /// Events sent from somewhere other than the terminal, such as a remote
+    /// player's connection. Dropping the sender stops the app.
+    pub fn channel() -> (Sender<io::Result<Event>>, Self) {
+        let (tx, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        (tx, Self::new(events, stop))
+    }
+
+    /// The next event, or a tick when there's none within `tick`. Finished
+    /// work is only looked at between waits, the app ticks quickly while
+    /// it has some going.
+    pub fn next(&self, tick: Duration) -> io::Result<Event> {
+        if let Ok(event) = self.worked.try_recv() {
+            return Ok(event);
+        }
+        match self.events.recv_timeout(tick) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => Ok(Event::Tick),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::other("input thread stopped")),
+        }
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Has the terminal deliver pasted text in one piece instead of as keys.
+pub fn enable_paste() -> io::Result<()> {
+    execute!(stdout(), EnableBracketedPaste)
+}
+
+pub fn disable_paste() -> io::Result<()> {
+    execute!(stdout(), DisableBracketedPaste)
+}
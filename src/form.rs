@@ -0,0 +1,457 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+pub type Validator = Box<dyn Fn(&str) -> Result<(), String>>;
+
+/// Single line text input with a cursor.
+#[derive(Debug, Default, Clone)]
+pub struct TextInput {
+    value: String,
+    /// cursor position in chars
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        let cursor = value.chars().count();
+        Self { value, cursor }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let i = self.byte_index(self.cursor);
+        self.value.insert(i, c);
+        self.cursor += 1;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let i = self.byte_index(self.cursor);
+            self.value.remove(i);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.value.chars().count() {
+            let i = self.byte_index(self.cursor);
+            self.value.remove(i);
+        }
+    }
+
+    /// Returns true if the key was used.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.value.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.chars().count(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn spans(&self, focused: bool) -> Vec<Span<'static>> {
+        if !focused {
+            return vec![Span::raw(self.value.clone())];
+        }
+        let before: String = self.value.chars().take(self.cursor).collect();
+        let at = self.value.chars().nth(self.cursor).unwrap_or(' ');
+        let after: String = self.value.chars().skip(self.cursor + 1).collect();
+        vec![
+            Span::raw(before),
+            Span::styled(
+                at.to_string(),
+                Style::new().add_modifier(Modifier::REVERSED),
+            ),
+            Span::raw(after),
+        ]
+    }
+}
+
+pub enum Input {
+    Text(TextInput),
+    Toggle(bool),
+    Select {
+        options: Vec<String>,
+        selected: usize,
+    },
+}
+
+pub struct Field {
+    pub label: String,
+    pub input: Input,
+    validator: Option<Validator>,
+    error: Option<String>,
+}
+
+impl Field {
+    pub fn text(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::new(label, Input::Text(TextInput::new(value)))
+    }
+
+    pub fn toggle(label: impl Into<String>, on: bool) -> Self {
+        Self::new(label, Input::Toggle(on))
+    }
+
+    pub fn select(label: impl Into<String>, options: Vec<String>, selected: usize) -> Self {
+        Self::new(label, Input::Select { options, selected })
+    }
+
+    fn new(label: impl Into<String>, input: Input) -> Self {
+        Self {
+            label: label.into(),
+            input,
+            validator: None,
+            error: None,
+        }
+    }
+
+    /// Checks the text value before the form can be submitted.
+    pub fn validate(mut self, validator: impl Fn(&str) -> Result<(), String> + 'static) -> Self {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    pub fn text_value(&self) -> &str {
+        match &self.input {
+            Input::Text(input) => input.value(),
+            Input::Toggle(true) => "on",
+            Input::Toggle(false) => "off",
+            Input::Select { options, selected } => {
+                options.get(*selected).map_or("", String::as_str)
+            }
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        matches!(self.input, Input::Toggle(true))
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        match self.input {
+            Input::Select { selected, .. } => Some(selected),
+            _ => None,
+        }
+    }
+
+    fn run_validator(&mut self) -> bool {
+        self.error = self
+            .validator
+            .as_ref()
+            .and_then(|validate| validate(self.text_value()).err());
+        self.error.is_none()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let used = match &mut self.input {
+            Input::Text(input) => input.handle_key(key),
+            Input::Toggle(on) => match key.code {
+                KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => {
+                    *on = !*on;
+                    true
+                }
+                _ => false,
+            },
+            Input::Select { options, selected } => match key.code {
+                KeyCode::Left if !options.is_empty() => {
+                    *selected = (*selected + options.len() - 1) % options.len();
+                    true
+                }
+                KeyCode::Right | KeyCode::Char(' ') if !options.is_empty() => {
+                    *selected = (*selected + 1) % options.len();
+                    true
+                }
+                _ => false,
+            },
+        };
+        if used && self.error.is_some() {
+            self.run_validator();
+        }
+        used
+    }
+
+    fn line(&self, focused: bool, style: &FormStyle) -> Line<'static> {
+        let label_style = match focused {
+            true => style.focused,
+            false => style.normal,
+        };
+        let mut spans = vec![Span::styled(format!("{}: ", self.label), label_style)];
+        match &self.input {
+            Input::Text(input) => spans.extend(input.spans(focused)),
+            Input::Toggle(on) => spans.push(Span::raw(if *on { "[x]" } else { "[ ]" })),
+            Input::Select { .. } => spans.push(Span::raw(format!("< {} >", self.text_value()))),
+        }
+        Line::from(spans)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormEvent {
+    /// the key was handled (or ignored) and the form is still open
+    Pending,
+    /// a button was pressed with every field valid
+    Submitted(usize),
+    Cancelled,
+}
+
+pub struct FormStyle {
+    pub normal: Style,
+    pub focused: Style,
+    pub error: Style,
+}
+
+impl Default for FormStyle {
+    fn default() -> Self {
+        Self {
+            normal: Style::new(),
+            focused: Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            error: Style::new().fg(Color::Red),
+        }
+    }
+}
+
+/// Fields stacked vertically with the buttons below them. Focus moves over
+/// the fields first and then the buttons.
+pub struct Form {
+    pub fields: Vec<Field>,
+    pub buttons: Vec<String>,
+    pub style: FormStyle,
+    focus: usize,
+}
+
+impl Form {
+    pub fn new(fields: Vec<Field>, buttons: &[&str]) -> Self {
+        Self {
+            fields,
+            buttons: buttons.iter().map(|b| b.to_string()).collect(),
+            style: FormStyle::default(),
+            focus: 0,
+        }
+    }
+
+    fn focus_count(&self) -> usize {
+        self.fields.len() + self.buttons.len()
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % self.focus_count().max(1);
+    }
+
+    pub fn focus_previous(&mut self) {
+        let count = self.focus_count().max(1);
+        self.focus = (self.focus + count - 1) % count;
+    }
+
+    pub fn field(&self, label: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.label == label)
+    }
+
+    /// Runs every validator, focusing the first invalid field.
+    pub fn validate(&mut self) -> bool {
+        let mut first_invalid = None;
+        for (i, field) in self.fields.iter_mut().enumerate() {
+            if !field.run_validator() && first_invalid.is_none() {
+                first_invalid = Some(i);
+            }
+        }
+        if let Some(i) = first_invalid {
+            self.focus = i;
+        }
+        first_invalid.is_none()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormEvent {
+        match key.code {
+            KeyCode::Esc => return FormEvent::Cancelled,
+            KeyCode::Tab | KeyCode::Down => self.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => self.focus_previous(),
+            KeyCode::Enter => {
+                // enter on a field submits with the first button
+                let button = self.focus.saturating_sub(self.fields.len());
+                if self.validate() {
+                    return FormEvent::Submitted(button);
+                }
+            }
+            _ if self.focus < self.fields.len() => {
+                self.fields[self.focus].handle_key(key);
+            }
+            KeyCode::Left => self.focus_previous(),
+            KeyCode::Right => self.focus_next(),
+            _ => {}
+        }
+        FormEvent::Pending
+    }
+
+    pub fn height(&self) -> u16 {
+        let errors = self.fields.iter().filter(|f| f.error.is_some()).count();
+        (self.fields.len() + errors + 2) as u16
+    }
+}
+
+impl Widget for &Form {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines = Vec::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            lines.push(field.line(self.focus == i, &self.style));
+            if let Some(error) = &field.error {
+                lines.push(Line::styled(format!("  {error}"), self.style.error));
+            }
+        }
+        lines.push(Line::default());
+        let buttons: Vec<Span> = self
+            .buttons
+            .iter()
+            .enumerate()
+            .flat_map(|(i, label)| {
+                let style = match self.focus == self.fields.len() + i {
+                    true => self.style.focused,
+                    false => self.style.normal,
+                };
+                [Span::styled(format!("[ {label} ]"), style), Span::raw(" ")]
+            })
+            .collect();
+        lines.push(Line::from(buttons).centered());
+        Paragraph::new(lines).render(area, buf);
+    }
+}
+
+/// Multi line text input with a cursor, Enter starts a new line.
+#[derive(Debug, Clone)]
+pub struct TextArea {
+    lines: Vec<String>,
+    row: usize,
+    /// cursor position in chars within the row
+    col: usize,
+}
+
+impl TextArea {
+    pub fn new(value: &str) -> Self {
+        let lines: Vec<String> = value.split('\n').map(String::from).collect();
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        Self { lines, row, col }
+    }
+
+    pub fn value(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn width(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map_or(line.len(), |(i, _)| i)
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let i = self.byte_index();
+            self.lines[self.row].remove(i);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.width(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.col < self.width(self.row) {
+            let i = self.byte_index();
+            self.lines[self.row].remove(i);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    /// Returns true if the key was used.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c) => {
+                let i = self.byte_index();
+                self.lines[self.row].insert(i, c);
+                self.col += 1;
+            }
+            KeyCode::Enter => {
+                let i = self.byte_index();
+                let rest = self.lines[self.row].split_off(i);
+                self.row += 1;
+                self.col = 0;
+                self.lines.insert(self.row, rest);
+            }
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.col = self.col.saturating_sub(1),
+            KeyCode::Right => self.col = (self.col + 1).min(self.width(self.row)),
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.col.min(self.width(self.row));
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = self.col.min(self.width(self.row));
+            }
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.width(self.row),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Widget for &TextArea {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // keep the cursor's row in view
+        let scroll = (self.row + 1).saturating_sub(area.height as usize);
+        let lines: Vec<Line> = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .map(|(row, line)| {
+                if row != self.row {
+                    return Line::raw(line.clone());
+                }
+                let before: String = line.chars().take(self.col).collect();
+                let at = line.chars().nth(self.col).unwrap_or(' ');
+                let after: String = line.chars().skip(self.col + 1).collect();
+                Line::from(vec![
+                    Span::raw(before),
+                    Span::styled(
+                        at.to_string(),
+                        Style::new().add_modifier(Modifier::REVERSED),
+                    ),
+                    Span::raw(after),
+                ])
+            })
+            .collect();
+        Paragraph::new(lines).render(area, buf);
+    }
+}
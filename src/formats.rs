@@ -0,0 +1,484 @@
+use std::{collections::BTreeMap, fmt::Write as _, fs, path::Path};
+
+use color_eyre::eyre::{Result, WrapErr, bail, eyre};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    charset::GlyphSet,
+    import::{self, CapabilityReport, Imported},
+    packed::{self, Packed},
+    soduko::BoardState,
+    symbols::Symbols,
+    variant::Variant,
+};
+
+pub type Parse = Box<dyn Fn(&str) -> Result<Imported> + Send + Sync>;
+pub type Serialize = Box<dyn Fn(&BoardState) -> Result<String> + Send + Sync>;
+
+/// A file format: a name for messages and a way to read and/or write it.
+pub struct Format {
+    pub name: String,
+    parse: Option<Parse>,
+    serialize: Option<Serialize>,
+}
+
+impl Format {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            parse: None,
+            serialize: None,
+        }
+    }
+
+    pub fn parse(
+        mut self,
+        parse: impl Fn(&str) -> Result<Imported> + Send + Sync + 'static,
+    ) -> Self {
+        self.parse = Some(Box::new(parse));
+        self
+    }
+
+    pub fn serialize(
+        mut self,
+        serialize: impl Fn(&BoardState) -> Result<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.serialize = Some(Box::new(serialize));
+        self
+    }
+
+    pub fn can_import(&self) -> bool {
+        self.parse.is_some()
+    }
+
+    pub fn can_export(&self) -> bool {
+        self.serialize.is_some()
+    }
+}
+
+/// Formats by file extension. Starts with the built in ones, and anyone
+/// using the crate can add their own or replace a built in one.
+pub struct FormatRegistry {
+    formats: BTreeMap<String, Format>,
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        let fpuzzles = || Format::new("f-puzzles").parse(import::fpuzzles);
+        registry.register("json", fpuzzles());
+        registry.register("fpuzzles", fpuzzles());
+        registry.register(
+            "sdk",
+            Format::new("sdk")
+                .parse(import::sdk)
+                .serialize(|board| Ok(sdk(classic(board)?))),
+        );
+        registry.register(
+            "sdm",
+            Format::new("sdm")
+                .parse(import::sdm)
+                .serialize(|board| Ok(format!("{}\n", code(classic(board)?)))),
+        );
+        registry.register(
+            "txt",
+            Format::new("81 character code")
+                .parse(|text| {
+                    Ok(Imported {
+                        board: text.parse()?,
+                        report: CapabilityReport::default(),
+                    })
+                })
+                .serialize(|board| Ok(format!("{}\n", code(classic(board)?)))),
+        );
+        registry
+    }
+}
+
+impl FormatRegistry {
+    /// A registry without even the built in formats.
+    pub fn empty() -> Self {
+        Self {
+            formats: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a format for `extension` (without the dot, any case), handing
+    /// back the one it replaces.
+    pub fn register(&mut self, extension: &str, format: Format) -> Option<Format> {
+        self.formats.insert(extension.to_ascii_lowercase(), format)
+    }
+
+    pub fn get(&self, extension: &str) -> Option<&Format> {
+        self.formats.get(&extension.to_ascii_lowercase())
+    }
+
+    pub fn extensions(&self) -> impl Iterator<Item = &str> {
+        self.formats.keys().map(String::as_str)
+    }
+
+    fn for_path(&self, path: &Path) -> Result<&Format> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+        self.get(extension).ok_or_else(|| {
+            let known: Vec<&str> = self.extensions().collect();
+            eyre!(
+                "don't know .{extension} files, known are .{}",
+                known.join(", .")
+            )
+        })
+    }
+
+    pub fn import(&self, path: &Path) -> Result<Imported> {
+        let format = self.for_path(path)?;
+        let Some(parse) = &format.parse else {
+            bail!("{} files can only be exported", format.name);
+        };
+        let text =
+            fs::read_to_string(path).wrap_err_with(|| format!("reading {}", path.display()))?;
+        parse(&text).wrap_err_with(|| format!("importing {}", path.display()))
+    }
+
+    pub fn export(&self, board: &BoardState, path: &Path) -> Result<()> {
+        let format = self.for_path(path)?;
+        let Some(serialize) = &format.serialize else {
+            bail!("{} files can only be imported", format.name);
+        };
+        let text = serialize(board)?;
+        fs::write(path, text).wrap_err_with(|| format!("writing {}", path.display()))
+    }
+}
+
+/// The board as plain text with lines between the boxes, for printing to a
+/// terminal or pasting somewhere.
+pub fn grid(board: &BoardState, symbols: &Symbols) -> String {
+    let width = symbols.width();
+    let mut out = String::new();
+    for (r, row) in board.iter().enumerate() {
+        if r > 0 && r % 3 == 0 {
+            let dashes = "-".repeat(3 * (width + 1) + 1);
+            let _ = writeln!(out, "{}", [&dashes[1..], &dashes, &dashes[1..]].join("+"));
+        }
+        let cells: Vec<String> = row
+            .iter()
+            .map(|&cell| {
+                let symbol = symbols.cell(cell);
+                let pad = width.saturating_sub(symbol.width());
+                format!("{symbol}{}", " ".repeat(pad))
+            })
+            .collect();
+        let boxes: Vec<String> = cells.chunks(3).map(|cells| cells.join(" ")).collect();
+        let _ = writeln!(out, "{}", boxes.join(" | "));
+    }
+    out
+}
+
+/// The common 81 character format, `.` for blanks, read back by the board's
+/// `FromStr`.
+pub fn code(board: &BoardState) -> String {
+    board
+        .iter()
+        .flatten()
+        .map(|cell| cell.map_or('.', |d| char::from(b'0' + d.get())))
+        .collect()
+}
+
+/// The board for a format of bare cells, which has nowhere to keep the
+/// houses a variant adds.
+fn classic(board: &BoardState) -> Result<&BoardState> {
+    if board.variant() != Variant::Classic {
+        bail!(
+            "{} puzzles don't fit in 81 characters, share a game code instead",
+            board.variant().label()
+        );
+    }
+    Ok(board)
+}
+
+/// The board in the 81 character format when it's classic, or else as a
+/// [`share_code`], which keeps the variant. [`parse_share_code`] reads
+/// either.
+pub fn portable_code(board: &BoardState) -> String {
+    match board.variant() {
+        Variant::Classic => code(board),
+        _ => share_code(board),
+    }
+}
+
+/// Nine lines of nine cells, `.` for blanks, as `.sdk` files have them.
+pub fn sdk(board: &BoardState) -> String {
+    let code = code(board);
+    let mut out = String::new();
+    for row in code.as_bytes().chunks(9) {
+        let _ = writeln!(out, "{}", String::from_utf8_lossy(row));
+    }
+    out
+}
+
+/// A board to pass on as a [`Packed`] code, the puzzle with the digits and
+/// notes written so far when it has them.
+pub fn game_code(game: &Packed) -> String {
+    let mut bytes = game.encode();
+    packed::seal(&mut bytes);
+    packed::to_text(&bytes)
+}
+
+/// Reads a [`game_code`], telling a cut off code apart from one with
+/// changed characters. Codes from before packing, 81 cells with or without
+/// a checksum, are read as a bare puzzle.
+pub fn parse_game_code(text: &str) -> Result<Packed> {
+    let text = text.trim();
+    if is_legacy(text) {
+        return parse_legacy_code(text).map(Packed::puzzle);
+    }
+    let bytes = packed::from_text(text)?;
+    let (game, rest) = Packed::decode(&bytes)?;
+    packed::unseal(&bytes, bytes.len() - rest.len())?;
+    Ok(game)
+}
+
+/// The board as a [`game_code`] with its digits as the givens.
+pub fn share_code(board: &BoardState) -> String {
+    game_code(&Packed::puzzle(*board))
+}
+
+/// Reads a [`share_code`] or any [`game_code`], the board as far as it was
+/// played.
+pub fn parse_share_code(text: &str) -> Result<BoardState> {
+    let game = parse_game_code(text)?;
+    Ok(game.board.unwrap_or(game.puzzle))
+}
+
+/// Packed codes start with their flags byte, which base64 turns into a
+/// letter, while the 81 character codes before them start with a cell.
+fn is_legacy(text: &str) -> bool {
+    text.starts_with(|c: char| c == '.' || c.is_ascii_digit())
+}
+
+/// `<81 cells>-<4 hex digits>`, or the bare 81 cells.
+fn parse_legacy_code(text: &str) -> Result<BoardState> {
+    let Some((cells, checksum)) = text.rsplit_once('-') else {
+        if text.chars().count() < 81 {
+            bail!("code is truncated: {} of 81 cells", text.chars().count());
+        }
+        return text.parse();
+    };
+    if cells.chars().count() != 81 {
+        bail!("code is truncated: {} of 81 cells", cells.chars().count());
+    }
+    if checksum.len() != 4 {
+        bail!("code is truncated: the checksum should be 4 characters");
+    }
+    let Ok(expected) = u16::from_str_radix(checksum, 16) else {
+        bail!("code is corrupted: {checksum:?} is not a checksum");
+    };
+    if crc16(cells.as_bytes()) != expected {
+        bail!("code is corrupted: the checksum doesn't match the cells");
+    }
+    cells.parse()
+}
+
+/// A puzzle passed around for others to solve: the givens, how the author
+/// rated it and a hash of the author's solution. The hash lets a solver
+/// confirm they reached the intended answer without the code giving it away.
+#[derive(Debug, Clone, Copy)]
+pub struct Exchange {
+    pub puzzle: BoardState,
+    /// 1 to 5 stars
+    pub rating: u8,
+    solution_hash: u64,
+}
+
+impl Exchange {
+    pub fn new(puzzle: BoardState, rating: u8, solution: &BoardState) -> Result<Self> {
+        if !(1..=5).contains(&rating) {
+            bail!("a rating is 1 to 5 stars, not {rating}");
+        }
+        Ok(Self {
+            puzzle,
+            rating,
+            solution_hash: fnv1a(code(solution).as_bytes()),
+        })
+    }
+
+    /// The packed puzzle, the rating and the hash, sealed like a
+    /// [`game_code`].
+    pub fn code(&self) -> String {
+        let mut bytes = Packed::puzzle(self.puzzle).encode();
+        bytes.push(self.rating);
+        bytes.extend(self.solution_hash.to_be_bytes());
+        packed::seal(&mut bytes);
+        packed::to_text(&bytes)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if is_legacy(text) {
+            return Self::parse_legacy(text);
+        }
+        let bytes = packed::from_text(text)?;
+        let (game, rest) = Packed::decode(&bytes)?;
+        if game.board.is_some() {
+            bail!("an exchange code is a bare puzzle, not a game");
+        }
+        let Some((&rating, rest)) = rest.split_first() else {
+            bail!("code is truncated: no rating");
+        };
+        let Some(hash) = rest.first_chunk::<8>() else {
+            bail!("code is truncated: no solution hash");
+        };
+        packed::unseal(&bytes, bytes.len() - rest.len() + 8)?;
+        if !(1..=5).contains(&rating) {
+            bail!("{rating} is not a rating");
+        }
+        Ok(Self {
+            puzzle: game.puzzle,
+            rating,
+            solution_hash: u64::from_be_bytes(*hash),
+        })
+    }
+
+    /// `<81 cells>-<rating>-<16 hex digits>-<4 hex digits>`, as exchange
+    /// codes were before packing.
+    fn parse_legacy(text: &str) -> Result<Self> {
+        let Some((body, checksum)) = text.rsplit_once('-') else {
+            bail!("code is truncated: no checksum");
+        };
+        let parts: Vec<&str> = body.split('-').collect();
+        let [cells, rating, hash] = parts[..] else {
+            bail!("code is truncated: expected cells, rating and solution hash");
+        };
+        if cells.chars().count() != 81 {
+            bail!("code is truncated: {} of 81 cells", cells.chars().count());
+        }
+        if checksum.len() != 4 || hash.len() != 16 {
+            bail!("code is truncated");
+        }
+        let expected = u16::from_str_radix(checksum, 16)
+            .map_err(|_| eyre!("code is corrupted: {checksum:?} is not a checksum"))?;
+        if crc16(body.as_bytes()) != expected {
+            bail!("code is corrupted: the checksum doesn't match");
+        }
+        Ok(Self {
+            puzzle: cells.parse()?,
+            rating: rating
+                .parse()
+                .ok()
+                .filter(|r| (1..=5).contains(r))
+                .ok_or_else(|| eyre!("{rating:?} is not a rating"))?,
+            solution_hash: u64::from_str_radix(hash, 16)?,
+        })
+    }
+
+    /// Whether a finished board is the one the author had in mind.
+    pub fn matches(&self, board: &BoardState) -> bool {
+        fnv1a(code(board).as_bytes()) == self.solution_hash
+    }
+
+    pub fn stars(&self, glyphs: GlyphSet) -> String {
+        let rating = self.rating as usize;
+        let (full, empty) = (glyphs.pick("★", "*"), glyphs.pick("☆", "."));
+        format!("{}{}", full.repeat(rating), empty.repeat(5 - rating))
+    }
+}
+
+/// 64 bit FNV-1a. Not a secret, only enough that nobody reads the solution
+/// off the code by accident.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// CRC-16/CCITT-FALSE. Catches every single bit flip and any burst of up to
+/// 16 changed bits.
+pub(crate) fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = match crc & 0x8000 != 0 {
+                true => (crc << 1) ^ 0x1021,
+                false => crc << 1,
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generator, notes::Notes, rng::Rng};
+
+    fn game(rng: &mut Rng) -> Packed {
+        let solution = generator::solved_grid(rng);
+        let puzzle = generator::dig(solution, rng);
+        let mut notes = Notes::default();
+        notes.toggle(0, 0, 4);
+        notes.toggle(8, 8, 9);
+        Packed::game(puzzle, solution, notes)
+    }
+
+    #[test]
+    fn every_bit_flip_is_caught() {
+        let mut rng = Rng::new(991);
+        let bytes = packed::from_text(&game_code(&game(&mut rng))).unwrap();
+        for bit in 0..bytes.len() * 8 {
+            let mut flipped = bytes.clone();
+            flipped[bit / 8] ^= 1 << (bit % 8);
+            let err = parse_game_code(&packed::to_text(&flipped)).unwrap_err();
+            let message = err.to_string();
+            assert!(
+                message.contains("corrupted") || message.contains("truncated"),
+                "bit {bit}: {message}"
+            );
+        }
+    }
+
+    #[test]
+    fn a_changed_exchange_code_is_corrupted() {
+        let mut rng = Rng::new(5);
+        let solution = generator::solved_grid(&mut rng);
+        let exchange = Exchange::new(generator::dig(solution, &mut rng), 3, &solution).unwrap();
+        let bytes = packed::from_text(&exchange.code()).unwrap();
+        for bit in 0..bytes.len() * 8 {
+            let mut flipped = bytes.clone();
+            flipped[bit / 8] ^= 1 << (bit % 8);
+            assert!(
+                Exchange::parse(&packed::to_text(&flipped)).is_err(),
+                "bit {bit}"
+            );
+        }
+        assert!(
+            Exchange::parse(&exchange.code())
+                .unwrap()
+                .matches(&solution)
+        );
+    }
+
+    #[test]
+    fn legacy_codes_catch_a_changed_cell() {
+        let mut rng = Rng::new(81);
+        let cells = code(&generator::dig(generator::solved_grid(&mut rng), &mut rng));
+        let text = format!("{cells}-{:04x}", crc16(cells.as_bytes()));
+        assert!(parse_game_code(&text).is_ok());
+        for pos in 0..81 {
+            let mut changed = text.clone().into_bytes();
+            changed[pos] = match changed[pos] {
+                b'.' => b'1',
+                b'9' => b'.',
+                d => d + 1,
+            };
+            let err = parse_game_code(std::str::from_utf8(&changed).unwrap()).unwrap_err();
+            assert!(err.to_string().contains("corrupted"), "{err}");
+        }
+        let err = parse_game_code(&text[..60]).unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{err}");
+    }
+}
@@ -0,0 +1,355 @@
+use std::str::FromStr;
+
+use color_eyre::eyre::{Report, Result, bail};
+use serde::Deserialize;
+
+use crate::{
+    difficulty::{Difficulty, Tier},
+    hints::Technique,
+    rating,
+    rng::Rng,
+    soduko::BoardState,
+    variant::Variant,
+};
+
+/// Fewest clues any 9x9 puzzle with a single solution has.
+const MIN_CLUES: usize = 17;
+/// Digs tried for a puzzle its difficulty admits before settling for the
+/// last one.
+const TIER_ATTEMPTS: usize = 20;
+/// Share of the cells left as clues on boards other than 9x9.
+const SIZED_CLUE_SHARE: (usize, usize) = (2, 5);
+/// Random digits put down before the solver fills in a variant's grid, per
+/// row of the board.
+const SEEDS_PER_ROW: usize = 1;
+
+/// Patterns the clues of a carved puzzle keep.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Symmetry {
+    None,
+    /// the same after a half turn
+    #[default]
+    Rotational,
+    /// mirrored left to right
+    Mirror,
+    /// mirrored along the main diagonal
+    Diagonal,
+}
+
+impl Symmetry {
+    /// The cells that are emptied together with `pos`, itself included, on
+    /// a board `size` cells across.
+    fn orbit(self, pos: usize, size: usize) -> Vec<usize> {
+        let (row, col) = (pos / size, pos % size);
+        let other = match self {
+            Symmetry::None => pos,
+            Symmetry::Rotational => size * size - 1 - pos,
+            Symmetry::Mirror => row * size + (size - 1 - col),
+            Symmetry::Diagonal => col * size + row,
+        };
+        match other == pos {
+            true => vec![pos],
+            false => vec![pos, other],
+        }
+    }
+}
+
+/// A new puzzle of `variant` with a single solution, as its difficulty's
+/// [`Tier`] has it.
+///
+/// [`Tier`]: crate::difficulty::Tier
+pub fn generate(difficulty: Difficulty, variant: Variant, rng: &mut Rng) -> BoardState {
+    carve(
+        variant_grid(variant, rng),
+        difficulty.tier(),
+        Symmetry::None,
+        rng,
+    )
+}
+
+/// A puzzle with a single solution, `solution`, and its clues laid out
+/// with `symmetry`. Lets a player hide a date or a pattern in the answer.
+pub fn carve(solution: BoardState, tier: Tier, symmetry: Symmetry, rng: &mut Rng) -> BoardState {
+    let mut carving = Carving::new(solution, tier, symmetry);
+    loop {
+        if let Some(puzzle) = carving.step(rng) {
+            return puzzle;
+        }
+    }
+}
+
+/// [`carve`] one dig at a time, for running between frames where there's
+/// no thread to run it on.
+#[derive(Debug, Clone)]
+pub struct Carving {
+    solution: BoardState,
+    tier: Tier,
+    symmetry: Symmetry,
+    attempts: usize,
+}
+
+impl Carving {
+    pub fn new(solution: BoardState, tier: Tier, symmetry: Symmetry) -> Self {
+        Self {
+            solution,
+            tier,
+            symmetry,
+            attempts: 0,
+        }
+    }
+
+    /// Digs once. `Some` with the puzzle once the tier admits it, or with
+    /// the last one dug once the attempts run out.
+    pub fn step(&mut self, rng: &mut Rng) -> Option<BoardState> {
+        let puzzle = dig_to(self.solution, self.tier.min_clues, self.symmetry, rng);
+        self.attempts += 1;
+        (self.attempts >= TIER_ATTEMPTS || self.tier.admits(&puzzle)).then_some(puzzle)
+    }
+}
+
+/// A random full grid: the solver's grid shuffled and relabelled.
+pub fn solved_grid(rng: &mut Rng) -> BoardState {
+    let base = BoardState::default()
+        .solve()
+        .expect("the empty board is solvable");
+    let mut labels = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+    rng.shuffle(&mut labels);
+    relabel(shuffled(&base, rng), |d| labels[d as usize - 1])
+}
+
+/// A random full grid of `variant`. Shuffling rows and columns would break
+/// the diagonals and windows, so these come from [`seeded_grid`] instead.
+pub fn variant_grid(variant: Variant, rng: &mut Rng) -> BoardState {
+    match variant {
+        Variant::Classic => solved_grid(rng),
+        _ => seeded_grid(BoardState::empty(variant), rng),
+    }
+}
+
+/// A random full grid that has `empty`'s size and variant: the solver
+/// finishing a few random digits.
+pub fn seeded_grid<const N: usize>(empty: BoardState<N>, rng: &mut Rng) -> BoardState<N> {
+    loop {
+        let mut board = empty;
+        for _ in 0..N * SEEDS_PER_ROW {
+            let pos = rng.below(N * N);
+            let (row, col) = (pos / N, pos % N);
+            if board[row][col].is_some() {
+                continue;
+            }
+            let candidates = board.candidates(row, col);
+            let digits: Vec<u8> = (1..=N as u8)
+                .filter(|d| candidates & (1 << d) != 0)
+                .collect();
+            if digits.is_empty() {
+                break;
+            }
+            board[row][col] = digits[rng.below(digits.len())].into();
+        }
+        if let Some(grid) = board.solve() {
+            return grid;
+        }
+    }
+}
+
+/// A puzzle with a single solution on a board other than 9x9. There are no
+/// tiers for those, so it keeps [`SIZED_CLUE_SHARE`] of the cells, which on
+/// 16x16 boards is also what keeps digging from taking seconds.
+pub fn generate_sized<const N: usize>(rng: &mut Rng) -> BoardState<N> {
+    let grid = seeded_grid(BoardState::default(), rng);
+    dig_to(
+        grid,
+        N * N * SIZED_CLUE_SHARE.0 / SIZED_CLUE_SHARE.1,
+        Symmetry::None,
+        rng,
+    )
+}
+
+/// Every value `d` replaced by `f(d)`, which has to be a permutation of 1-9.
+pub fn relabel(mut grid: BoardState, f: impl Fn(u8) -> u8) -> BoardState {
+    for row in grid.iter_mut() {
+        for cell in row.iter_mut() {
+            if let Some(d) = **cell {
+                *cell = f(d.get()).into();
+            }
+        }
+    }
+    grid
+}
+
+/// Swaps rows within bands, bands, columns within stacks and stacks, and
+/// maybe transposes. Every result is still a valid grid.
+pub fn shuffled(grid: &BoardState, rng: &mut Rng) -> BoardState {
+    let order = |rng: &mut Rng| {
+        let mut bands = [0, 1, 2];
+        rng.shuffle(&mut bands);
+        let mut lines = Vec::with_capacity(9);
+        for band in bands {
+            let mut inner = [0, 1, 2];
+            rng.shuffle(&mut inner);
+            lines.extend(inner.map(|i| band * 3 + i));
+        }
+        lines
+    };
+    let rows = order(rng);
+    let cols = order(rng);
+    let transpose = rng.below(2) == 1;
+    let mut out = BoardState::default();
+    for r in 0..9 {
+        for c in 0..9 {
+            let (sr, sc) = match transpose {
+                true => (cols[c], rows[r]),
+                false => (rows[r], cols[c]),
+            };
+            out[r][c] = grid[sr][sc];
+        }
+    }
+    out
+}
+
+/// Which cells of a puzzle are clues, for novelty puzzles whose givens draw
+/// a shape. Read from nine lines of nine characters, `.`, `0` or a space
+/// for a blank and anything else for a clue. A short or empty line is blank
+/// to the end, blank lines after the ninth are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mask([[bool; 9]; 9]);
+
+impl Mask {
+    pub fn clues(&self) -> usize {
+        self.0.iter().flatten().filter(|&&clue| clue).count()
+    }
+}
+
+impl FromStr for Mask {
+    type Err = Report;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut lines: Vec<&str> = text.lines().collect();
+        while lines.len() > 9 && lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        if lines.len() != 9 {
+            bail!("a mask has 9 lines, this one has {}", lines.len());
+        }
+        let mut mask = [[false; 9]; 9];
+        for (r, line) in lines.iter().enumerate() {
+            let cells: Vec<char> = line.trim_end().chars().collect();
+            if cells.len() > 9 {
+                bail!("line {} of the mask is longer than 9 cells", r + 1);
+            }
+            for (c, cell) in cells.into_iter().enumerate() {
+                mask[r][c] = !matches!(cell, '.' | '0' | ' ');
+            }
+        }
+        Ok(Self(mask))
+    }
+}
+
+/// A puzzle with clues exactly where `mask` has them and a single solution.
+/// Fills in random grids until one works, giving up after `attempts`.
+pub fn from_mask(mask: &Mask, attempts: u32, rng: &mut Rng) -> Result<BoardState> {
+    if mask.clues() < MIN_CLUES {
+        bail!(
+            "the mask has {} clues, a puzzle needs at least {MIN_CLUES} for a single solution",
+            mask.clues()
+        );
+    }
+    for _ in 0..attempts {
+        let mut puzzle = solved_grid(rng);
+        for (r, row) in mask.0.iter().enumerate() {
+            for (c, &clue) in row.iter().enumerate() {
+                if !clue {
+                    puzzle[r][c] = 0.into();
+                }
+            }
+        }
+        if puzzle.count_solutions(2) == 1 {
+            return Ok(puzzle);
+        }
+    }
+    bail!("no grid gave the mask a single solution in {attempts} attempts")
+}
+
+/// A puzzle that takes `needs` somewhere and nothing harder than `up_to`
+/// to solve with the hint techniques. Digs out random grids and rates them
+/// until one works, giving up after `attempts`.
+pub fn with_techniques(
+    needs: Option<Technique>,
+    up_to: Technique,
+    attempts: u32,
+    rng: &mut Rng,
+) -> Result<BoardState> {
+    if let Some(needs) = needs
+        && needs > up_to
+    {
+        bail!(
+            "a puzzle can't need {} and nothing harder than {}",
+            needs.name(),
+            up_to.name()
+        );
+    }
+    for _ in 0..attempts {
+        let puzzle = dig(solved_grid(rng), rng);
+        let Some(used) = rating::techniques(&puzzle) else {
+            continue;
+        };
+        let hardest = used.last().copied().unwrap_or(Technique::NakedSingle);
+        if hardest <= up_to && needs.is_none_or(|needs| used.contains(&needs)) {
+            return Ok(puzzle);
+        }
+    }
+    bail!("no puzzle in {attempts} attempts took the techniques asked for")
+}
+
+/// Empties cells in random order, keeping a clue whenever removing it would
+/// let another value fit there, so the puzzle keeps a single solution.
+pub fn dig<const N: usize>(solution: BoardState<N>, rng: &mut Rng) -> BoardState<N> {
+    dig_to(solution, 0, Symmetry::None, rng)
+}
+
+/// Like [`dig`], emptying the cells of a symmetry orbit together and
+/// stopping once only `min_clues` clues are left.
+///
+/// Any second solution after emptying an orbit has to differ in one of its
+/// cells, so checking those cells is enough to keep the solution unique.
+fn dig_to<const N: usize>(
+    solution: BoardState<N>,
+    min_clues: usize,
+    symmetry: Symmetry,
+    rng: &mut Rng,
+) -> BoardState<N> {
+    let mut cells: Vec<usize> = (0..N * N).collect();
+    rng.shuffle(&mut cells);
+    let mut puzzle = solution;
+    let mut clues = N * N;
+    for pos in cells {
+        let orbit = symmetry.orbit(pos, N);
+        if puzzle[pos / N][pos % N].is_none() || clues - orbit.len() < min_clues {
+            continue;
+        }
+        for &cell in &orbit {
+            puzzle[cell / N][cell % N] = 0.into();
+        }
+        let ambiguous = orbit.iter().any(|&cell| {
+            let (row, col) = (cell / N, cell % N);
+            let value = solution[row][col].expect("solutions are full").get();
+            let candidates = puzzle.candidates(row, col) & !(1 << value);
+            (1..=N as u8)
+                .filter(|d| candidates & (1 << d) != 0)
+                .any(|d| {
+                    let mut other = puzzle;
+                    other[row][col] = d.into();
+                    other.solvable()
+                })
+        });
+        if ambiguous {
+            for &cell in &orbit {
+                puzzle[cell / N][cell % N] = solution[cell / N][cell % N];
+            }
+        } else {
+            clues -= orbit.len();
+        }
+    }
+    puzzle
+}
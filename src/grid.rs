@@ -0,0 +1,164 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    symbols::line,
+    text::Text,
+    widgets::{StatefulWidget, Widget},
+};
+
+use crate::{
+    config::CellConfig,
+    cursor::Cursor,
+    soduko::{CellState, box_dims},
+};
+
+/// What goes in one cell: its lines, over a style of its own.
+pub struct GridCell<'a> {
+    /// the digit written in it, whatever the lines show
+    pub content: CellState,
+    pub text: Text<'a>,
+    pub style: Style,
+}
+
+/// The board: cells `padding` columns apart with a line between the boxes,
+/// drawn in the middle of the padding where it runs between columns. The
+/// selected row, column and cell are highlighted on top of whatever the
+/// cells brought, the cells of a multi-cell selection under the cursor.
+pub struct SudokuGrid<'a> {
+    /// cells across, 9 but on 6x6 and 16x16 boards
+    pub size: usize,
+    /// row by row, `size * size` of them
+    pub cells: Vec<GridCell<'a>>,
+    pub cell: CellConfig,
+    /// under everything, the cells and dividers included
+    pub style: Style,
+    pub divider_style: Style,
+    pub lines: line::Set<'static>,
+    pub row_highlight_style: Style,
+    pub column_highlight_style: Style,
+    pub cell_highlight_style: Style,
+    /// for the other cells holding the selected cell's digit, `None` to
+    /// leave them be
+    pub same_digit_style: Option<Style>,
+    /// the cells picked out for a change to all of them, as (row, col)
+    pub selection: Vec<(usize, usize)>,
+    pub selection_style: Style,
+}
+
+/// How far column `col` of a board `size` cells across starts from its left.
+fn column_x(cell: CellConfig, size: usize, col: usize) -> u16 {
+    let stack = (col / box_dims(size).1) as u16;
+    col as u16 * (cell.width + cell.padding) + stack
+}
+
+/// How far row `row` of a board `size` cells down starts from its top.
+fn row_y(cell: CellConfig, size: usize, row: usize) -> u16 {
+    let band = (row / box_dims(size).0) as u16;
+    row as u16 * cell.height + band
+}
+
+fn cell_rect(board: Rect, cell: CellConfig, size: usize, row: usize, col: usize) -> Rect {
+    Rect::new(
+        board.x + column_x(cell, size, col),
+        board.y + row_y(cell, size, row),
+        cell.width,
+        cell.height,
+    )
+    .intersection(board)
+}
+
+/// The cell under a point on a 9x9 board. Padding and dividers go with the
+/// cell before them.
+pub fn cell_at(board: Rect, cell: CellConfig, column: u16, row: u16) -> Option<(usize, usize)> {
+    if !board.contains((column, row).into()) {
+        return None;
+    }
+    let (x, y) = (column - board.x, row - board.y);
+    if x >= column_x(cell, 9, 8) + cell.width || y >= row_y(cell, 9, 8) + cell.height {
+        return None;
+    }
+    let col = (0..9).rev().find(|&col| column_x(cell, 9, col) <= x)?;
+    let r = (0..9).rev().find(|&r| row_y(cell, 9, r) <= y)?;
+    Some((r, col))
+}
+
+impl StatefulWidget for SudokuGrid<'_> {
+    type State = Cursor;
+
+    fn render(self, area: Rect, buf: &mut Buffer, cursor: &mut Cursor) {
+        let (cell, size) = (self.cell, self.size);
+        let board = Rect::new(
+            area.x,
+            area.y,
+            cell.board_width_of(size),
+            cell.board_height_of(size),
+        );
+        let board = board.intersection(area);
+        buf.set_style(board, self.style);
+
+        let (box_rows, box_cols) = box_dims(size);
+        let columns: Vec<u16> = (box_cols..size)
+            .step_by(box_cols)
+            .map(|col| board.x + column_x(cell, size, col - 1) + cell.width + cell.padding / 2)
+            .collect();
+        let rows: Vec<u16> = (box_rows..size)
+            .step_by(box_rows)
+            .map(|row| board.y + row_y(cell, size, row) - 1)
+            .collect();
+        for y in board.top()..board.bottom() {
+            for &x in &columns {
+                let symbol = match rows.contains(&y) {
+                    true => self.lines.cross,
+                    false => self.lines.vertical,
+                };
+                if let Some(c) = buf.cell_mut((x, y)) {
+                    c.set_symbol(symbol).set_style(self.divider_style);
+                }
+            }
+        }
+        for &y in &rows {
+            for x in (board.left()..board.right()).filter(|x| !columns.contains(x)) {
+                if let Some(c) = buf.cell_mut((x, y)) {
+                    c.set_symbol(self.lines.horizontal)
+                        .set_style(self.divider_style);
+                }
+            }
+        }
+
+        let (row, col) = cursor.cell();
+        let selected = self.cells.get(row * size + col).and_then(|c| *c.content);
+        for (i, grid_cell) in self.cells.into_iter().enumerate() {
+            let rect = cell_rect(board, cell, size, i / size, i % size);
+            buf.set_style(rect, grid_cell.style);
+            grid_cell.text.render(rect, buf);
+            if let Some(style) = self.same_digit_style
+                && selected.is_some()
+                && *grid_cell.content == selected
+            {
+                buf.set_style(rect, style);
+            }
+        }
+
+        let selected_row = Rect::new(
+            board.x,
+            board.y + row_y(cell, size, row),
+            board.width,
+            cell.height,
+        );
+        buf.set_style(selected_row.intersection(board), self.row_highlight_style);
+        for r in 0..size {
+            buf.set_style(
+                cell_rect(board, cell, size, r, col),
+                self.column_highlight_style,
+            );
+        }
+        for &(r, c) in &self.selection {
+            buf.set_style(cell_rect(board, cell, size, r, c), self.selection_style);
+        }
+        buf.set_style(
+            cell_rect(board, cell, size, row, col),
+            self.cell_highlight_style,
+        );
+    }
+}
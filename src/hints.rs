@@ -0,0 +1,465 @@
+use std::fmt::{self, Display, Formatter};
+
+use clap::ValueEnum;
+
+use crate::{
+    soduko::{BoardState, House},
+    variant::Variant,
+};
+
+/// Solving techniques a person would use, easiest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Technique {
+    /// the only value left for a cell
+    NakedSingle,
+    /// the only cell left for a value in a house
+    HiddenSingle,
+    /// a value confined to one line of a box can't go elsewhere on that line
+    PointingPair,
+    /// a value confined to one box within a line can't go elsewhere in that box
+    BoxLineReduction,
+    /// two cells of a house sharing the same two candidates
+    NakedPair,
+    /// a value confined to the same two columns in two rows, or the other
+    /// way around, can't go elsewhere in those columns
+    XWing,
+    /// the same with three rows and three columns
+    Swordfish,
+}
+
+impl Technique {
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked single",
+            Technique::HiddenSingle => "hidden single",
+            Technique::PointingPair => "pointing pair",
+            Technique::BoxLineReduction => "box/line reduction",
+            Technique::NakedPair => "naked pair",
+            Technique::XWing => "x-wing",
+            Technique::Swordfish => "swordfish",
+        }
+    }
+
+    /// How the technique works, for a hint's explanation.
+    pub fn description(self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "The cell can take no other value.",
+            Technique::HiddenSingle => "No other cell of the house can take the value.",
+            Technique::PointingPair => {
+                "Within a box the value only fits on one row or column, so it can't go \
+                 anywhere else on that line."
+            }
+            Technique::BoxLineReduction => {
+                "Within a row or column the value only fits in one box, so it can't go \
+                 anywhere else in that box."
+            }
+            Technique::NakedPair => {
+                "Two cells of a house can only take the same two values, so no other \
+                 cell of the house can take them."
+            }
+            Technique::XWing => {
+                "In two rows the value only fits in the same two columns, so it takes \
+                 those columns in the two rows and nowhere else in them, or the same \
+                 with rows and columns swapped."
+            }
+            Technique::Swordfish => "An x-wing over three rows and three columns.",
+        }
+    }
+}
+
+/// The next value a person could place, how to find it and the cells that
+/// show why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    pub row: usize,
+    pub col: usize,
+    pub digit: u8,
+    /// the house or cells the reasoning looks at
+    pub cells: Vec<(usize, usize)>,
+    /// elimination techniques needed before the placement shows up
+    pub after: Vec<Technique>,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} at r{}c{}",
+            self.technique.name(),
+            self.digit,
+            self.row + 1,
+            self.col + 1
+        )?;
+        if !self.after.is_empty() {
+            let after: Vec<_> = self.after.iter().map(|t| t.name()).collect();
+            write!(f, " after {}", after.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Step {
+    /// The step spelled out a line at a time: where it places what, how
+    /// each technique it takes works and the cells to look at.
+    pub fn explanation(&self) -> Vec<String> {
+        let mut lines = vec![self.to_string(), String::new()];
+        for &technique in &self.after {
+            lines.push(format!("First, {}:", technique.name()));
+            lines.push(format!("  {}", technique.description()));
+        }
+        lines.push(format!("Then, {}:", self.technique.name()));
+        lines.push(format!("  {}", self.technique.description()));
+        lines.push(String::new());
+        lines.push("Look at:".to_string());
+        lines.extend(
+            self.cells
+                .iter()
+                .map(|(row, col)| format!("  r{}c{}", row + 1, col + 1)),
+        );
+        lines
+    }
+}
+
+type Candidates = [[u32; 9]; 9];
+
+/// Finds the next placement using singles, eliminating candidates with the
+/// other techniques when no single is left. `None` when the board has a
+/// conflict or needs more than these techniques.
+pub fn next_step(board: &BoardState) -> Option<Step> {
+    if !board.check() {
+        return None;
+    }
+    let mut candidates = candidates(board);
+    let mut after = Vec::new();
+    loop {
+        if let Some(mut step) = naked_singles(&candidates)
+            .chain(hidden_singles(&candidates, board.variant()))
+            .next()
+        {
+            step.after = after;
+            return Some(step);
+        }
+        let technique = [
+            (
+                Technique::PointingPair,
+                pointing as fn(&mut Candidates) -> bool,
+            ),
+            (Technique::BoxLineReduction, box_line),
+            (Technique::NakedPair, naked_pair),
+            (Technique::XWing, x_wing),
+            (Technique::Swordfish, swordfish),
+        ]
+        .into_iter()
+        .find_map(|(technique, eliminate)| eliminate(&mut candidates).then_some(technique))?;
+        if !after.contains(&technique) {
+            after.push(technique);
+        }
+    }
+}
+
+/// Every naked and hidden single on `board` as it is, without eliminating
+/// anything first. The coach judges placements by these.
+pub fn singles(board: &BoardState) -> Vec<Step> {
+    let candidates = candidates(board);
+    naked_singles(&candidates)
+        .chain(hidden_singles(&candidates, board.variant()))
+        .collect()
+}
+
+fn candidates(board: &BoardState) -> Candidates {
+    let mut candidates: Candidates = [[0; 9]; 9];
+    for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+        if board[row][col].is_none() {
+            candidates[row][col] = board.candidates(row, col);
+        }
+    }
+    candidates
+}
+
+fn digits(mask: u32) -> impl Iterator<Item = u8> {
+    (1..=9).filter(move |d| mask & (1 << d) != 0)
+}
+
+fn box_of(row: usize, col: usize) -> usize {
+    row / 3 * 3 + col / 3
+}
+
+fn naked_singles(candidates: &Candidates) -> impl Iterator<Item = Step> {
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(r, c)| candidates[r][c].count_ones() == 1)
+        .map(|(row, col)| Step {
+            technique: Technique::NakedSingle,
+            row,
+            col,
+            digit: candidates[row][col].trailing_zeros() as u8,
+            cells: vec![(row, col)],
+            after: Vec::new(),
+        })
+}
+
+fn hidden_singles(candidates: &Candidates, variant: Variant) -> impl Iterator<Item = Step> {
+    variant.houses().flat_map(move |house| {
+        (1..=9).filter_map(move |d| {
+            let mut spots = house
+                .cells()
+                .filter(|&(r, c)| candidates[r][c] & (1 << d) != 0);
+            let (Some((row, col)), None) = (spots.next(), spots.next()) else {
+                return None;
+            };
+            Some(Step {
+                technique: Technique::HiddenSingle,
+                row,
+                col,
+                digit: d,
+                cells: house.cells().collect(),
+                after: Vec::new(),
+            })
+        })
+    })
+}
+
+/// Removes `d` from the cells of `house` that `keep` rejects. True if any
+/// candidate went.
+fn eliminate(
+    candidates: &mut Candidates,
+    house: House,
+    d: u8,
+    keep: impl Fn(usize, usize) -> bool,
+) -> bool {
+    let mut changed = false;
+    for (r, c) in house.cells().filter(|&(r, c)| !keep(r, c)) {
+        if candidates[r][c] & (1 << d) != 0 {
+            candidates[r][c] &= !(1 << d);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// The single line of `spots` in both directions, if there is one.
+fn line_of(spots: &[(usize, usize)]) -> Option<House> {
+    let (r, c) = *spots.first()?;
+    if spots.iter().all(|&(row, _)| row == r) {
+        Some(House::Row(r))
+    } else if spots.iter().all(|&(_, col)| col == c) {
+        Some(House::Column(c))
+    } else {
+        None
+    }
+}
+
+fn spots(candidates: &Candidates, house: House, d: u8) -> Vec<(usize, usize)> {
+    house
+        .cells()
+        .filter(|&(r, c)| candidates[r][c] & (1 << d) != 0)
+        .collect()
+}
+
+fn pointing(candidates: &mut Candidates) -> bool {
+    for b in 0..9 {
+        for d in 1..=9 {
+            let spots = spots(candidates, House::Box(b), d);
+            if spots.len() < 2 {
+                continue;
+            }
+            if let Some(line) = line_of(&spots)
+                && eliminate(candidates, line, d, |r, c| box_of(r, c) == b)
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn box_line(candidates: &mut Candidates) -> bool {
+    for line in (0..9).map(House::Row).chain((0..9).map(House::Column)) {
+        for d in 1..=9 {
+            let spots = spots(candidates, line, d);
+            let Some(&(r, c)) = spots.first() else {
+                continue;
+            };
+            let b = box_of(r, c);
+            if spots.len() >= 2
+                && spots.iter().all(|&(r, c)| box_of(r, c) == b)
+                && eliminate(candidates, House::Box(b), d, |r, c| {
+                    line.cells().any(|cell| cell == (r, c))
+                })
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn naked_pair(candidates: &mut Candidates) -> bool {
+    for house in House::all() {
+        let cells: Vec<_> = house.cells().collect();
+        for (i, &(r1, c1)) in cells.iter().enumerate() {
+            let pair = candidates[r1][c1];
+            if pair.count_ones() != 2 {
+                continue;
+            }
+            let Some(&(r2, c2)) = cells[i + 1..]
+                .iter()
+                .find(|&&(r, c)| candidates[r][c] == pair)
+            else {
+                continue;
+            };
+            let mut changed = false;
+            for d in digits(pair) {
+                changed |= eliminate(candidates, house, d, |r, c| {
+                    (r, c) == (r1, c1) || (r, c) == (r2, c2)
+                });
+            }
+            if changed {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn x_wing(candidates: &mut Candidates) -> bool {
+    fish(candidates, 2)
+}
+
+fn swordfish(candidates: &mut Candidates) -> bool {
+    fish(candidates, 3)
+}
+
+/// `size` rows in which a value only fits within the same `size` columns
+/// take it out of the rest of those columns, and the same with rows and
+/// columns swapped.
+fn fish(candidates: &mut Candidates, size: usize) -> bool {
+    for d in 1..=9 {
+        for by_row in [true, false] {
+            let cell = |base: usize, cross: usize| match by_row {
+                true => (base, cross),
+                false => (cross, base),
+            };
+            // where the value fits in each base line, one bit per cross line
+            let lines: Vec<(usize, u16)> = (0..9)
+                .filter_map(|base| {
+                    let mask = (0..9)
+                        .filter(|&cross| {
+                            let (r, c) = cell(base, cross);
+                            candidates[r][c] & (1 << d) != 0
+                        })
+                        .fold(0u16, |mask, cross| mask | 1 << cross);
+                    (2..=size as u32)
+                        .contains(&mask.count_ones())
+                        .then_some((base, mask))
+                })
+                .collect();
+            for combo in combinations(lines.len(), size) {
+                let cover = combo.iter().fold(0, |mask, &i| mask | lines[i].1);
+                if cover.count_ones() as usize != size {
+                    continue;
+                }
+                let bases: Vec<usize> = combo.iter().map(|&i| lines[i].0).collect();
+                let mut changed = false;
+                for cross in (0..9).filter(|cross| cover & (1 << cross) != 0) {
+                    let house = match by_row {
+                        true => House::Column(cross),
+                        false => House::Row(cross),
+                    };
+                    changed |= eliminate(candidates, house, d, |r, c| {
+                        bases.contains(if by_row { &r } else { &c })
+                    });
+                }
+                if changed {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Every way to pick `k` of `0..n`, in increasing order.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    (k - 1..n)
+        .flat_map(|last| {
+            combinations(last, k - 1).into_iter().map(move |mut combo| {
+                combo.push(last);
+                combo
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: u32 = 0b11_1111_1110;
+
+    /// Every value open in every cell but `d` in `cells`.
+    fn without(d: u8, cells: impl Iterator<Item = (usize, usize)>) -> Candidates {
+        let mut candidates = [[ALL; 9]; 9];
+        for (r, c) in cells {
+            candidates[r][c] &= !(1 << d);
+        }
+        candidates
+    }
+
+    fn has(candidates: &Candidates, (r, c): (usize, usize), d: u8) -> bool {
+        candidates[r][c] & (1 << d) != 0
+    }
+
+    #[test]
+    fn finds_a_naked_single() {
+        let board: BoardState = format!("12345678.{}", ".".repeat(72)).parse().unwrap();
+        let step = singles(&board)
+            .into_iter()
+            .find(|step| step.technique == Technique::NakedSingle)
+            .unwrap();
+        assert_eq!((step.row, step.col, step.digit), (0, 8, 9));
+    }
+
+    #[test]
+    fn finds_a_hidden_single() {
+        let candidates = without(5, (0..9).filter(|&c| c != 4).map(|c| (0, c)));
+        let steps: Vec<_> = hidden_singles(&candidates, Variant::Classic).collect();
+        assert_eq!(steps.len(), 1);
+        assert_eq!((steps[0].row, steps[0].col, steps[0].digit), (0, 4, 5));
+    }
+
+    #[test]
+    fn pointing_clears_the_rest_of_the_line() {
+        let mut candidates = without(3, (1..3).flat_map(|r| (0..3).map(move |c| (r, c))));
+        assert!(pointing(&mut candidates));
+        assert!((0..3).all(|c| has(&candidates, (0, c), 3)));
+        assert!((3..9).all(|c| !has(&candidates, (0, c), 3)));
+        assert!(has(&candidates, (1, 5), 3));
+    }
+
+    #[test]
+    fn box_line_clears_the_rest_of_the_box() {
+        let mut candidates = without(4, (3..9).map(|c| (0, c)));
+        assert!(box_line(&mut candidates));
+        assert!((0..3).all(|c| has(&candidates, (0, c), 4)));
+        assert!((1..3).all(|r| (0..3).all(|c| !has(&candidates, (r, c), 4))));
+        assert!(has(&candidates, (3, 0), 4));
+    }
+
+    #[test]
+    fn naked_pair_clears_the_rest_of_the_house() {
+        let mut candidates = [[ALL; 9]; 9];
+        candidates[0][0] = 1 << 1 | 1 << 2;
+        candidates[0][4] = 1 << 1 | 1 << 2;
+        assert!(naked_pair(&mut candidates));
+        for c in [1, 2, 3, 5, 6, 7, 8] {
+            assert!(!has(&candidates, (0, c), 1) && !has(&candidates, (0, c), 2));
+        }
+        assert_eq!(candidates[0][0], 1 << 1 | 1 << 2);
+        assert!(has(&candidates, (1, 0), 1) && has(&candidates, (1, 0), 2));
+    }
+}
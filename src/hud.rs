@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{BorderType, Paragraph, Widget, Wrap},
+};
+
+use crate::{charset::GlyphSet, locale::format_elapsed};
+
+/// Live numbers for the running game, shown next to the board.
+pub struct Hud {
+    /// cells the player has filled
+    pub filled: usize,
+    /// cells that were empty at the start
+    pub to_fill: usize,
+    /// placements that left the board without a solution
+    pub mistakes: u32,
+    pub hints: u32,
+    pub elapsed: Duration,
+    /// percent of past wins on this difficulty slower than the current pace
+    pub faster_than: Option<u32>,
+    /// the player's note on the puzzle
+    pub note: Option<String>,
+    pub style: Style,
+    pub border_style: Style,
+    pub glyphs: GlyphSet,
+}
+
+impl Hud {
+    /// Time the whole board takes at the pace so far.
+    pub fn projected(&self) -> Option<Duration> {
+        (self.filled > 0).then(|| self.elapsed * self.to_fill as u32 / self.filled as u32)
+    }
+}
+
+impl Widget for Hud {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines = vec![
+            Line::from(format!("filled    {}/{}", self.filled, self.to_fill)),
+            Line::from(format!("mistakes  {}", self.mistakes)),
+            Line::from(format!("hints     {}", self.hints)),
+        ];
+        if let Some(projected) = self.projected() {
+            let per_cell = self.elapsed.as_secs_f64() / self.filled as f64;
+            lines.push(Line::from(format!("pace      {per_cell:.1}s/cell")));
+            lines.push(Line::from(format!(
+                "on track  {}",
+                format_elapsed(projected)
+            )));
+        }
+        if let Some(percent) = self.faster_than {
+            lines.push(Line::from(format!("faster than {percent}% of your wins")));
+        }
+        if let Some(note) = self.note {
+            lines.push(Line::default());
+            lines.extend(
+                note.lines()
+                    .map(|line| Line::from(line.to_string()).italic()),
+            );
+        }
+        let block = self
+            .glyphs
+            .block(BorderType::Rounded)
+            .title(" Stats ")
+            .border_style(self.border_style);
+        Paragraph::new(lines)
+            .style(self.style)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
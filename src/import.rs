@@ -0,0 +1,181 @@
+use color_eyre::eyre::{Result, bail, eyre};
+use serde_json::Value;
+
+use crate::{soduko::BoardState, variant::Variant};
+
+/// f-puzzles keys that describe the puzzle rather than add rules.
+const FPUZZLES_META: &[&str] = &["size", "grid", "title", "author", "ruleset", "solution"];
+/// f-puzzles keys that only draw on the grid.
+const FPUZZLES_COSMETIC: &[&str] = &["text", "line", "rectangle", "circle", "cage"];
+/// SadMan `.sdk` header lines: author, description, comment, published,
+/// source, level and url.
+const SDK_META: &[char] = &['A', 'D', 'C', 'B', 'S', 'L', 'U'];
+
+/// Something in the file this engine doesn't enforce or draw.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ignored {
+    /// the name the file format uses
+    pub name: String,
+    pub count: usize,
+    /// only decoration, the puzzle plays the same without it
+    pub cosmetic: bool,
+}
+
+/// What an importer could and couldn't carry over from a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapabilityReport {
+    pub ignored: Vec<Ignored>,
+}
+
+impl CapabilityReport {
+    fn ignore(&mut self, name: impl Into<String>, count: usize, cosmetic: bool) {
+        self.ignored.push(Ignored {
+            name: name.into(),
+            count,
+            cosmetic,
+        });
+    }
+
+    /// Rules that were dropped. Playing without them may allow answers the
+    /// setter didn't intend, so the player is asked first.
+    pub fn dropped_rules(&self) -> impl Iterator<Item = &Ignored> {
+        self.ignored.iter().filter(|ignored| !ignored.cosmetic)
+    }
+
+    /// Everything ignored on one line, rules first. `None` when nothing
+    /// was.
+    pub fn summary(&self) -> Option<String> {
+        let (rules, cosmetic): (Vec<_>, Vec<_>) =
+            self.ignored.iter().partition(|ignored| !ignored.cosmetic);
+        let items: Vec<String> = rules
+            .into_iter()
+            .chain(cosmetic)
+            .map(|ignored| {
+                let kind = match ignored.cosmetic {
+                    true => "decoration",
+                    false => "rule",
+                };
+                format!("{} x{} ({kind})", ignored.name, ignored.count)
+            })
+            .collect();
+        (!items.is_empty()).then(|| items.join(", "))
+    }
+}
+
+/// A board read from a file together with what was left out.
+#[derive(Debug, Clone)]
+pub struct Imported {
+    pub board: BoardState,
+    pub report: CapabilityReport,
+}
+
+impl Imported {
+    /// What's wrong with a board that doesn't have exactly one solution.
+    /// Such a puzzle can still be played, but not solved by logic alone.
+    pub fn ambiguity(&self) -> Option<&'static str> {
+        match self.board.count_solutions(2) {
+            0 => Some("this puzzle has no solution"),
+            1 => None,
+            _ => Some("this puzzle has more than one solution"),
+        }
+    }
+}
+
+/// f-puzzles JSON. Only givens are taken from the grid, and both diagonals
+/// together are played as an X-Sudoku. Every other top level key is a
+/// constraint or a decoration this engine leaves out.
+pub fn fpuzzles(text: &str) -> Result<Imported> {
+    let json: Value = serde_json::from_str(text)?;
+    let size = json.get("size").and_then(Value::as_u64);
+    if size != Some(9) {
+        bail!("only 9x9 puzzles are supported, this one is {size:?}");
+    }
+    let grid = json
+        .get("grid")
+        .and_then(Value::as_array)
+        .ok_or_else(|| eyre!("no grid"))?;
+    let mut board = BoardState::default();
+    let mut report = CapabilityReport::default();
+    let mut regions = 0;
+    for (r, row) in grid.iter().enumerate().take(9) {
+        let cells = row
+            .as_array()
+            .ok_or_else(|| eyre!("row {} is not a list", r + 1))?;
+        for (c, cell) in cells.iter().enumerate().take(9) {
+            let given = cell.get("given").and_then(Value::as_bool).unwrap_or(false);
+            if let Some(value) = cell.get("value").and_then(Value::as_u64)
+                && given
+            {
+                if !(1..=9).contains(&value) {
+                    bail!("r{}c{} holds {value}", r + 1, c + 1);
+                }
+                board[r][c] = (value as u8).into();
+            }
+            if cell.get("region").is_some_and(|region| !region.is_null()) {
+                regions += 1;
+            }
+        }
+    }
+    if regions > 0 {
+        report.ignore("irregular regions", regions, false);
+    }
+    let diagonal = |key| json.get(key).and_then(Value::as_bool) == Some(true);
+    let x = diagonal("diagonal+") && diagonal("diagonal-");
+    if x {
+        board = board.with_variant(Variant::X)?;
+    }
+    if let Some(object) = json.as_object() {
+        for (key, value) in object {
+            if FPUZZLES_META.contains(&key.as_str()) || x && key.starts_with("diagonal") {
+                continue;
+            }
+            let count = match value {
+                Value::Bool(true) => 1,
+                Value::Array(items) => items.len(),
+                Value::Object(items) => items.len(),
+                _ => 0,
+            };
+            if count > 0 {
+                report.ignore(key, count, FPUZZLES_COSMETIC.contains(&key.as_str()));
+            }
+        }
+    }
+    Ok(Imported { board, report })
+}
+
+/// SadMan `.sdk`: nine lines of cells after optional `#` header lines. Header
+/// letters other than the usual metadata are reported, since variant tools
+/// put their rules there.
+pub fn sdk(text: &str) -> Result<Imported> {
+    let mut report = CapabilityReport::default();
+    let mut cells = String::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('#') {
+            match header.chars().next() {
+                Some(c) if SDK_META.contains(&c) => {}
+                Some(c) => report.ignore(format!("#{c} header"), 1, false),
+                None => {}
+            }
+        } else {
+            cells.push_str(line);
+        }
+    }
+    let board = cells.replace(['x', 'X'], ".").parse()?;
+    Ok(Imported { board, report })
+}
+
+/// SadMan `.sdm` collections: one 81 character puzzle per line. The first
+/// one is played, the rest are reported.
+pub fn sdm(text: &str) -> Result<Imported> {
+    let mut puzzles = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let first = puzzles
+        .next()
+        .ok_or_else(|| eyre!("the collection has no puzzles"))?;
+    let board = first.replace(['x', 'X'], ".").parse()?;
+    let mut report = CapabilityReport::default();
+    let rest = puzzles.count();
+    if rest > 0 {
+        report.ignore("further puzzles", rest, true);
+    }
+    Ok(Imported { board, report })
+}
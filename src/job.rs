@@ -0,0 +1,143 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analyze, config::state_dir, formats, generator, persist, rng::Rng, soduko::BoardState,
+};
+
+/// A search for a puzzle rated at least `target`, saved after every
+/// candidate so it picks up where it left off when the app starts again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationJob {
+    pub target: u32,
+    rng: Rng,
+    pub attempts: u64,
+    /// hardest puzzle so far as an 81 character code, with its rating
+    pub best: Option<(String, u32)>,
+}
+
+impl GenerationJob {
+    pub fn new(target: u32, rng: Rng) -> Self {
+        Self {
+            target,
+            rng,
+            attempts: 0,
+            best: None,
+        }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        Some(state_dir()?.join("generation.json"))
+    }
+
+    pub fn load() -> color_eyre::Result<Option<Self>> {
+        let Some(path) = Self::path() else {
+            return Ok(None);
+        };
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text =
+            fs::read_to_string(&path).wrap_err_with(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&text)
+            .map(Some)
+            .wrap_err_with(|| format!("parsing {}", path.display()))
+    }
+
+    /// Writes next to the checkpoint and renames over it, so quitting mid
+    /// write never leaves a broken file behind.
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        persist::write_replacing(&path, &serde_json::to_string_pretty(self)?)
+    }
+
+    /// Forgets the job once its puzzle has been played.
+    pub fn remove() -> color_eyre::Result<()> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                fs::remove_file(&path).wrap_err_with(|| format!("removing {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.best
+            .as_ref()
+            .is_some_and(|&(_, rating)| rating >= self.target)
+    }
+
+    pub fn puzzle(&self) -> Option<BoardState> {
+        self.best.as_ref()?.0.parse().ok()
+    }
+
+    /// Generates and rates one candidate, keeping it if it beats the best.
+    pub fn step(&mut self) {
+        let puzzle = generator::dig(generator::solved_grid(&mut self.rng), &mut self.rng);
+        // a candidate past the target ends the job, however far past it is
+        let rating = analyze::rating_up_to(&puzzle, self.target);
+        self.attempts += 1;
+        if self.best.as_ref().is_none_or(|&(_, best)| rating > best) {
+            self.best = Some((formats::code(&puzzle), rating));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum JobEvent {
+    Done(BoardState),
+    Failed(String),
+}
+
+/// A job running on its own thread. Dropping this stops it after the
+/// candidate being worked on.
+#[derive(Debug)]
+pub struct Background {
+    events: Receiver<JobEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Background {
+    pub fn spawn(mut job: GenerationJob) -> Self {
+        let (tx, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) && !job.is_done() {
+                job.step();
+                if let Err(err) = job.save() {
+                    let _ = tx.send(JobEvent::Failed(format!("{err:#}")));
+                    return;
+                }
+            }
+            if let Some(puzzle) = job.puzzle().filter(|_| job.is_done()) {
+                let _ = tx.send(JobEvent::Done(puzzle));
+            }
+        });
+        Self { events, stop }
+    }
+
+    /// The next event if there is one, without waiting.
+    pub fn poll(&self) -> Option<JobEvent> {
+        self.events.try_recv().ok()
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,103 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+
+use crate::{
+    actions::ActionRegistry,
+    charset::GlyphSet,
+    keymap::{Action, Keymap, Mode},
+};
+
+/// The list behind the key binding screen: every rebindable action of every
+/// mode, one row each. The screen's own mode is left out so its keys can't
+/// be lost.
+pub struct KeyEditor {
+    rows: Vec<(Mode, Action)>,
+    selected: usize,
+    capturing: bool,
+}
+
+impl KeyEditor {
+    pub fn new() -> Self {
+        let mut rows = Vec::new();
+        for mode in Mode::ALL.into_iter().filter(|mode| *mode != Mode::Keys) {
+            rows.extend(
+                ActionRegistry::all()
+                    .iter()
+                    .filter(|info| info.id.is_some() && info.default_keys(mode).next().is_some())
+                    .map(|info| (mode, info.action)),
+            );
+        }
+        Self {
+            rows,
+            selected: 0,
+            capturing: false,
+        }
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.rows.len() - 1);
+    }
+
+    pub fn selected(&self) -> (Mode, Action) {
+        self.rows[self.selected]
+    }
+
+    /// Waiting for the next key press to bind to the selected row.
+    pub fn is_capturing(&self) -> bool {
+        self.capturing
+    }
+
+    pub fn set_capturing(&mut self, capturing: bool) {
+        self.capturing = capturing;
+    }
+}
+
+pub struct KeyEditorView<'a> {
+    pub editor: &'a KeyEditor,
+    pub keymap: &'a Keymap,
+    pub style: Style,
+    pub selected_style: Style,
+    /// for rows that differ from the defaults
+    pub rebound_style: Style,
+    pub glyphs: GlyphSet,
+}
+
+impl Widget for KeyEditorView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let editor = self.editor;
+        let height = area.height.saturating_sub(1) as usize;
+        let scroll = editor.selected.saturating_sub(height.saturating_sub(1));
+        let mut lines: Vec<Line> = Vec::new();
+        for (i, &(mode, action)) in editor.rows.iter().enumerate().skip(scroll).take(height) {
+            let keys = match editor.capturing && i == editor.selected {
+                true => "press a key, Esc cancels".to_string(),
+                false => self.keymap.keys_label(mode, action, self.glyphs),
+            };
+            let text = format!(" {:<8} {:<24} {keys}", mode.label(), action.description());
+            let style = if i == editor.selected {
+                self.selected_style
+            } else if self.keymap.is_rebound(mode, action) {
+                self.rebound_style
+            } else {
+                self.style
+            };
+            lines.push(Line::styled(text, style));
+        }
+        while lines.len() < height {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(format!(
+            " Enter rebind | Backspace reset | p preset: {} | Esc close",
+            self.keymap.preset().label()
+        )));
+        Paragraph::new(lines).style(self.style).render(area, buf);
+    }
+}
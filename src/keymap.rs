@@ -0,0 +1,986 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, stdout},
+    panic,
+    sync::{
+        Once,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use ratatui::crossterm::{
+    event::{
+        KeyCode, KeyEvent, KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::supports_keyboard_enhancement,
+};
+use serde::Deserialize;
+
+use crate::{
+    actions::{ActionInfo, ActionRegistry},
+    charset::GlyphSet,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Normal,
+    Notes,
+    Help,
+    Prompt,
+    Paused,
+    Center,
+    Keys,
+    Library,
+    Error,
+    Themes,
+    Replay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    NextEmpty,
+    PreviousEmpty,
+    NextConflict,
+    JumpBack,
+    ToggleSelect,
+    Write(u8),
+    Erase,
+    ToggleNotes,
+    ToggleNote(u8),
+    ToggleCenterNotes,
+    ToggleCenter(u8),
+    ClearNotes,
+    Check,
+    ToggleAutoCheck,
+    ToggleRapidEntry,
+    Solve,
+    Reveal,
+    Clear,
+    NewGame,
+    Carve,
+    ToggleHud,
+    ToggleConflicts,
+    NextTheme,
+    PreviousTheme,
+    ToggleHelp,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    ScrollUp,
+    ScrollDown,
+    Accept,
+    Decline,
+    WatchSolve,
+    Discard,
+    CommandLine,
+    TogglePause,
+    PlayGenerated,
+    ToggleCoach,
+    CoachReport,
+    Undo,
+    Guess,
+    Rollback,
+    Preview,
+    Hint,
+    Submit,
+    CycleHighlight,
+    ClearHighlights,
+    BugReport,
+    Replay,
+    ReplayFaster,
+    ReplaySlower,
+    PauseReplay,
+    EditNote,
+    Export,
+    ToggleLibrary,
+    PlaySelected,
+    ToggleMark,
+    TagMarked,
+    ToggleArchive,
+    ArchiveMarked,
+    PurgeMarked,
+    ImportCollection,
+    Retry,
+    Continue,
+    ToggleThemes,
+    ApplyTheme,
+    MainMenu,
+    ToggleRace,
+    EditKeys,
+    Rebind,
+    ResetBinding,
+    NextPreset,
+}
+
+impl Action {
+    pub fn info(self) -> &'static ActionInfo {
+        ActionRegistry::get(self)
+    }
+
+    pub fn description(self) -> &'static str {
+        self.info().description
+    }
+
+    /// The name used in the `[keys]` config table. Actions carrying a value
+    /// have none and can't be rebound.
+    pub fn name(self) -> Option<&'static str> {
+        self.info().id
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub key: KeyCode,
+    pub action: Action,
+}
+
+const fn bind(key: KeyCode, action: Action) -> Binding {
+    Binding { key, action }
+}
+
+const MOVEMENT: &[Binding] = &[
+    bind(KeyCode::Up, Action::MoveUp),
+    bind(KeyCode::Char('k'), Action::MoveUp),
+    bind(KeyCode::Down, Action::MoveDown),
+    bind(KeyCode::Char('j'), Action::MoveDown),
+    bind(KeyCode::Left, Action::MoveLeft),
+    bind(KeyCode::Char('h'), Action::MoveLeft),
+    bind(KeyCode::Right, Action::MoveRight),
+    bind(KeyCode::Char('l'), Action::MoveRight),
+];
+
+const SELECTING: &[Binding] = &[
+    bind(KeyCode::Tab, Action::NextEmpty),
+    bind(KeyCode::BackTab, Action::PreviousEmpty),
+    bind(KeyCode::Char('x'), Action::NextConflict),
+    bind(KeyCode::Char('\''), Action::JumpBack),
+    bind(KeyCode::Char('V'), Action::ToggleSelect),
+];
+
+const NORMAL: &[Binding] = &[
+    bind(KeyCode::Esc, Action::Quit),
+    bind(KeyCode::Char('q'), Action::Quit),
+    bind(KeyCode::Char('1'), Action::Write(1)),
+    bind(KeyCode::Char('2'), Action::Write(2)),
+    bind(KeyCode::Char('3'), Action::Write(3)),
+    bind(KeyCode::Char('4'), Action::Write(4)),
+    bind(KeyCode::Char('5'), Action::Write(5)),
+    bind(KeyCode::Char('6'), Action::Write(6)),
+    bind(KeyCode::Char('7'), Action::Write(7)),
+    bind(KeyCode::Char('8'), Action::Write(8)),
+    bind(KeyCode::Char('9'), Action::Write(9)),
+    bind(KeyCode::Backspace, Action::Erase),
+    bind(KeyCode::Delete, Action::Erase),
+    bind(KeyCode::Char('0'), Action::Erase),
+    bind(KeyCode::Char('u'), Action::Undo),
+    bind(KeyCode::Char('b'), Action::Guess),
+    bind(KeyCode::Char('B'), Action::Rollback),
+    bind(KeyCode::Char('p'), Action::ToggleNotes),
+    bind(KeyCode::Char('P'), Action::ToggleCenterNotes),
+    bind(KeyCode::Enter, Action::Check),
+    bind(KeyCode::Char('c'), Action::Check),
+    bind(KeyCode::Char('a'), Action::ToggleAutoCheck),
+    bind(KeyCode::Char('w'), Action::ToggleRapidEntry),
+    bind(KeyCode::Char('X'), Action::ToggleConflicts),
+    bind(KeyCode::Char('s'), Action::Solve),
+    bind(KeyCode::Char('r'), Action::Reveal),
+    bind(KeyCode::Char('v'), Action::Preview),
+    bind(KeyCode::Char('?'), Action::Hint),
+    bind(KeyCode::Char('S'), Action::Submit),
+    bind(KeyCode::Char('o'), Action::CycleHighlight),
+    bind(KeyCode::Char('O'), Action::ClearHighlights),
+    bind(KeyCode::Char('n'), Action::Clear),
+    bind(KeyCode::Char('N'), Action::NewGame),
+    bind(KeyCode::Char('g'), Action::Carve),
+    bind(KeyCode::Char('t'), Action::NextTheme),
+    bind(KeyCode::Char('T'), Action::PreviousTheme),
+    bind(KeyCode::F(1), Action::ToggleHelp),
+    bind(KeyCode::Char(' '), Action::TogglePause),
+    bind(KeyCode::Char('G'), Action::PlayGenerated),
+    bind(KeyCode::Char('C'), Action::ToggleCoach),
+    bind(KeyCode::Char('D'), Action::CoachReport),
+    bind(KeyCode::Char('i'), Action::ToggleHud),
+    bind(KeyCode::Char('K'), Action::EditKeys),
+    bind(KeyCode::Char('m'), Action::EditNote),
+    bind(KeyCode::Char('e'), Action::Export),
+    bind(KeyCode::Char('L'), Action::ToggleLibrary),
+    bind(KeyCode::Char('y'), Action::ToggleThemes),
+    bind(KeyCode::Char('M'), Action::MainMenu),
+    bind(KeyCode::Char('R'), Action::ToggleRace),
+    bind(KeyCode::Char(':'), Action::CommandLine),
+    bind(KeyCode::F(12), Action::BugReport),
+    bind(KeyCode::Char('Y'), Action::Replay),
+];
+
+const NOTES: &[Binding] = &[
+    bind(KeyCode::Esc, Action::ToggleNotes),
+    bind(KeyCode::Char('p'), Action::ToggleNotes),
+    bind(KeyCode::Char('1'), Action::ToggleNote(1)),
+    bind(KeyCode::Char('2'), Action::ToggleNote(2)),
+    bind(KeyCode::Char('3'), Action::ToggleNote(3)),
+    bind(KeyCode::Char('4'), Action::ToggleNote(4)),
+    bind(KeyCode::Char('5'), Action::ToggleNote(5)),
+    bind(KeyCode::Char('6'), Action::ToggleNote(6)),
+    bind(KeyCode::Char('7'), Action::ToggleNote(7)),
+    bind(KeyCode::Char('8'), Action::ToggleNote(8)),
+    bind(KeyCode::Char('9'), Action::ToggleNote(9)),
+    bind(KeyCode::Backspace, Action::ClearNotes),
+    bind(KeyCode::Delete, Action::ClearNotes),
+    bind(KeyCode::Char('0'), Action::ClearNotes),
+    bind(KeyCode::Char('P'), Action::ToggleCenterNotes),
+    bind(KeyCode::F(1), Action::ToggleHelp),
+];
+
+const CENTER: &[Binding] = &[
+    bind(KeyCode::Esc, Action::ToggleCenterNotes),
+    bind(KeyCode::Char('P'), Action::ToggleCenterNotes),
+    bind(KeyCode::Char('p'), Action::ToggleNotes),
+    bind(KeyCode::Char('1'), Action::ToggleCenter(1)),
+    bind(KeyCode::Char('2'), Action::ToggleCenter(2)),
+    bind(KeyCode::Char('3'), Action::ToggleCenter(3)),
+    bind(KeyCode::Char('4'), Action::ToggleCenter(4)),
+    bind(KeyCode::Char('5'), Action::ToggleCenter(5)),
+    bind(KeyCode::Char('6'), Action::ToggleCenter(6)),
+    bind(KeyCode::Char('7'), Action::ToggleCenter(7)),
+    bind(KeyCode::Char('8'), Action::ToggleCenter(8)),
+    bind(KeyCode::Char('9'), Action::ToggleCenter(9)),
+    bind(KeyCode::Backspace, Action::ClearNotes),
+    bind(KeyCode::Delete, Action::ClearNotes),
+    bind(KeyCode::Char('0'), Action::ClearNotes),
+    bind(KeyCode::F(1), Action::ToggleHelp),
+];
+
+const HELP: &[Binding] = &[
+    bind(KeyCode::Esc, Action::ToggleHelp),
+    bind(KeyCode::Char('q'), Action::ToggleHelp),
+    bind(KeyCode::F(1), Action::ToggleHelp),
+    bind(KeyCode::Char('/'), Action::Search),
+    bind(KeyCode::Char('n'), Action::NextMatch),
+    bind(KeyCode::Char('N'), Action::PreviousMatch),
+    bind(KeyCode::Up, Action::ScrollUp),
+    bind(KeyCode::Char('k'), Action::ScrollUp),
+    bind(KeyCode::Down, Action::ScrollDown),
+    bind(KeyCode::Char('j'), Action::ScrollDown),
+];
+
+const PROMPT: &[Binding] = &[
+    bind(KeyCode::Char('y'), Action::Accept),
+    bind(KeyCode::Enter, Action::Accept),
+    bind(KeyCode::Char('s'), Action::WatchSolve),
+    bind(KeyCode::Char('d'), Action::Discard),
+    bind(KeyCode::Char('n'), Action::Decline),
+    bind(KeyCode::Esc, Action::Decline),
+];
+
+const PAUSED: &[Binding] = &[
+    bind(KeyCode::Char(' '), Action::TogglePause),
+    bind(KeyCode::Esc, Action::TogglePause),
+    bind(KeyCode::Char('q'), Action::Quit),
+];
+
+const KEYS: &[Binding] = &[
+    bind(KeyCode::Esc, Action::EditKeys),
+    bind(KeyCode::Char('q'), Action::EditKeys),
+    bind(KeyCode::Char('K'), Action::EditKeys),
+    bind(KeyCode::Up, Action::ScrollUp),
+    bind(KeyCode::Char('k'), Action::ScrollUp),
+    bind(KeyCode::Down, Action::ScrollDown),
+    bind(KeyCode::Char('j'), Action::ScrollDown),
+    bind(KeyCode::Enter, Action::Rebind),
+    bind(KeyCode::Backspace, Action::ResetBinding),
+    bind(KeyCode::Delete, Action::ResetBinding),
+    bind(KeyCode::Char('p'), Action::NextPreset),
+];
+
+const LIBRARY: &[Binding] = &[
+    bind(KeyCode::Esc, Action::ToggleLibrary),
+    bind(KeyCode::Char('q'), Action::ToggleLibrary),
+    bind(KeyCode::Char('L'), Action::ToggleLibrary),
+    bind(KeyCode::Up, Action::ScrollUp),
+    bind(KeyCode::Char('k'), Action::ScrollUp),
+    bind(KeyCode::Down, Action::ScrollDown),
+    bind(KeyCode::Char('j'), Action::ScrollDown),
+    bind(KeyCode::Enter, Action::PlaySelected),
+    bind(KeyCode::Char(' '), Action::ToggleMark),
+    bind(KeyCode::Char('t'), Action::TagMarked),
+    bind(KeyCode::Char('/'), Action::Search),
+    bind(KeyCode::Char('a'), Action::ToggleArchive),
+    bind(KeyCode::Char('d'), Action::ArchiveMarked),
+    bind(KeyCode::Delete, Action::ArchiveMarked),
+    bind(KeyCode::Char('X'), Action::PurgeMarked),
+    bind(KeyCode::Char('i'), Action::ImportCollection),
+];
+
+const ERROR: &[Binding] = &[
+    bind(KeyCode::Char('r'), Action::Retry),
+    bind(KeyCode::Enter, Action::Continue),
+    bind(KeyCode::Esc, Action::Continue),
+    bind(KeyCode::Char('c'), Action::Continue),
+    bind(KeyCode::Up, Action::ScrollUp),
+    bind(KeyCode::Char('k'), Action::ScrollUp),
+    bind(KeyCode::Down, Action::ScrollDown),
+    bind(KeyCode::Char('j'), Action::ScrollDown),
+];
+
+const THEMES: &[Binding] = &[
+    bind(KeyCode::Esc, Action::ToggleThemes),
+    bind(KeyCode::Char('q'), Action::ToggleThemes),
+    bind(KeyCode::Char('y'), Action::ToggleThemes),
+    bind(KeyCode::Enter, Action::ApplyTheme),
+    bind(KeyCode::Up, Action::ScrollUp),
+    bind(KeyCode::Char('k'), Action::ScrollUp),
+    bind(KeyCode::Down, Action::ScrollDown),
+    bind(KeyCode::Char('j'), Action::ScrollDown),
+];
+
+const REPLAY: &[Binding] = &[
+    bind(KeyCode::Esc, Action::Replay),
+    bind(KeyCode::Char('q'), Action::Replay),
+    bind(KeyCode::Char('Y'), Action::Replay),
+    bind(KeyCode::Char('+'), Action::ReplayFaster),
+    bind(KeyCode::Char('='), Action::ReplayFaster),
+    bind(KeyCode::Char('-'), Action::ReplaySlower),
+    bind(KeyCode::Char(' '), Action::PauseReplay),
+];
+
+impl Mode {
+    pub const ALL: [Mode; 11] = [
+        Mode::Normal,
+        Mode::Notes,
+        Mode::Center,
+        Mode::Help,
+        Mode::Prompt,
+        Mode::Paused,
+        Mode::Keys,
+        Mode::Library,
+        Mode::Error,
+        Mode::Themes,
+        Mode::Replay,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Mode::Normal => "NORMAL",
+            Mode::Notes => "NOTES",
+            Mode::Center => "CENTER",
+            Mode::Help => "HELP",
+            Mode::Prompt => "PROMPT",
+            Mode::Paused => "PAUSED",
+            Mode::Keys => "KEYS",
+            Mode::Library => "LIBRARY",
+            Mode::Error => "ERROR",
+            Mode::Themes => "THEMES",
+            Mode::Replay => "REPLAY",
+        }
+    }
+
+    /// The table name under `[keys]` in the config.
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Normal => "normal",
+            Mode::Notes => "notes",
+            Mode::Center => "center",
+            Mode::Help => "help",
+            Mode::Prompt => "prompt",
+            Mode::Paused => "paused",
+            Mode::Keys => "keys",
+            Mode::Library => "library",
+            Mode::Error => "error",
+            Mode::Themes => "themes",
+            Mode::Replay => "replay",
+        }
+    }
+
+    fn tables(self) -> &'static [&'static [Binding]] {
+        match self {
+            Mode::Normal => &[NORMAL, MOVEMENT, SELECTING],
+            Mode::Notes => &[NOTES, MOVEMENT, SELECTING],
+            Mode::Center => &[CENTER, MOVEMENT, SELECTING],
+            Mode::Help => &[HELP],
+            Mode::Prompt => &[PROMPT],
+            Mode::Paused => &[PAUSED],
+            Mode::Keys => &[KEYS],
+            Mode::Library => &[LIBRARY],
+            Mode::Error => &[ERROR],
+            Mode::Themes => &[THEMES],
+            Mode::Replay => &[REPLAY],
+        }
+    }
+
+    pub fn bindings(self) -> impl Iterator<Item = &'static Binding> {
+        self.tables().iter().flat_map(|table| table.iter())
+    }
+
+    /// What digit `d` does in this mode, if the mode takes digits.
+    pub fn digit_action(self, d: u8) -> Option<Action> {
+        match self {
+            Mode::Normal => Some(Action::Write(d)),
+            Mode::Notes => Some(Action::ToggleNote(d)),
+            Mode::Center => Some(Action::ToggleCenter(d)),
+            _ => None,
+        }
+    }
+
+    pub fn lookup(self, key: KeyCode) -> Option<Action> {
+        self.bindings()
+            .find(|binding| binding.key == key)
+            .map(|binding| binding.action)
+    }
+}
+
+/// Ready-made changes to the default bindings, picked with `preset` in the
+/// `[keys]` table. The player's own changes go on top.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyPreset {
+    #[default]
+    Standard,
+    /// for speed: the left hand moves on WASD and switches modes next to
+    /// it, the right hand writes on the letters under it laid out like a
+    /// number pad
+    #[serde(rename = "two-handed")]
+    TwoHanded,
+}
+
+/// The two-handed preset, minus its digits. What the digits and WASD push
+/// out moves to a capital, modes are left with Esc as before.
+const TWO_HANDED: &[(Mode, Action, KeyCode)] = &[
+    (Mode::Normal, Action::MoveUp, KeyCode::Char('w')),
+    (Mode::Normal, Action::MoveLeft, KeyCode::Char('a')),
+    (Mode::Normal, Action::MoveDown, KeyCode::Char('s')),
+    (Mode::Normal, Action::MoveRight, KeyCode::Char('d')),
+    (Mode::Normal, Action::ToggleNotes, KeyCode::Char('e')),
+    (Mode::Normal, Action::ToggleCenterNotes, KeyCode::Char('r')),
+    (Mode::Normal, Action::Erase, KeyCode::Char('f')),
+    (Mode::Normal, Action::Undo, KeyCode::Char('z')),
+    (Mode::Normal, Action::ToggleAutoCheck, KeyCode::Char('A')),
+    (Mode::Normal, Action::ToggleRapidEntry, KeyCode::Char('F')),
+    (Mode::Normal, Action::Solve, KeyCode::Char('Z')),
+    (Mode::Normal, Action::Reveal, KeyCode::Char('H')),
+    (Mode::Normal, Action::Export, KeyCode::Char('E')),
+    (Mode::Normal, Action::EditNote, KeyCode::Char('W')),
+    (Mode::Normal, Action::CycleHighlight, KeyCode::Char('O')),
+    (Mode::Normal, Action::ClearHighlights, KeyCode::Char('U')),
+    (Mode::Normal, Action::ToggleHud, KeyCode::Char('I')),
+    (Mode::Notes, Action::MoveUp, KeyCode::Char('w')),
+    (Mode::Notes, Action::MoveLeft, KeyCode::Char('a')),
+    (Mode::Notes, Action::MoveDown, KeyCode::Char('s')),
+    (Mode::Notes, Action::MoveRight, KeyCode::Char('d')),
+    (Mode::Notes, Action::ClearNotes, KeyCode::Char('f')),
+    (Mode::Center, Action::MoveUp, KeyCode::Char('w')),
+    (Mode::Center, Action::MoveLeft, KeyCode::Char('a')),
+    (Mode::Center, Action::MoveDown, KeyCode::Char('s')),
+    (Mode::Center, Action::MoveRight, KeyCode::Char('d')),
+    (Mode::Center, Action::ClearNotes, KeyCode::Char('f')),
+];
+
+impl KeyPreset {
+    pub const ALL: [KeyPreset; 2] = [KeyPreset::Standard, KeyPreset::TwoHanded];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyPreset::Standard => "standard",
+            KeyPreset::TwoHanded => "two-handed",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|preset| *preset == self)
+            .unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn overrides(self) -> &'static [(Mode, Action, KeyCode)] {
+        match self {
+            KeyPreset::Standard => &[],
+            KeyPreset::TwoHanded => TWO_HANDED,
+        }
+    }
+
+    /// 1-3 on the bottom row, 4-6 on the home row and 7-9 above, like a
+    /// number pad under the right hand.
+    fn digits(self) -> Option<[KeyCode; 9]> {
+        match self {
+            KeyPreset::Standard => None,
+            KeyPreset::TwoHanded => {
+                Some(['m', ',', '.', 'j', 'k', 'l', 'u', 'i', 'o'].map(KeyCode::Char))
+            }
+        }
+    }
+}
+
+/// The default bindings with the player's changes from the `[keys]` config
+/// table on top, one table per mode mapping action names to keys:
+///
+/// ```toml
+/// [keys.normal]
+/// undo = "z"
+/// move_left = "Left"
+/// ```
+///
+/// A rebound action loses its default keys in that mode.
+///
+/// `digits` swaps the number row for nine other keys, 1 to 9 in order, in
+/// every mode that takes digits. They win over whatever else those keys do
+/// there, so `digits = "asdfghjkl"` leaves moving to the arrow keys:
+///
+/// ```toml
+/// [keys]
+/// digits = "asdfghjkl"
+/// ```
+///
+/// `preset` starts from a [`KeyPreset`] instead of the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "KeyTables")]
+pub struct Keymap {
+    preset: KeyPreset,
+    /// the player's own, over the preset's
+    overrides: Vec<(Mode, Action, KeyCode)>,
+    /// the keys for 1 to 9 in place of the number row, over the preset's
+    digits: Option<[KeyCode; 9]>,
+}
+
+/// The `[keys]` table as written.
+#[derive(Deserialize)]
+struct KeyTables {
+    #[serde(default)]
+    preset: KeyPreset,
+    digits: Option<String>,
+    #[serde(flatten)]
+    modes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl TryFrom<KeyTables> for Keymap {
+    type Error = String;
+
+    fn try_from(tables: KeyTables) -> Result<Self, String> {
+        let mut keymap = Keymap {
+            preset: tables.preset,
+            ..Keymap::default()
+        };
+        if let Some(digits) = tables.digits {
+            let keys: Vec<KeyCode> = digits.chars().map(KeyCode::Char).collect();
+            let keys: [KeyCode; 9] = keys
+                .try_into()
+                .map_err(|_| format!("digits takes nine keys, got {digits:?}"))?;
+            if let Some((i, _)) = keys
+                .iter()
+                .enumerate()
+                .find(|(i, key)| keys[..*i].contains(key))
+            {
+                return Err(format!("{:?} is in digits twice", keys[i]));
+            }
+            keymap.digits = Some(keys);
+        }
+        for (mode_name, table) in tables.modes {
+            let mode = Mode::ALL
+                .into_iter()
+                .find(|mode| mode.name() == mode_name)
+                .ok_or_else(|| format!("unknown mode {mode_name:?}"))?;
+            for (action_name, key_name) in table {
+                let action = ActionRegistry::by_id(&action_name)
+                    .filter(|info| info.default_keys(mode).next().is_some())
+                    .map(|info| info.action)
+                    .ok_or_else(|| format!("{action_name:?} is not an action of {mode_name}"))?;
+                let key =
+                    parse_key(&key_name).ok_or_else(|| format!("unknown key {key_name:?}"))?;
+                keymap.set(mode, action, Some(key));
+            }
+        }
+        Ok(keymap)
+    }
+}
+
+impl Keymap {
+    pub fn preset(&self) -> KeyPreset {
+        self.preset
+    }
+
+    /// Starts from `preset`, keeping the player's own changes on top.
+    pub fn set_preset(&mut self, preset: KeyPreset) {
+        self.preset = preset;
+    }
+
+    /// The player's rebindings, then the preset's for actions they left be.
+    fn all_overrides(&self) -> impl Iterator<Item = &(Mode, Action, KeyCode)> {
+        self.overrides
+            .iter()
+            .chain(self.preset.overrides().iter().filter(|(mode, action, _)| {
+                !self
+                    .overrides
+                    .iter()
+                    .any(|(m, a, _)| (m, a) == (mode, action))
+            }))
+    }
+
+    fn key_for(&self, mode: Mode, action: Action) -> Option<KeyCode> {
+        self.all_overrides()
+            .find(|(m, a, _)| *m == mode && *a == action)
+            .map(|(_, _, key)| *key)
+    }
+
+    fn digit_keys(&self) -> Option<[KeyCode; 9]> {
+        self.digits.or(self.preset.digits())
+    }
+
+    /// Rebinds `action` in `mode` to `key`, or back to its defaults.
+    pub fn set(&mut self, mode: Mode, action: Action, key: Option<KeyCode>) {
+        self.overrides
+            .retain(|(m, a, _)| (*m, *a) != (mode, action));
+        if let Some(key) = key {
+            self.overrides.push((mode, action, key));
+        }
+    }
+
+    pub fn is_rebound(&self, mode: Mode, action: Action) -> bool {
+        self.key_for(mode, action).is_some()
+    }
+
+    /// The bindings in effect for `mode`, rebound keys in place of the
+    /// defaults they replace. With digit keys set, they take the place of
+    /// the number row and of anything else on those keys.
+    pub fn bindings(&self, mode: Mode) -> Vec<Binding> {
+        let mut bindings: Vec<Binding> = Vec::new();
+        for binding in mode.bindings() {
+            let key = self.key_for(mode, binding.action).unwrap_or(binding.key);
+            let key = match (self.digit_key(mode, binding.action), key) {
+                (Some(digit), KeyCode::Char('1'..='9')) => digit,
+                (None, key) if self.digit(mode, key).is_some() => continue,
+                (_, key) => key,
+            };
+            let binding = bind(key, binding.action);
+            if !bindings.contains(&binding) {
+                bindings.push(binding);
+            }
+        }
+        bindings
+    }
+
+    /// The digit `key` stands for in `mode`, when digit keys are set.
+    fn digit(&self, mode: Mode, key: KeyCode) -> Option<u8> {
+        mode.digit_action(1)?;
+        let i = self.digit_keys()?.iter().position(|k| *k == key)?;
+        Some(i as u8 + 1)
+    }
+
+    /// The key set for the digit `action` enters, if it's one.
+    fn digit_key(&self, mode: Mode, action: Action) -> Option<KeyCode> {
+        let digits = self.digit_keys()?;
+        (1..=9)
+            .find(|&d| mode.digit_action(d) == Some(action))
+            .map(|d| digits[d as usize - 1])
+    }
+
+    /// The key pressed for digit `d`, the number row unless digit keys are
+    /// set.
+    pub fn digit_label(&self, d: u8) -> char {
+        match self.digit_keys().map(|keys| keys[d as usize - 1]) {
+            Some(KeyCode::Char(c)) => c,
+            _ => char::from(b'0' + d),
+        }
+    }
+
+    pub fn lookup(&self, mode: Mode, key: KeyCode) -> Option<Action> {
+        if let Some(d) = self.digit(mode, key) {
+            return mode.digit_action(d);
+        }
+        self.all_overrides()
+            .find(|(m, _, k)| *m == mode && *k == key)
+            .map(|(_, action, _)| *action)
+            .or_else(|| {
+                self.bindings(mode)
+                    .into_iter()
+                    .find(|binding| binding.key == key)
+                    .map(|binding| binding.action)
+            })
+    }
+
+    /// Looks up `key` as typed on `layout`. Movement follows the position
+    /// of the key so hjkl stays a row under the right hand, everything else
+    /// follows the label and only falls back to the position when the label
+    /// isn't bound.
+    pub fn lookup_on(&self, mode: Mode, key: KeyCode, layout: KeyboardLayout) -> Option<Action> {
+        // digit keys are set by what they type, like the number row
+        if let Some(d) = self.digit(mode, key) {
+            return mode.digit_action(d);
+        }
+        let positional = self.lookup(mode, layout.physical(key));
+        match positional {
+            Some(action) if action.info().movement => positional,
+            _ => self
+                .lookup(mode, key)
+                .filter(|action| !action.info().movement)
+                .or(positional),
+        }
+    }
+
+    /// The action other than `action` that `key` already triggers in `mode`.
+    pub fn conflict(&self, mode: Mode, action: Action, key: KeyCode) -> Option<Action> {
+        self.lookup(mode, key).filter(|other| *other != action)
+    }
+
+    /// The keys for `action` in `mode` as one label, like `1-9`.
+    pub fn keys_label(&self, mode: Mode, action: Action, glyphs: GlyphSet) -> String {
+        let keys: Vec<KeyCode> = self
+            .bindings(mode)
+            .into_iter()
+            .filter(|binding| binding.action == action)
+            .map(|binding| binding.key)
+            .collect();
+        key_list(&keys, glyphs)
+    }
+
+    /// Bindings sharing a description are merged into one hint, in table order.
+    pub fn hints(&self, mode: Mode, glyphs: GlyphSet) -> Vec<(String, Action)> {
+        let mut hints: Vec<(Vec<KeyCode>, Action)> = Vec::new();
+        for binding in self.bindings(mode) {
+            let description = binding.action.description();
+            match hints
+                .iter_mut()
+                .find(|(_, action)| action.description() == description)
+            {
+                Some((keys, _)) => keys.push(binding.key),
+                None => hints.push((vec![binding.key], binding.action)),
+            }
+        }
+        hints
+            .into_iter()
+            .map(|(keys, action)| (key_list(&keys, glyphs), action))
+            .collect()
+    }
+
+    /// Every binding of every mode, for the help overlay.
+    pub fn help_lines(&self, glyphs: GlyphSet) -> Vec<String> {
+        let mut lines = vec![
+            format!("  {:<24} command palette", "Ctrl+p"),
+            format!("  {:<24} toggle note without notes mode", "Shift+1-9"),
+            String::new(),
+        ];
+        for mode in Mode::ALL {
+            lines.push(format!("{} mode", mode.label()));
+            for (keys, action) in self.hints(mode, glyphs) {
+                lines.push(format!("  {keys:<24} {}", action.description()));
+            }
+            lines.push(String::new());
+        }
+        lines
+    }
+}
+
+/// The key names the `[keys]` table understands: a single character or
+/// one of the names below, `F1` to `F12` included.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("Space", KeyCode::Char(' ')),
+    ("Esc", KeyCode::Esc),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+];
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    if let Some((_, key)) = KEY_NAMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+        return Some(*key);
+    }
+    if let Some(n) = name.strip_prefix(['F', 'f'])
+        && let Ok(n) = n.parse()
+        && (1..=12).contains(&n)
+    {
+        return Some(KeyCode::F(n));
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(KeyCode::Char(c)),
+        _ => None,
+    }
+}
+
+/// The name [`parse_key`] reads back, if the key has one.
+pub fn key_name(key: KeyCode) -> Option<String> {
+    if let Some((name, _)) = KEY_NAMES.iter().find(|(_, k)| *k == key) {
+        return Some(name.to_string());
+    }
+    match key {
+        KeyCode::Char(c) => Some(c.to_string()),
+        KeyCode::F(n @ 1..=12) => Some(format!("F{n}")),
+        _ => None,
+    }
+}
+
+/// The keyboard the player types on. Bindings are written for QWERTY,
+/// other layouts are mapped back to the QWERTY key in the same place.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Azerty,
+    Qwertz,
+    Dvorak,
+    Colemak,
+}
+
+/// The unshifted keys of the digit row and the three letter rows, left to
+/// right. Every layout lists its keys in the same order.
+const QWERTY_KEYS: &str = "1234567890qwertyuiopasdfghjkl;zxcvbnm,./";
+
+impl KeyboardLayout {
+    fn keys(self) -> &'static str {
+        match self {
+            KeyboardLayout::Qwerty => QWERTY_KEYS,
+            KeyboardLayout::Azerty => "&é\"'(-è_çàazertyuiopqsdfghjklmwxcvbn,;:!",
+            KeyboardLayout::Qwertz => "1234567890qwertzuiopasdfghjklöyxcvbnm,.-",
+            KeyboardLayout::Dvorak => "1234567890',.pyfgcrlaoeuidhtns;qjkxbmwvz",
+            KeyboardLayout::Colemak => "1234567890qwfpgjluy;arstdhneiozxcvbkm,./",
+        }
+    }
+
+    /// The digit row with shift held, `None` where shift is what gives the
+    /// digits.
+    fn shifted_digits(self) -> Option<&'static str> {
+        match self {
+            KeyboardLayout::Qwerty | KeyboardLayout::Dvorak | KeyboardLayout::Colemak => {
+                Some("!@#$%^&*(")
+            }
+            KeyboardLayout::Qwertz => Some("!\"§$%&/()"),
+            KeyboardLayout::Azerty => None,
+        }
+    }
+
+    /// The digit under `key` when it was pressed with shift, whether the
+    /// terminal reports the shifted character or the digit with shift held.
+    pub fn shifted_digit(self, key: KeyEvent) -> Option<u8> {
+        let KeyCode::Char(c) = key.code else {
+            return None;
+        };
+        let shifted = self.shifted_digits()?;
+        if key.modifiers.contains(KeyModifiers::SHIFT)
+            && let Some(d) = c.to_digit(10).filter(|&d| d != 0)
+        {
+            return Some(d as u8);
+        }
+        shifted.chars().position(|s| s == c).map(|i| i as u8 + 1)
+    }
+
+    /// The QWERTY key in the place of `key`. Shifted letters map to the
+    /// shifted QWERTY letter, anything off the four rows is kept.
+    pub fn physical(self, key: KeyCode) -> KeyCode {
+        let KeyCode::Char(c) = key else {
+            return key;
+        };
+        if self == KeyboardLayout::Qwerty {
+            return key;
+        }
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let Some(i) = self.keys().chars().position(|k| k == lower) else {
+            return key;
+        };
+        let qwerty = QWERTY_KEYS
+            .chars()
+            .nth(i)
+            .expect("layouts list the same keys");
+        match c.is_uppercase() {
+            true => KeyCode::Char(qwerty.to_ascii_uppercase()),
+            false => KeyCode::Char(qwerty),
+        }
+    }
+}
+
+/// Asks for unambiguous key reports on terminals that speak the kitty
+/// keyboard protocol, so Esc isn't mistaken for the start of a sequence and
+/// shifted keys arrive as the character the layout gives them. crossterm
+/// doesn't pass on the base layout key, so positions still come from the
+/// configured [`KeyboardLayout`]. Returns whether the terminal took it.
+///
+/// A panic before [`restore_keyboard`] pops the flags too, ahead of the
+/// hooks installed before, so the shell isn't left with them.
+pub fn enhance_keyboard() -> io::Result<bool> {
+    if !supports_keyboard_enhancement()? {
+        return Ok(false);
+    }
+    execute!(
+        stdout(),
+        PushKeyboardEnhancementFlags(
+            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+        )
+    )?;
+    ENHANCED.store(true, Ordering::SeqCst);
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if ENHANCED.swap(false, Ordering::SeqCst) {
+                let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+            }
+            previous(info);
+        }));
+    });
+    Ok(true)
+}
+
+/// Whether the kitty keyboard flags are pushed and still need popping.
+static ENHANCED: AtomicBool = AtomicBool::new(false);
+
+pub fn restore_keyboard() -> io::Result<()> {
+    ENHANCED.store(false, Ordering::SeqCst);
+    execute!(stdout(), PopKeyboardEnhancementFlags)
+}
+
+pub fn key_label(key: KeyCode, glyphs: GlyphSet) -> String {
+    let [up, down, left, right] = glyphs.arrows;
+    match key {
+        KeyCode::Up => up.into(),
+        KeyCode::Down => down.into(),
+        KeyCode::Left => left.into(),
+        KeyCode::Right => right.into(),
+        KeyCode::Char(' ') => "Space".into(),
+        KeyCode::Char(c) => c.to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Joins key labels, collapsing runs of consecutive characters like 1..9 into `1-9`.
+fn key_list(keys: &[KeyCode], glyphs: GlyphSet) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < keys.len() {
+        let mut end = i;
+        while let (KeyCode::Char(a), Some(KeyCode::Char(b))) = (keys[end], keys.get(end + 1))
+            && *b as u32 == a as u32 + 1
+        {
+            end += 1;
+        }
+        if end - i >= 2 {
+            parts.push(format!(
+                "{}-{}",
+                key_label(keys[i], glyphs),
+                key_label(keys[end], glyphs)
+            ));
+        } else {
+            parts.extend(keys[i..=end].iter().map(|key| key_label(*key, glyphs)));
+        }
+        i = end + 1;
+    }
+    parts.join(", ")
+}
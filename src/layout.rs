@@ -0,0 +1,192 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use serde::Deserialize;
+
+use crate::config::CellConfig;
+
+/// The smallest cells a board shrinks to on a small terminal: the symbol
+/// alone, no padding.
+pub const COMPACT: CellConfig = CellConfig {
+    width: 1,
+    height: 1,
+    padding: 0,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    #[default]
+    Center,
+    Left,
+    Top,
+}
+
+/// Where the board goes on screen and how large it may grow.
+///
+/// Without a max size the board keeps the configured cell size. With one,
+/// cells are scaled up by whole multiples as long as the board still fits
+/// both the terminal and the max size.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    pub align: Align,
+    pub max_board_width: Option<u16>,
+    pub max_board_height: Option<u16>,
+}
+
+/// Areas shared by every screen: a one line header, the board, the footer and
+/// the status line along the bottom edge.
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenLayout {
+    pub cell: CellConfig,
+    pub header: Rect,
+    pub board: Rect,
+    pub footer: Rect,
+    pub status: Rect,
+}
+
+impl ScreenLayout {
+    pub fn new(area: Rect, cell: CellConfig, config: &LayoutConfig) -> Self {
+        let [area, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let cell = scale(cell, area, config);
+        // too small even then is caught before drawing
+        let cell = fit(
+            cell,
+            area.width.saturating_sub(2),
+            area.height.saturating_sub(3),
+            9,
+        )
+        .unwrap_or(COMPACT);
+        let board_w = cell.board_width();
+        let board_h = cell.board_height() + 2;
+        match config.align {
+            Align::Center => {
+                let [top, middle, footer] = Layout::vertical([
+                    Constraint::Fill(1),
+                    Constraint::Length(board_h),
+                    Constraint::Fill(1),
+                ])
+                .areas(area);
+                let [_, header, _] = Layout::vertical([
+                    Constraint::Fill(3),
+                    Constraint::Length(1),
+                    Constraint::Max(2),
+                ])
+                .areas(top);
+                Self {
+                    cell,
+                    header,
+                    board: center_horizontal(middle, board_w),
+                    footer,
+                    status,
+                }
+            }
+            Align::Top => {
+                let [header, middle, footer] = Layout::vertical([
+                    Constraint::Length(1),
+                    Constraint::Length(board_h),
+                    Constraint::Fill(1),
+                ])
+                .areas(area);
+                Self {
+                    cell,
+                    header,
+                    board: center_horizontal(middle, board_w),
+                    footer,
+                    status,
+                }
+            }
+            Align::Left => {
+                let [left, footer] =
+                    Layout::horizontal([Constraint::Length(board_w + 2), Constraint::Fill(1)])
+                        .areas(area);
+                let [_, header, middle, _] = Layout::vertical([
+                    Constraint::Fill(1),
+                    Constraint::Length(1),
+                    Constraint::Length(board_h),
+                    Constraint::Fill(1),
+                ])
+                .areas(left);
+                Self {
+                    cell,
+                    header,
+                    board: center_horizontal(middle, board_w),
+                    footer,
+                    status,
+                }
+            }
+        }
+    }
+}
+
+fn center_horizontal(area: Rect, width: u16) -> Rect {
+    let [_, board, _] = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Length(width),
+        Constraint::Fill(1),
+    ])
+    .vertical_margin(1)
+    .areas(area);
+    board
+}
+
+/// `cell`, or smaller cells when a board of `size` cells across doesn't fit
+/// `width` by `height`: first without padding, then narrower and lower.
+/// `None` when even [`COMPACT`] cells don't fit.
+pub fn fit(cell: CellConfig, width: u16, height: u16, size: usize) -> Option<CellConfig> {
+    if cell.board_width_of(size) <= width && cell.board_height_of(size) <= height {
+        return Some(cell);
+    }
+    let height = (1..=cell.height).rev().find(|&h| {
+        CellConfig {
+            height: h,
+            ..COMPACT
+        }
+        .board_height_of(size)
+            <= height
+    })?;
+    let width = (1..=cell.width).rev().find(|&w| {
+        CellConfig {
+            width: w,
+            ..COMPACT
+        }
+        .board_width_of(size)
+            <= width
+    })?;
+    Some(CellConfig {
+        width,
+        height,
+        padding: 0,
+    })
+}
+
+/// The terminal size `boards` boards of `size` cells across need side by
+/// side at their most compact, header and status line included.
+pub fn min_size(size: usize, boards: u16) -> (u16, u16) {
+    let width = COMPACT.board_width_of(size) * boards + 4 * (boards - 1) + 2;
+    (width, COMPACT.board_height_of(size) + 5)
+}
+
+fn scale(cell: CellConfig, area: Rect, config: &LayoutConfig) -> CellConfig {
+    if config.max_board_width.is_none() && config.max_board_height.is_none() {
+        return cell;
+    }
+    let max_w = config
+        .max_board_width
+        .unwrap_or(u16::MAX)
+        .min(area.width.saturating_sub(2));
+    // header line and the margin around the board
+    let max_h = config
+        .max_board_height
+        .unwrap_or(u16::MAX)
+        .min(area.height.saturating_sub(3));
+    let mut scaled = cell;
+    for factor in 2.. {
+        let next = cell.scaled(factor);
+        if next.board_width() > max_w || next.board_height() > max_h {
+            break;
+        }
+        scaled = next;
+    }
+    scaled
+}
@@ -1,75 +1,393 @@
+mod actions;
+pub mod analyze;
+mod animation;
+mod bot;
+mod cell_render;
+mod certificate;
+mod charset;
+mod clipboard;
+mod coach;
+mod collection;
+mod color;
+mod config;
+mod crash;
+mod cursor;
+mod daily;
+mod diagnostics;
+mod difficulty;
+pub mod doctor;
+mod duel;
+mod error_screen;
+mod events;
+pub mod form;
+pub mod formats;
+pub mod generator;
+mod grid;
+mod hints;
+mod hud;
+pub mod import;
+mod job;
+mod key_editor;
+mod keymap;
+mod layout;
+mod library;
+mod locale;
+mod menu;
+mod notes;
+pub mod packed;
+mod palette;
+mod pause;
+mod persist;
+mod prefs;
+mod rating;
+mod remote;
+mod replay;
+mod report;
+mod rng;
+mod rules;
+mod searchable;
+mod shading;
+mod sized;
+mod snapshot;
 mod soduko;
+mod solver;
+#[cfg(feature = "ssh")]
+pub mod ssh;
+mod stats;
+mod stats_view;
+mod statusline;
+mod symbols;
+pub mod telnet;
+mod theme;
+mod toast;
+mod touch;
+mod variant;
+pub mod walkthrough;
+mod watch;
+pub mod wordoku;
+mod worker;
 
+use color_eyre::eyre::{WrapErr, eyre};
 use ratatui::{
-    DefaultTerminal, Frame,
-    crossterm::event::{self, Event, KeyCode, KeyEventKind},
-    layout::{Constraint, Layout, Margin, Rect},
+    DefaultTerminal, Frame, Terminal,
+    backend::{Backend, TestBackend},
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+    layout::{Constraint, Flex, Layout, Margin, Rect},
     style::{self, Color, Modifier, Style, Stylize},
-    text::Text,
+    text::{Line, Span, Text},
     widgets::{
-        Block, BorderType, Cell, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
-        Table, TableState,
+        Block, BorderType, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
     },
 };
+use std::{
+    fs, io, mem,
+    num::NonZeroU8,
+    ops::Range,
+    path::Path,
+    sync::{atomic::AtomicBool, mpsc::Sender},
+    time::{Duration, Instant},
+};
+
 use style::palette::tailwind;
+use toml_edit::DocumentMut;
+use unicode_width::UnicodeWidthStr;
 
-use crate::soduko::BoardState;
+use crate::{
+    actions::ActionRegistry,
+    animation::{Animations, Easing, Effect, Unfold},
+    bot::Bot,
+    cell_render::{Marks, render_notes},
+    charset::GlyphSet,
+    clipboard::Clipboard,
+    coach::{CoachSummary, MoveQuality, RatedMove},
+    collection::{CollectionImport, ImportOutcome, ImportView},
+    config::CellConfig,
+    crash::Bundle,
+    cursor::{Cursor, Selection},
+    daily::{Archive, ArchiveView, Date},
+    diagnostics::Diagnostics,
+    duel::{Duel, DuelEvent, Seat},
+    error_screen::{ErrorScreen, ErrorView, Operation},
+    events::{Event, Events},
+    form::{Field, Form, FormEvent, TextArea},
+    formats::Exchange,
+    grid::{GridCell, SudokuGrid},
+    hints::Step,
+    hud::Hud,
+    import::{CapabilityReport, Imported},
+    job::{Background, JobEvent},
+    key_editor::{KeyEditor, KeyEditorView},
+    keymap::{Action, KeyPreset, Mode},
+    layout::{COMPACT, ScreenLayout},
+    library::{Change, Library, LibraryView, Typing},
+    menu::{MainMenu, MenuEvent, MenuView, Screen},
+    notes::{NoteLayout, Notes},
+    packed::Packed,
+    palette::{Palette, PaletteEvent, PaletteView},
+    pause::{Cover, PauseCover},
+    persist::{SavedGuess, Undo},
+    rating::Grade,
+    replay::{Recording, Replay},
+    report::GameReport,
+    rules::{Completion, RuleSet},
+    searchable::{SearchView, SearchableText},
+    shading::Shading,
+    sized::{SizedBoard, SizedEvent, SizedGame},
+    soduko::CellKind,
+    stats::{GameRecord, Suggestion},
+    stats_view::StatsView,
+    statusline::{Status, StatusLine},
+    theme::{TableColors, Theme},
+    toast::{Toast, ToastView},
+    touch::{Gesture, Gestures, Pad, Profile},
+    watch::ConfigWatcher,
+    worker::{Done, Work, Worker},
+};
+pub use crate::{
+    bot::Skill, config::Config, crash::install_panic_hook, difficulty::Difficulty,
+    formats::FormatRegistry, hints::Technique, job::GenerationJob, persist::SavedGame,
+    prefs::Prefs, rng::Rng, rules::GameMode, sized::BoardSize, soduko::BoardState, stats::Stats,
+    symbols::Symbols, variant::Variant,
+};
 
-const PALETTES: [tailwind::Palette; 4] = [
-    tailwind::BLUE,
-    tailwind::EMERALD,
-    tailwind::RED,
-    tailwind::INDIGO,
-];
-const INFO_TEXT: [&str; 6] = [
-    "(Esc) quit | (↑) move up | (↓) move down | (←) move left | (→) move right",
-    "Write numbers 1-9 in cells",
-    "(Backspace, Delete, 0) erease cell",
-    "('c' or Enter) check if solvable",
-    "(s) to solve if possible",
-    "(n) to clear all cells",
+const ITEM_HEIGHT: usize = 4;
+const THEME_TRANSITION: Duration = Duration::from_millis(300);
+const FLASH: Duration = Duration::from_millis(400);
+const PULSE: Duration = Duration::from_millis(600);
+const FILL_IN: Duration = Duration::from_millis(300);
+const HINT_COLOR: Color = tailwind::AMBER.c400;
+const CONFLICT_COLOR: Color = tailwind::RED.c400;
+/// backgrounds the player can color cells with, in the order they cycle
+const HIGHLIGHTS: [Color; 6] = [
+    tailwind::RED.c900,
+    tailwind::AMBER.c800,
+    tailwind::LIME.c900,
+    tailwind::SKY.c900,
+    tailwind::VIOLET.c900,
+    tailwind::PINK.c900,
 ];
+const FILL_IN_STAGGER: Duration = Duration::from_millis(15);
+/// time between cells when watching the solution unfold
+const UNFOLD_STEP: Duration = Duration::from_millis(120);
+const PREVIEW: Duration = Duration::from_secs(3);
+/// hints a solution preview counts as
+const PREVIEW_COST: u32 = 5;
+/// hints revealing a cell counts as, it gives the digit away where a hint
+/// only points at a step
+const REVEAL_COST: u32 = 2;
 
-const ITEM_HEIGHT: usize = 4;
+type SodukoData = BoardState;
 
-struct TableColors {
-    buffer_bg: Color,
-    header_bg: Color,
-    header_fg: Color,
-    row_fg: Color,
-    selected_row_style_fg: Color,
-    selected_column_style_fg: Color,
-    selected_cell_style_fg: Color,
-    normal_row_color: Color,
-    alt_row_color: Color,
-    footer_border_color: Color,
+/// The game as it was when the player marked a guess, to go back to when
+/// the guess runs into a contradiction.
+struct Guess {
+    data: SodukoData,
+    notes: Notes,
+    center: Notes,
+    revealed: [[bool; 9]; 9],
+    /// length of the undo history at the time
+    history: usize,
+    cell: (usize, usize),
 }
 
-impl TableColors {
-    const fn new(color: &tailwind::Palette) -> Self {
-        Self {
-            buffer_bg: tailwind::SLATE.c950,
-            header_bg: color.c900,
-            header_fg: tailwind::SLATE.c200,
-            row_fg: tailwind::SLATE.c200,
-            selected_row_style_fg: color.c400,
-            selected_column_style_fg: color.c400,
-            selected_cell_style_fg: color.c600,
-            normal_row_color: tailwind::SLATE.c950,
-            alt_row_color: tailwind::SLATE.c900,
-            footer_border_color: color.c400,
+/// A yes/no question shown over the board until it's answered.
+enum Prompt {
+    Suggestion(Suggestion),
+    /// whether to give up and fill in the solution, at once or cell by cell
+    Solve,
+    /// whether to save the game on the way out
+    Quit,
+    /// an import that dropped rules, waiting for the go ahead
+    Import(Box<Imported>),
+    /// whether to forget this many puzzles of the library's archive
+    Purge(usize),
+}
+
+impl Prompt {
+    fn message(&self) -> String {
+        match self {
+            Prompt::Suggestion(suggestion) => suggestion.message(),
+            Prompt::Quit => {
+                "Save the game before quitting?\ny save and quit, d quit without saving".into()
+            }
+            Prompt::Solve => {
+                "Give up and fill in the solution?\ny all at once, s one cell at a time".into()
+            }
+            Prompt::Purge(count) => format!(
+                "Forget {count} puzzle{} and {} games for good?",
+                if *count == 1 { "" } else { "s" },
+                if *count == 1 { "its" } else { "their" }
+            ),
+            Prompt::Import(imported) => {
+                let rules: Vec<String> = imported
+                    .report
+                    .dropped_rules()
+                    .map(|rule| format!("{} x{}", rule.name, rule.count))
+                    .collect();
+                format!(
+                    "This puzzle has rules that won't be checked:\n{}\nPlay it anyway?",
+                    rules.join("\n")
+                )
+            }
         }
     }
 }
-
-type SodukoData = BoardState;
 pub struct App {
-    state: TableState,
+    cursor: Cursor,
     data: SodukoData,
+    /// for generating new puzzles
+    rng: Rng,
+    /// where the puzzle came from if it was shared with a solution hash
+    exchange: Option<Exchange>,
+    /// the board as it was when the game started
+    puzzle: BoardState,
+    /// solution shown dimmed in the empty cells until it times out
+    preview: Option<(BoardState, Instant)>,
+    /// the step the hint engine explained last, until the next placement
+    hint: Option<Step>,
+    /// cells changed this game as they were before, newest last
+    history: Vec<Undo>,
+    /// stretches of `history` written by one change to many cells, undone
+    /// together
+    bulk: Vec<Range<usize>>,
+    /// cells picked out for clearing, noting or coloring all at once
+    selection: Selection,
+    /// changed since last saved, to tell whether quitting would lose
+    /// anything
+    dirty: bool,
+    /// guesses marked this game, the latest last
+    guesses: Vec<Guess>,
+    game_mode: GameMode,
+    /// how values are shown and typed, letters in a wordoku
+    symbols: Symbols,
+    /// unicode or ASCII, whatever the terminal shows
+    glyphs: GlyphSet,
+    notes: Notes,
+    /// center marks, kept apart from the corner marks of notes mode
+    center: Notes,
+    /// cells filled by the reveal command, drawn in the hint color
+    revealed: [[bool; 9]; 9],
+    hints_used: u32,
+    /// placements this game that left the board without a solution
+    mistakes: u32,
+    /// 1 based index into [`HIGHLIGHTS`] per cell, 0 for none
+    highlights: [[u8; 9]; 9],
+    /// answers submitted this game, where the rules ask for it
+    submissions: u32,
+    /// proof of the last competition solve
+    certificate: Option<String>,
     scroll_state: ScrollbarState,
     colors: TableColors,
+    target_colors: TableColors,
+    /// colors shown when the transition started, and when it started
+    transition: Option<(TableColors, Instant)>,
+    /// the theme shown, the one picked unless the picker is previewing
+    /// another
     color_index: usize,
+    /// whether the board was checked and found right, shown in the theme's
+    /// success or error colors until the next change
+    verdict: Option<bool>,
+    /// the built in themes and the player's
+    themes: Vec<Theme>,
+    /// the theme picked
+    theme: usize,
+    /// the theme selection open over the board, and the theme to go back
+    /// to when it's closed without picking
+    theme_picker: Option<(usize, usize)>,
+    prefs: Prefs,
     auto_check: bool,
+    /// writing a digit moves on to the next cell that isn't a clue, and
+    /// Enter goes down a row, for typing in a whole grid
+    rapid_entry: bool,
+    /// placements rated so far, while the coach is on
+    coach: Option<CoachSummary>,
+    /// the placements behind `coach`, in order
+    rated: Vec<RatedMove>,
+    last_move: Option<MoveQuality>,
+    /// the bot the game is a race against
+    race: Option<Bot>,
+    /// the solution being written in cell by cell, until a key stops it
+    unfolding: Option<Unfold>,
+    /// the moves of the game so far, to replay once it's over
+    recording: Recording,
+    replay: Option<Replay>,
+    /// the game time each tenth of the empty cells was first filled by
+    curve: Vec<u32>,
+    mode: Mode,
+    started: Instant,
+    /// when the game was paused and what covers the board meanwhile
+    paused: Option<(Instant, Box<dyn Cover>)>,
+    modified: bool,
+    animations: Animations,
+    toast: Option<Toast>,
+    help: Option<SearchableText>,
+    /// the puzzle, board and notes last kept for a crash report
+    reported: Option<(BoardState, SodukoData, Notes)>,
+    /// what the help panel shows, the keys or a hint's explanation
+    help_title: &'static str,
+    key_editor: Option<KeyEditor>,
+    palette: Option<Palette>,
+    library: Option<Library>,
+    /// where to put the board, open over it
+    export: Option<Form>,
+    /// tags put on every puzzle of the collection being imported
+    import_tags: Vec<String>,
+    /// which collection to import into the library and how to tag it
+    import_form: Option<Form>,
+    collection: Option<CollectionImport>,
+    clipboard: Clipboard,
+    /// a failed save or import, open over everything until dealt with
+    error: Option<ErrorScreen>,
+    /// set once the app should quit, after the error screen is dealt with
+    exit: bool,
+    /// the puzzle note being written, open over the board
+    note_editor: Option<TextArea>,
+    /// the puzzle note as written this game, before it's recorded, empty
+    /// when it was cleared
+    note: Option<String>,
+    difficulty: Difficulty,
+    /// the variant new games are played in
+    variant: Variant,
+    stats: Stats,
+    prompt: Option<Prompt>,
+    /// set once the current game has been recorded in the stats
+    finished: bool,
+    /// shown in the header while playing an imported puzzle without
+    /// exactly one solution
+    warning: Option<&'static str>,
+    /// shown in the header while playing an imported puzzle, see
+    /// [`rating::rate`]
+    grade: Option<Grade>,
+    config: Config,
+    gestures: Gestures,
+    /// hard puzzle search running in the background
+    generation: Option<Background>,
+    /// its puzzle once found, until played
+    generated: Option<BoardState>,
+    /// where workers send their puzzles, once the event loop runs
+    workers: Option<Sender<Event>>,
+    /// the puzzle being generated off the render thread
+    worker: Option<Worker>,
+    /// the id of the work asked for last, what older work finishes with is
+    /// dropped
+    work_id: u64,
+    /// layout of the last frame, for finding what a tap landed on
+    layout: Option<ScreenLayout>,
+    /// latencies measured this session, when asked for
+    diagnostics: Option<Diagnostics>,
+    /// size of the last frame, for drawing the view again offscreen
+    area: Rect,
+    /// the menus, or the game
+    screen: Screen,
+    /// where each footer hint was drawn, for clicking them
+    buttons: Vec<(Rect, Action)>,
+    /// a remote player's game, kept away from the host's files, clipboard
+    /// and config
+    sandboxed: bool,
+    /// the preferences or stats file didn't read, and isn't written over
+    /// until it does
+    prefs_unread: bool,
+    stats_unread: bool,
 }
 
 pub type Result = color_eyre::Result<()>;
@@ -82,74 +400,515 @@ impl Default for App {
 
 impl App {
     pub fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let mut rng = Rng::from_time();
+        let puzzle = generator::generate(Difficulty::default(), config.variant, &mut rng);
+        let themes = Theme::all(&config.themes);
+        let glyphs = config.charset.glyphs();
         Self {
-            state: TableState::default().with_selected(0),
+            cursor: Cursor::default(),
             scroll_state: ScrollbarState::new(9 + 2),
-            colors: TableColors::new(&PALETTES[0]),
+            colors: themes[0].colors,
+            target_colors: themes[0].colors,
+            transition: None,
             color_index: 0,
-            data: SodukoData::default(),
+            verdict: None,
+            themes,
+            theme: 0,
+            theme_picker: None,
+            prefs: Prefs::default(),
+            data: puzzle,
+            puzzle,
+            rng,
+            exchange: None,
+            history: Vec::new(),
+            bulk: Vec::new(),
+            selection: Selection::default(),
+            dirty: false,
+            guesses: Vec::new(),
+            preview: None,
+            hint: None,
+            game_mode: GameMode::default(),
+            symbols: config.symbols.symbols().with_glyphs(glyphs),
+            glyphs,
+            notes: Notes::default(),
+            center: Notes::default(),
+            revealed: [[false; 9]; 9],
+            hints_used: 0,
+            mistakes: 0,
+            highlights: [[0; 9]; 9],
+            submissions: 0,
+            certificate: None,
             auto_check: false,
+            rapid_entry: false,
+            coach: config.coach.then(CoachSummary::default),
+            rated: Vec::new(),
+            race: None,
+            unfolding: None,
+            recording: Recording::default(),
+            replay: None,
+            curve: Vec::new(),
+            last_move: None,
+            mode: Mode::default(),
+            started: Instant::now(),
+            paused: None,
+            modified: false,
+            animations: Animations::new(!config.reduced_motion),
+            toast: None,
+            help: None,
+            reported: None,
+            help_title: " Help ",
+            key_editor: None,
+            palette: None,
+            library: None,
+            export: None,
+            import_form: None,
+            import_tags: Vec::new(),
+            collection: None,
+            clipboard: Clipboard::default(),
+            error: None,
+            exit: false,
+            note_editor: None,
+            note: None,
+            difficulty: Difficulty::default(),
+            variant: config.variant,
+            stats: Stats::default(),
+            prompt: None,
+            finished: false,
+            warning: None,
+            grade: None,
+            config,
+            gestures: Gestures::default(),
+            generation: None,
+            workers: None,
+            worker: None,
+            work_id: 0,
+            generated: None,
+            layout: None,
+            area: Rect::default(),
+            screen: Screen::Game,
+            diagnostics: None,
+            buttons: Vec::new(),
+            sandboxed: false,
+            prefs_unread: false,
+            stats_unread: false,
         }
     }
-    pub fn next_row(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= 9 - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+
+    /// [`App::with_config`] with the config file as it read, or with the
+    /// defaults and the error open until the file is fixed and reloaded.
+    pub fn with_config_file(config: color_eyre::Result<Config>) -> Self {
+        match config {
+            Ok(config) => Self::with_config(config),
+            Err(err) => {
+                let mut app = Self::with_config(Config::default());
+                app.fail(Operation::ReloadConfig, err);
+                app
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
-        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        }
     }
 
-    pub fn previous_row(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    9 - 1
-                } else {
-                    i - 1
-                }
+    /// Takes the preferences as they read, or opens the error and plays on
+    /// the defaults without saving them over the file.
+    pub fn with_prefs(mut self, prefs: color_eyre::Result<Prefs>) -> Self {
+        match prefs {
+            Ok(prefs) => self.use_prefs(prefs),
+            Err(err) => {
+                self.prefs_unread = true;
+                self.fail(Operation::LoadPrefs, err);
+            }
+        }
+        self
+    }
+
+    fn use_prefs(&mut self, prefs: Prefs) {
+        self.prefs_unread = false;
+        self.prefs = prefs;
+        self.theme = self
+            .themes
+            .iter()
+            .position(|theme| theme.name == self.prefs.theme)
+            .unwrap_or(0);
+        self.color_index = self.theme;
+        self.colors = self.themes[self.theme].colors;
+        self.target_colors = self.colors;
+    }
+
+    /// Takes the stats as they read, or opens the error and records games
+    /// without saving them over the file.
+    pub fn with_stats(mut self, stats: color_eyre::Result<Stats>) -> Self {
+        match stats {
+            Ok(stats) => self.stats = stats,
+            Err(err) => {
+                self.stats_unread = true;
+                self.fail(Operation::LoadStats, err);
+            }
+        }
+        self
+    }
+
+    /// Starts a new puzzle of `difficulty`, which new games keep to.
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        let puzzle = generator::generate(difficulty, self.variant, &mut self.rng);
+        self.with_puzzle(puzzle)
+    }
+
+    /// Opens a game on a 6x6 or 16x16 board over the game.
+    pub fn with_size(mut self, size: BoardSize) -> Self {
+        self.work(Work::Sized(size));
+        self
+    }
+
+    /// Starts a new puzzle of `variant`, which new games keep to.
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        let puzzle = generator::generate(self.difficulty, variant, &mut self.rng);
+        self.with_puzzle(puzzle)
+    }
+
+    pub fn with_puzzle(mut self, puzzle: BoardState) -> Self {
+        self.data = puzzle;
+        self.puzzle = puzzle;
+        self
+    }
+
+    /// Picks up a game passed on as a [`formats::game_code`].
+    pub fn with_game(mut self, game: Packed) -> Self {
+        self = self.with_puzzle(game.puzzle);
+        if let Some(board) = game.board {
+            self.data = board;
+        }
+        self.notes = game.notes;
+        self
+    }
+
+    /// Plays a puzzle from an exchange code, checking the finished board
+    /// against the author's solution.
+    pub fn with_exchange(mut self, exchange: Exchange) -> Self {
+        self = self.with_puzzle(exchange.puzzle);
+        self.toast = Some(Toast::info(format!(
+            "{} puzzle, the author's rating",
+            exchange.stars(self.glyphs)
+        )));
+        self.exchange = Some(exchange);
+        self
+    }
+
+    /// Picks up the game that was in progress when the app last closed, or
+    /// keeps the new one with a toast saying why when the save won't read.
+    pub fn with_saved(mut self, saved: color_eyre::Result<Option<SavedGame>>) -> Self {
+        let resumed = saved.and_then(|saved| match saved {
+            Some(saved) => self.resume(saved),
+            None => Ok(()),
+        });
+        if let Err(err) = resumed {
+            self.toast = Some(Toast::error(format!(
+                "couldn't resume the saved game, starting a new one: {err:#}"
+            )));
+        }
+        self
+    }
+
+    fn resume(&mut self, saved: SavedGame) -> color_eyre::Result<()> {
+        // everything that can fail first, so a bad save leaves the new game
+        // as it was
+        let puzzle = saved.puzzle()?;
+        let data = saved.board()?;
+        let history = saved.history()?;
+        let exchange = saved.exchange.as_deref().map(Exchange::parse).transpose()?;
+        let guesses = saved
+            .guesses
+            .iter()
+            .map(|guess| {
+                Ok(Guess {
+                    data: saved.guess_board(guess)?,
+                    notes: guess.notes,
+                    center: guess.center,
+                    revealed: guess.revealed,
+                    history: guess.history,
+                    cell: guess.cell,
+                })
+            })
+            .collect::<color_eyre::Result<_>>()?;
+        self.puzzle = puzzle;
+        self.data = data;
+        self.notes = saved.notes;
+        self.center = saved.center;
+        self.history = history;
+        self.bulk = saved.bulk;
+        self.guesses = guesses;
+        self.exchange = exchange;
+        self.set_game_mode(saved.game_mode);
+        self.difficulty = saved.difficulty;
+        self.hints_used = saved.hints;
+        self.mistakes = saved.mistakes;
+        self.revealed = saved.revealed;
+        self.highlights = saved.highlights;
+        self.submissions = saved.submissions;
+        self.curve = saved.curve;
+        self.recording = Recording::resumed(saved.moves);
+        let played = Duration::from_secs(saved.seconds);
+        self.started = Instant::now()
+            .checked_sub(played)
+            .unwrap_or_else(Instant::now);
+        self.touch();
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Resumes an unfinished job, or offers the puzzle of a finished one.
+    /// A job file that won't read leaves a toast instead.
+    pub fn with_generation(mut self, job: color_eyre::Result<Option<GenerationJob>>) -> Self {
+        match job {
+            Ok(Some(job)) => match job.puzzle().filter(|_| job.is_done()) {
+                Some(puzzle) => self.generated_ready(puzzle),
+                None => self.generation = Some(Background::spawn(job)),
+            },
+            Ok(None) => {}
+            Err(err) => {
+                self.toast = Some(Toast::error(format!(
+                    "couldn't pick up the hard puzzle search: {err:#}"
+                )));
             }
-            None => 0,
+        }
+        self
+    }
+
+    fn generated_ready(&mut self, puzzle: BoardState) {
+        self.generated = Some(puzzle);
+        self.toast = Some(Toast::info("hard puzzle ready, press G to play it"));
+    }
+
+    fn poll_generation(&mut self) {
+        let Some(event) = self.generation.as_ref().and_then(Background::poll) else {
+            return;
         };
-        self.state.select(Some(i));
-        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        self.generation = None;
+        match event {
+            JobEvent::Done(puzzle) => self.generated_ready(puzzle),
+            JobEvent::Failed(err) => self.toast = Some(Toast::error(err)),
+        }
+    }
+
+    fn play_generated(&mut self) {
+        let Some(puzzle) = self.generated.take() else {
+            self.toast = Some(Toast::info("no generated puzzle yet"));
+            return;
+        };
+        self.start(puzzle);
+        if let Err(err) = GenerationJob::remove() {
+            self.toast = Some(Toast::error(format!("{err:#}")));
+        }
+    }
+
+    /// Keeps a remote player off the host: no exports, imports, clipboard
+    /// or bug reports, and key changes last only the session instead of
+    /// going into the host's config.
+    pub(crate) fn sandboxed(mut self) -> Self {
+        self.sandboxed = true;
+        self.clipboard = Clipboard::disabled();
+        self
+    }
+
+    /// Tells a remote player what they can't do, `true` when sandboxed.
+    fn refuse_remote(&mut self, what: &str) -> bool {
+        if self.sandboxed {
+            self.toast = Some(Toast::info(format!("no {what} in remote games")));
+        }
+        self.sandboxed
+    }
+
+    /// Edits the config file, or only the running game's config for a
+    /// remote player.
+    fn edit_config(&self, edit: impl FnOnce(&mut DocumentMut)) -> Result {
+        match self.sandboxed {
+            true => Ok(()),
+            false => Config::edit_file(edit),
+        }
+    }
+
+    /// Measures input and draw latencies and shows them in the header.
+    pub fn with_diagnostics(mut self) -> Self {
+        self.diagnostics = Some(Diagnostics::default());
+        self
+    }
+
+    /// Writes the latencies measured this session as JSON.
+    pub fn save_diagnostics(&self, path: &Path) -> Result {
+        match &self.diagnostics {
+            Some(diagnostics) => diagnostics.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Plays an imported board, first asking whether to go ahead if the
+    /// import had to drop some of the puzzle's rules.
+    pub fn with_import(mut self, imported: Imported) -> Self {
+        if imported.report.dropped_rules().next().is_some() {
+            self.prompt = Some(Prompt::Import(Box::new(imported)));
+            self.mode = Mode::Prompt;
+            return self;
+        }
+        self = self.with_puzzle(imported.board);
+        self.note_import(&imported);
+        self
+    }
+
+    /// Plays an imported puzzle.
+    fn play_import(&mut self, imported: &Imported) {
+        self.start(imported.board);
+        self.note_import(imported);
+    }
+
+    /// Tells what an imported puzzle is like and what of its file was left
+    /// out.
+    fn note_import(&mut self, imported: &Imported) {
+        self.warning = imported.ambiguity();
+        self.grade = rating::rate(&imported.board);
+        if let Some(summary) = imported.report.summary() {
+            self.toast = Some(Toast::info(format!("left out {summary}")));
+        }
+    }
+
+    /// What the game mode allows, waiting for a submit if the config says
+    /// so.
+    fn rules(&self) -> RuleSet {
+        let mut rules = self.game_mode.rules();
+        rules.submit |= self.config.completion == Completion::Manual;
+        rules
+    }
+
+    /// Plays in `game_mode`, the daily one on the day's puzzle.
+    pub fn with_game_mode(mut self, game_mode: GameMode) -> Self {
+        self.set_game_mode(game_mode);
+        if game_mode == GameMode::Daily {
+            let date = Date::today();
+            self.difficulty = Difficulty::default();
+            self.start(date.puzzle());
+            self.toast = Some(Toast::info(format!(
+                "the daily puzzle of {}",
+                locale::format_date(&self.config.locale, date)
+            )));
+        }
+        self
+    }
+
+    fn set_game_mode(&mut self, game_mode: GameMode) {
+        self.game_mode = game_mode;
+        // the coach may have been switched on in the config
+        if !game_mode.rules().hints {
+            self.coach = None;
+        }
+    }
+
+    pub fn with_symbols(mut self, symbols: Symbols) -> Self {
+        self.symbols = symbols.with_glyphs(self.glyphs);
+        self
+    }
+
+    pub fn next_row(&mut self) {
+        self.cursor.next_row();
+        self.scroll_state = self.scroll_state.position(self.cursor.row() * ITEM_HEIGHT);
+    }
+
+    pub fn previous_row(&mut self) {
+        self.cursor.previous_row();
+        self.scroll_state = self.scroll_state.position(self.cursor.row() * ITEM_HEIGHT);
     }
 
     pub fn next_column(&mut self) {
-        self.state.select_next_column();
+        self.cursor.next_column();
     }
 
     pub fn previous_column(&mut self) {
-        self.state.select_previous_column();
+        self.cursor.previous_column();
     }
 
     pub fn next_color(&mut self) {
-        self.color_index = (self.color_index + 1) % PALETTES.len();
+        self.pick_theme((self.theme + 1) % self.themes.len());
     }
 
     pub fn previous_color(&mut self) {
-        let count = PALETTES.len();
-        self.color_index = (self.color_index + count - 1) % count;
+        let count = self.themes.len();
+        self.pick_theme((self.theme + count - 1) % count);
+    }
+
+    fn pick_theme(&mut self, index: usize) {
+        self.theme = index;
+        self.color_index = index;
+        self.prefs.theme = self.themes[index].name.clone();
+    }
+
+    /// Opens the theme selection, or closes it going back to the theme
+    /// from before.
+    fn toggle_theme_picker(&mut self) {
+        match self.theme_picker.take() {
+            Some((_, original)) => {
+                self.color_index = original;
+                self.mode = Mode::Normal;
+            }
+            None => {
+                self.theme_picker = Some((self.theme, self.theme));
+                self.mode = Mode::Themes;
+            }
+        }
+    }
+
+    fn apply_theme(&mut self) {
+        if let Some((selected, _)) = self.theme_picker.take() {
+            self.pick_theme(selected);
+            self.mode = Mode::Normal;
+        }
+    }
+
+    /// Moves the selection, showing the selected theme straight away.
+    fn preview_theme(&mut self, delta: isize) {
+        if let Some((selected, _)) = &mut self.theme_picker {
+            *selected = selected
+                .saturating_add_signed(delta)
+                .min(self.themes.len() - 1);
+            self.color_index = *selected;
+        }
     }
 
     fn set_colors(&mut self) {
-        self.colors = TableColors::new(&PALETTES[self.color_index]);
+        let colors = self.themes[self.color_index].colors;
+        let target = match self.verdict {
+            Some(true) => colors.accented(colors.success),
+            Some(false) => colors.accented(colors.error),
+            None => colors,
+        };
+        if target != self.target_colors {
+            if !self.config.reduced_motion {
+                self.transition = Some((self.colors, Instant::now()));
+            }
+            self.target_colors = target;
+        }
+        self.colors = match self.transition {
+            Some((from, started)) if started.elapsed() < THEME_TRANSITION => {
+                let t = started.elapsed().as_secs_f32() / THEME_TRANSITION.as_secs_f32();
+                from.lerp(&target, color::ease_in_out(t))
+            }
+            _ => {
+                self.transition = None;
+                target
+            }
+        };
     }
 
     fn good_color(&mut self) {
-        self.color_index = 1;
+        self.verdict = Some(true);
     }
     fn neautral_color(&mut self) {
-        self.color_index = 0;
+        self.color_index = self.theme;
+        self.verdict = None;
     }
     fn bad_color(&mut self) {
-        self.color_index = 2;
+        self.verdict = Some(false);
     }
 
     fn check(&mut self) {
@@ -160,215 +919,3717 @@ impl App {
         }
     }
 
-    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result {
+    pub fn run(&mut self, mut terminal: DefaultTerminal) -> Result {
+        touch::set_mouse_capture(self.config.captures_mouse())?;
+        let enhanced = keymap::enhance_keyboard().unwrap_or(false);
+        events::enable_paste()?;
+        let result = self.event_loop(&mut terminal, Events::spawn());
+        events::disable_paste()?;
+        if enhanced {
+            keymap::restore_keyboard()?;
+        }
+        touch::set_mouse_capture(false)?;
+        result
+    }
+
+    /// Draws and handles `events` until the app quits or they run out.
+    pub(crate) fn event_loop<B: Backend<Error = io::Error>>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        events: Events,
+    ) -> Result {
+        // a remote player's game keeps the config it started with
+        let watcher = match self.sandboxed {
+            true => None,
+            false => Config::path().and_then(|path| ConfigWatcher::new(vec![path])),
+        };
+        self.workers = Some(events.workers());
+        // when the event the next frame shows arrived
+        let mut input: Option<Instant> = None;
         loop {
+            if watcher.as_ref().is_some_and(ConfigWatcher::changed) {
+                self.reload_config();
+            }
+            self.on_tick();
+            let drawing = Instant::now();
             terminal.draw(|frame| self.draw(frame))?;
-
-            if let Event::Key(key) = event::read()?
-                && key.kind == KeyEventKind::Press
-            {
-                match key.code {
-                    KeyCode::Enter | KeyCode::Char('c') => self.check(),
-                    KeyCode::Char('a') => self.auto_check = !self.auto_check,
-                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                    KeyCode::Char('j') | KeyCode::Down => self.next_row(),
-                    KeyCode::Char('k') | KeyCode::Up => self.previous_row(),
-                    KeyCode::Char('l') | KeyCode::Right => self.next_column(),
-                    KeyCode::Char('h') | KeyCode::Left => self.previous_column(),
-                    KeyCode::Char('s') => self.solve(),
-                    KeyCode::Char('n') => self.clear(),
-                    KeyCode::Backspace | KeyCode::Delete => {
-                        let Some((r, col)) = self.state.selected_cell() else {
-                            continue;
-                        };
-                        self.data.set(r as u8, col as u8, 0.into());
-                        if self.auto_check {
-                            self.check();
-                        } else {
-                            self.neautral_color();
-                        }
-                    }
-                    KeyCode::Char(c) if c.is_ascii_digit() => {
-                        let Some((r, col)) = self.state.selected_cell() else {
-                            continue;
-                        };
-                        let d = c.to_digit(10).unwrap() as u8;
-                        self.data.set(r as u8, col as u8, d.into());
-                        if self.auto_check {
-                            self.check();
-                        } else {
-                            self.neautral_color();
-                        }
+            if let Some(diagnostics) = &mut self.diagnostics {
+                diagnostics.drew(drawing.elapsed());
+                if let Some(at) = input.take() {
+                    diagnostics.rendered(at.elapsed());
+                }
+            }
+            let event = events.next(self.tick_rate())?;
+            if !matches!(event, Event::Tick) {
+                input = Some(Instant::now());
+            }
+            if let Event::Key(key) = &event {
+                crash::note_key(
+                    keymap::key_name(key.code).unwrap_or_else(|| format!("{:?}", key.code)),
+                );
+            }
+            let quit = match event {
+                Event::Key(key) => self.handle_key(key),
+                Event::Mouse(mouse) => self.handle_mouse(mouse),
+                Event::Paste(text) => {
+                    self.paste(&text);
+                    false
+                }
+                // a full screen terminal picks up the new size by itself, one
+                // drawn over a connection has to be told
+                Event::Resize(width, height) => {
+                    terminal.resize(Rect::new(0, 0, width, height))?;
+                    false
+                }
+                Event::Worked(id, work, done) => {
+                    if id == self.work_id {
+                        self.worked(work, done);
                     }
-                    _ => {}
+                    false
                 }
+                Event::Tick => false,
+            };
+            if let (Some(diagnostics), Some(at)) = (&mut self.diagnostics, input) {
+                diagnostics.handled(at.elapsed());
+            }
+            let board = (self.puzzle, self.data, self.notes);
+            if self.reported != Some(board) {
+                crash::note_board(self.board_for_report());
+                self.reported = Some(board);
+            }
+            if quit {
+                return Ok(());
             }
         }
     }
 
-    fn draw(&mut self, frame: &mut Frame) {
-        let vertical = Layout::vertical([
-            Constraint::Fill(1),
-            Constraint::Length(9 + 2 + 2),
-            Constraint::Fill(1),
-        ]);
-        let vertical_areas = vertical.split(frame.area());
-        let grid_row = Layout::horizontal([
-            Constraint::Fill(1),
-            Constraint::Length(4 * 9),
-            Constraint::Fill(1),
-        ])
-        .vertical_margin(1)
-        .split(vertical_areas[1]);
-
-        self.set_colors();
-
-        self.render_header(frame, vertical_areas[0]);
-        self.render_table(frame, grid_row[1]);
-        // self.render_scrollbar(frame, rects[0]);
-        self.render_footer(frame, vertical_areas[2]);
+    /// How long to wait for input before the next tick: regularly so the
+    /// timer in the status line keeps running, and at frame rate while
+    /// anything is animating.
+    pub fn tick_rate(&self) -> Duration {
+        if self.transition.is_some()
+            || self.animations.is_active()
+            || self.worker.is_some()
+            || self.unfolding.is_some()
+            || self.replay.is_some()
+        {
+            Duration::from_millis(16)
+        } else {
+            Duration::from_millis(250)
+        }
     }
 
-    fn render_table(&mut self, frame: &mut Frame, area: Rect) {
-        let selected_row_style = Style::default()
-            .add_modifier(Modifier::REVERSED)
-            .fg(self.colors.selected_row_style_fg);
-        let selected_col_style = Style::default().fg(self.colors.selected_column_style_fg);
-        let selected_cell_style = Style::default()
-            .add_modifier(Modifier::REVERSED)
-            .fg(self.colors.selected_cell_style_fg);
-
-        let rows = self.data.iter().enumerate().map(|(r, data)| {
-            let color = match r % 2 {
-                0 => self.colors.normal_row_color,
-                _ => self.colors.alt_row_color,
-            };
-            let base_style = Style::new().fg(self.colors.row_fg).bg(color);
-            let underline = (r + 1) % 3 == 0 && (r + 1) < 9;
-            let style = if underline {
-                // base_style.add_modifier(Modifier::UNDERLINED)
-                base_style
-            } else {
-                base_style
-            };
-            data.iter()
-                .enumerate()
-                .map(|(col, content)| {
-                    let mut text = Text::from(format!("{content}"));
-                    if (col + 1) % 3 == 0 && (col + 1) < 9 {
-                        text.push_span(" |");
-                        text = text.right_aligned();
-                    } else {
-                        text = text.centered();
-                    }
-                    if underline {
-                        text.push_line("----");
-                    }
-                    Cell::from(text)
-                })
-                .collect::<Row>()
-                .style(style)
-                .height(if underline { 2 } else { 1 })
-        });
-        let t = Table::new(
-            rows,
-            [
-                Constraint::Length(4),
-                Constraint::Length(4),
-                Constraint::Length(4),
-                //
-                Constraint::Length(4),
-                Constraint::Length(4),
-                Constraint::Length(4),
-                //
-                Constraint::Length(4),
-                Constraint::Length(4),
-                Constraint::Length(4),
-            ],
-        )
-        // .header(header)
-        .row_highlight_style(selected_row_style)
-        .column_highlight_style(selected_col_style)
-        .cell_highlight_style(selected_cell_style)
-        // .highlight_symbol(Text::from(vec![
-        //     "".into(),
-        //     bar.into(),
-        //     bar.into(),
-        //     "".into(),
-        // ]))
-        .bg(self.colors.buffer_bg)
-        .column_spacing(0);
-        // .highlight_spacing(HighlightSpacing::Always);
-        frame.render_stateful_widget(t, area, &mut self.state);
+    /// Takes in whatever work in the background has finished.
+    pub fn on_tick(&mut self) {
+        self.poll_generation();
+        self.poll_collection();
+        if let Some(library) = &mut self.library {
+            library.poll_rating();
+        }
+        self.record_progress();
+        self.advance_race();
+        self.unfold();
+        self.play_replay();
+        if let Screen::Duel(duel) = &mut self.screen
+            && let Some(DuelEvent::OutOfTime(seat)) = duel.tick()
+        {
+            self.out_of_time(seat);
+        }
     }
 
-    #[allow(dead_code)]
-    fn render_scrollbar(&mut self, frame: &mut Frame, area: Rect) {
-        frame.render_stateful_widget(
-            Scrollbar::default()
-                .orientation(ScrollbarOrientation::VerticalRight)
-                .begin_symbol(None)
-                .end_symbol(None),
-            area.inner(Margin {
-                vertical: 1,
-                horizontal: 1,
-            }),
-            &mut self.scroll_state,
-        );
+    fn out_of_time(&mut self, seat: usize) {
+        self.toast = Some(Toast::info(format!(
+            "player {} ran out of time, player {} wins",
+            seat + 1,
+            2 - seat
+        )));
     }
 
-    fn render_header(&self, frame: &mut Frame, area: Rect) {
-        let header_style = Style::default()
-            .fg(self.colors.header_fg)
-            .bg(self.colors.header_bg);
-        let lay = Layout::vertical([
-            Constraint::Fill(3),
-            Constraint::Length(1),
-            Constraint::Max(2),
-        ])
-        .split(area);
-        frame.render_widget(
-            Paragraph::new("Soduku")
-                .style(header_style.add_modifier(Modifier::BOLD))
-                .centered(),
-            lay[1],
-        );
+    /// Handles a key press. `true` once the app should quit, with
+    /// everything saved that could be.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.dispatch_key(key) {
+            self.exit = true;
+        }
+        self.exit
     }
-    fn render_footer(&self, frame: &mut Frame, area: Rect) {
-        let mut text = Text::from_iter(INFO_TEXT);
-        if self.auto_check {
-            text.push_line("(a) to toggle auto check off");
-        } else {
-            text.push_line("(a) to toggle auto check on");
-        };
-        let info_footer = Paragraph::new(text)
-            .style(
-                Style::new()
-                    .fg(self.colors.row_fg)
-                    .bg(self.colors.buffer_bg),
-            )
-            .centered()
-            .block(
-                Block::bordered()
-                    .border_type(BorderType::Double)
-                    .border_style(Style::new().fg(self.colors.footer_border_color)),
-            );
-        frame.render_widget(info_footer, area);
+
+    /// Turns mouse events into gestures on the touch profile, or clicks and
+    /// scrolls with `mouse` on. `true` once the app should quit.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> bool {
+        // the board is the replay's until it stops
+        if !matches!(self.screen, Screen::Game) || self.replay.is_some() {
+            return false;
+        }
+        if self.config.profile == Profile::Touch {
+            if let Some(gesture) = self.gestures.handle(mouse, &self.config.touch) {
+                self.gesture(gesture);
+            }
+        } else if self.config.mouse {
+            self.click(mouse);
+        }
+        self.exit
     }
 
-    fn solve(&mut self) {
-        let Some(solution) = self.data.solve() else {
-            self.bad_color();
+    /// A click selects a cell or runs the footer hint under it, the wheel
+    /// steps the value of the cell under it up or down.
+    fn click(&mut self, mouse: MouseEvent) {
+        let Some(layout) = self.layout else {
             return;
         };
-        self.data = solution;
-    }
-
-    fn clear(&mut self) {
-        self.data = BoardState::default();
-        self.neautral_color();
+        let on_board = matches!(self.mode, Mode::Normal | Mode::Notes | Mode::Center);
+        let cell = grid::cell_at(layout.board, layout.cell, mouse.column, mouse.row)
+            .filter(|_| on_board && self.error.is_none());
+        let step = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some((r, col)) = cell {
+                    self.cursor.select(r, col);
+                    return;
+                }
+                let position = (mouse.column, mouse.row).into();
+                let button = self
+                    .buttons
+                    .iter()
+                    .find(|(rect, _)| rect.contains(position))
+                    .map(|&(_, action)| action);
+                match button {
+                    Some(Action::Quit) => self.exit = self.quit(),
+                    Some(action) => self.apply(action),
+                    None => {}
+                }
+                return;
+            }
+            MouseEventKind::ScrollUp => 1,
+            MouseEventKind::ScrollDown => 9,
+            _ => return,
+        };
+        let Some((r, col)) = cell else {
+            return;
+        };
+        self.cursor.select(r, col);
+        let value = self.data[r][col].map_or(0, |d| d.get());
+        match (value + step) % 10 {
+            0 => self.apply(Action::Erase),
+            d => self.apply(Action::Write(d)),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {}
+    /// Sends a key press to whatever is open on top, the palette or the
+    /// keymap. `true` when it quit the app.
+    fn dispatch_key(&mut self, key: KeyEvent) -> bool {
+        // the error screen covers every other overlay
+        if self.error.is_some() {
+            let layout = self.config.keyboard_layout;
+            if let Some(action) = self.config.keys.lookup_on(Mode::Error, key.code, layout) {
+                self.apply(action);
+            }
+            return false;
+        }
+        // any key stops the solution unfolding, and does nothing else
+        if self.unfolding.take().is_some() {
+            self.toast = Some(Toast::info("stopped filling in the solution"));
+            return false;
+        }
+        match &mut self.screen {
+            Screen::Menu(menu) => {
+                return match menu.handle_key(key) {
+                    Some(event) => self.menu_event(event),
+                    None => false,
+                };
+            }
+            Screen::Settings(form, _) => {
+                match form.handle_key(key) {
+                    FormEvent::Submitted(0) => self.save_settings(),
+                    FormEvent::Submitted(_) | FormEvent::Cancelled => self.close_settings(),
+                    FormEvent::Pending => {}
+                }
+                return false;
+            }
+            Screen::Daily(archive, _) => {
+                if key.code == KeyCode::Esc || key.code == KeyCode::Char('q') {
+                    self.close_archive();
+                } else if let Some(entry) = archive.handle_key(key) {
+                    let (date, puzzle) = (entry.date, entry.puzzle);
+                    self.show_game();
+                    self.difficulty = Difficulty::default();
+                    self.start(puzzle);
+                    self.toast = Some(Toast::info(format!(
+                        "the daily puzzle of {}",
+                        locale::format_date(&self.config.locale, date)
+                    )));
+                }
+                return false;
+            }
+            Screen::Stats(_) => {
+                if matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter) {
+                    self.close_stats();
+                }
+                return false;
+            }
+            Screen::Duel(duel) => {
+                match duel.handle_key(key) {
+                    Some(DuelEvent::Won(seat, time)) => {
+                        self.toast = Some(Toast::info(format!(
+                            "player {} wins in {}",
+                            seat + 1,
+                            locale::format_elapsed(time)
+                        )))
+                    }
+                    Some(DuelEvent::OutOfTime(seat)) => self.out_of_time(seat),
+                    Some(DuelEvent::Leave) => self.show_menu(self.modified),
+                    None => {}
+                }
+                return false;
+            }
+            Screen::Sized(board) => {
+                match board.handle_key(key) {
+                    Some(SizedEvent::Solved(time)) => {
+                        self.toast = Some(Toast::info(format!(
+                            "solved in {}",
+                            locale::format_elapsed(time)
+                        )))
+                    }
+                    Some(SizedEvent::Leave) => self.show_menu(self.modified),
+                    None => {}
+                }
+                return false;
+            }
+            Screen::Game => {}
+        }
+        if let Some(help) = &mut self.help
+            && help.is_searching()
+        {
+            help.handle_search_key(key);
+            return false;
+        }
+        if let Some(library) = &mut self.library
+            && library.is_typing()
+        {
+            let changes = library.handle_typing_key(key);
+            self.store_library_changes(changes);
+            return false;
+        }
+        if self
+            .key_editor
+            .as_ref()
+            .is_some_and(KeyEditor::is_capturing)
+        {
+            self.capture_key(key.code);
+            return false;
+        }
+        if self.collection.is_some() {
+            if key.code == KeyCode::Esc {
+                self.collection = None;
+                self.toast = Some(Toast::info("import cancelled"));
+            }
+            return false;
+        }
+        if let Some(form) = &mut self.import_form {
+            match form.handle_key(key) {
+                FormEvent::Submitted(0) => self.import_collection(),
+                FormEvent::Submitted(_) | FormEvent::Cancelled => self.import_form = None,
+                FormEvent::Pending => {}
+            }
+            return false;
+        }
+        if let Some(form) = &mut self.export {
+            match form.handle_key(key) {
+                FormEvent::Submitted(0) => self.export(),
+                FormEvent::Submitted(_) | FormEvent::Cancelled => self.export = None,
+                FormEvent::Pending => {}
+            }
+            return false;
+        }
+        if let Some(editor) = &mut self.note_editor {
+            if key.code == KeyCode::Esc {
+                self.close_note_editor();
+            } else {
+                editor.handle_key(key);
+            }
+            return false;
+        }
+        if let Some(palette) = &mut self.palette {
+            match palette.handle_key(key) {
+                Some(PaletteEvent::Run(Action::Quit)) => {
+                    self.palette = None;
+                    return self.quit();
+                }
+                Some(PaletteEvent::Run(action)) => {
+                    self.palette = None;
+                    self.apply(action);
+                }
+                Some(PaletteEvent::Command(command)) => {
+                    self.palette = None;
+                    return self.command(&command);
+                }
+                Some(PaletteEvent::Close) => self.palette = None,
+                None => {}
+            }
+            return false;
+        }
+        if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open_palette();
+            return false;
+        }
+        if self.rapid_entry && self.mode == Mode::Normal && key.code == KeyCode::Enter {
+            let (r, _) = self.cursor.cell();
+            self.cursor.select((r + 1) % 9, 0);
+            self.skip_clues();
+            return false;
+        }
+        // shift and a digit pencils the digit in without going to notes
+        // mode, unless the shifted character is bound to something itself
+        let layout = self.config.keyboard_layout;
+        if self.mode == Mode::Normal
+            && let Some(d) = layout.shifted_digit(key)
+            && (matches!(key.code, KeyCode::Char('1'..='9'))
+                || self
+                    .config
+                    .keys
+                    .lookup_on(self.mode, key.code, layout)
+                    .is_none())
+        {
+            self.apply(Action::ToggleNote(d));
+            return false;
+        }
+        let on_board = matches!(self.mode, Mode::Normal | Mode::Notes | Mode::Center);
+        if on_board && key.code == KeyCode::Esc && !self.selection.is_empty() {
+            self.selection.clear();
+            return false;
+        }
+        // shift and an arrow stretches a selection from where the cursor was
+        let stretching = on_board
+            && key.modifiers.contains(KeyModifiers::SHIFT)
+            && matches!(
+                key.code,
+                KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right
+            );
+        let from = self.cursor.cell();
+        let key = self.symbol_key(key.code);
+        let action = self.config.keys.lookup_on(self.mode, key, layout);
+        match action {
+            Some(Action::Quit) => return self.quit(),
+            Some(action) => self.apply(action),
+            None => {}
+        }
+        if action.is_some_and(|action| action.info().movement) {
+            match stretching {
+                true => self.selection.stretch(from, self.cursor.cell()),
+                false => self.selection.settle(),
+            }
+        }
+        false
+    }
+
+    /// Whether keys currently go into a text field rather than to actions.
+    fn is_typing(&self) -> bool {
+        matches!(&self.screen, Screen::Menu(menu) if menu.import.is_some())
+            || self.help.as_ref().is_some_and(SearchableText::is_searching)
+            || self.library.as_ref().is_some_and(Library::is_typing)
+            || self.import_form.is_some()
+            || self.export.is_some()
+            || self.note_editor.is_some()
+            || self.palette.is_some()
+    }
+
+    /// Types pasted text into the open text field, or plays a pasted puzzle
+    /// when nothing has been played yet.
+    pub fn paste(&mut self, text: &str) {
+        if self.error.is_some() {
+            return;
+        }
+        if !matches!(self.screen, Screen::Game) && !self.is_typing() {
+            return;
+        }
+        if self.is_typing() {
+            for c in text.chars() {
+                let code = match c {
+                    '\n' if self.note_editor.is_some() => KeyCode::Enter,
+                    c if c.is_control() => continue,
+                    c => KeyCode::Char(c),
+                };
+                self.dispatch_key(KeyEvent::from(code));
+            }
+            return;
+        }
+        let Ok(board) = formats::parse_share_code(text) else {
+            self.toast = Some(Toast::info("the pasted text isn't a puzzle"));
+            return;
+        };
+        if self.modified {
+            self.toast = Some(Toast::info("clear the board before pasting a puzzle"));
+            return;
+        }
+        self.start(board);
+        self.warning = Imported {
+            board,
+            report: CapabilityReport::default(),
+        }
+        .ambiguity();
+        self.grade = rating::rate(&board);
+        self.toast = Some(Toast::info("playing the pasted puzzle"));
+    }
+
+    /// The board, and its code with the notes, for a crash report.
+    fn board_for_report(&self) -> String {
+        let game = Packed::game(self.puzzle, self.data, self.notes);
+        let code = formats::game_code(&game);
+        format!("{}\n{code}\n", formats::grid(&self.data, &self.symbols))
+    }
+
+    /// What `--print-on-exit` writes to stdout once the terminal is back:
+    /// the board, and optionally its solution, export code and an exchange
+    /// code rated `exchange` stars.
+    pub fn exit_report(&self, solution: bool, code: bool, exchange: Option<u8>) -> String {
+        let mut out = formats::grid(&self.data, &self.symbols);
+        if solution {
+            out.push('\n');
+            match self.puzzle.solve() {
+                Some(solved) => out.push_str(&formats::grid(&solved, &self.symbols)),
+                None => out.push_str("no solution\n"),
+            }
+        }
+        if code {
+            out.push('\n');
+            let game = Packed::game(self.puzzle, self.data, self.notes);
+            out.push_str(&formats::game_code(&game));
+            out.push('\n');
+        }
+        if let Some(rating) = exchange {
+            out.push('\n');
+            let exchange = self
+                .puzzle
+                .solve()
+                .ok_or_else(|| eyre!("the puzzle has no solution"))
+                .and_then(|solution| Exchange::new(self.puzzle, rating, &solution));
+            match exchange {
+                Ok(exchange) => out.push_str(&exchange.code()),
+                Err(err) => out.push_str(&format!("no exchange code: {err}")),
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// On a letter board a typed letter stands for its value's digit key.
+    /// Upper case always enters the letter, lower case only when the key
+    /// isn't bound to something else.
+    fn symbol_key(&self, key: KeyCode) -> KeyCode {
+        let KeyCode::Char(c) = key else {
+            return key;
+        };
+        if self.symbols.is_digits()
+            || !matches!(self.mode, Mode::Normal | Mode::Notes | Mode::Center)
+        {
+            return key;
+        }
+        match self.symbols.value(c) {
+            Some(d) if c.is_uppercase() || self.config.keys.lookup(self.mode, key).is_none() => {
+                KeyCode::Char(char::from(b'0' + d))
+            }
+            _ => key,
+        }
+    }
+
+    /// Swipes and pad buttons press keys; taps and long presses on the board
+    /// select a cell, a long press also switching notes mode.
+    fn gesture(&mut self, gesture: Gesture) {
+        let Some(layout) = self.layout else {
+            return;
+        };
+        let key = match gesture {
+            Gesture::Swipe(key) => Some(key),
+            Gesture::Tap { column, row } | Gesture::LongPress { column, row } => {
+                if let Some((r, col)) = grid::cell_at(layout.board, layout.cell, column, row)
+                    && matches!(self.mode, Mode::Normal | Mode::Notes | Mode::Center)
+                {
+                    self.cursor.select(r, col);
+                    if matches!(gesture, Gesture::LongPress { .. }) {
+                        self.apply(Action::ToggleNotes);
+                    }
+                    return;
+                }
+                Pad::key_at(layout.footer, column, row)
+            }
+        };
+        // the pad has no quit button, but a remapped key could still mean quit
+        if let Some(action) = key.and_then(|key| self.mode.lookup(key))
+            && action != Action::Quit
+        {
+            self.apply(action);
+        }
+    }
+
+    fn reload_config(&mut self) {
+        match Config::load() {
+            Ok(config) => {
+                if config.captures_mouse() != self.config.captures_mouse()
+                    && let Err(err) = touch::set_mouse_capture(config.captures_mouse())
+                {
+                    self.toast = Some(Toast::error(err.to_string()));
+                    return;
+                }
+                self.animations.enabled = !config.reduced_motion;
+                self.glyphs = config.charset.glyphs();
+                // a wordoku keeps its letters
+                if self.symbols.is_digits() {
+                    self.symbols = config.symbols.symbols();
+                }
+                self.symbols = self.symbols.clone().with_glyphs(self.glyphs);
+                self.themes = Theme::all(&config.themes);
+                self.theme = self
+                    .themes
+                    .iter()
+                    .position(|theme| theme.name == self.prefs.theme)
+                    .unwrap_or(0);
+                self.color_index = self.theme;
+                self.theme_picker = None;
+                if self.mode == Mode::Themes {
+                    self.mode = Mode::Normal;
+                }
+                self.config = config;
+                self.toast = Some(Toast::info("config reloaded"));
+            }
+            Err(err) => self.fail(Operation::ReloadConfig, err),
+        }
+    }
+
+    fn apply(&mut self, action: Action) {
+        if !self.rules().allows(action) {
+            self.toast = Some(Toast::info(format!(
+                "no {} in {} games",
+                action.description(),
+                self.game_mode.label()
+            )));
+            return;
+        }
+        match action {
+            Action::Quit => {}
+            Action::MoveUp => self.previous_row(),
+            Action::MoveDown => self.next_row(),
+            Action::MoveLeft => self.previous_column(),
+            Action::MoveRight => self.next_column(),
+            Action::NextEmpty | Action::PreviousEmpty => {
+                let data = &self.data;
+                let forward = action == Action::NextEmpty;
+                if !self.cursor.jump(forward, |r, col| data[r][col].is_none()) {
+                    self.toast = Some(Toast::info("no other empty cells"));
+                }
+            }
+            Action::NextConflict => {
+                let conflicts = self.data.conflicts();
+                if !self.cursor.jump(true, |r, col| conflicts[r][col]) {
+                    self.toast = Some(Toast::info("no other conflicts"));
+                }
+            }
+            Action::JumpBack => {
+                self.cursor.jump_back();
+            }
+            Action::Write(d) => {
+                self.write(d);
+                if self.rapid_entry && d != 0 {
+                    self.advance();
+                }
+            }
+            Action::Erase if !self.selection.is_empty() => self.erase_selection(),
+            Action::Erase => self.write(0),
+            Action::Undo => self.undo(),
+            Action::Guess => self.guess(),
+            Action::Rollback => self.rollback(),
+            Action::ToggleNotes => {
+                self.mode = match self.mode {
+                    Mode::Notes => Mode::Normal,
+                    _ => Mode::Notes,
+                }
+            }
+            Action::ToggleCenterNotes => {
+                self.mode = match self.mode {
+                    Mode::Center => Mode::Normal,
+                    _ => Mode::Center,
+                }
+            }
+            Action::ToggleNote(d) if !self.selection.is_empty() => self.note_selection(d, false),
+            Action::ToggleCenter(d) if !self.selection.is_empty() => self.note_selection(d, true),
+            Action::ToggleNote(d) => self.toggle_note(d, false),
+            Action::ToggleCenter(d) => self.toggle_note(d, true),
+            Action::ToggleSelect => {
+                let (r, col) = self.cursor.cell();
+                self.selection.toggle(r, col);
+            }
+            Action::ClearNotes => {
+                let (r, col) = self.cursor.cell();
+                self.remember(r, col);
+                self.notes.clear_cell(r, col);
+                self.center.clear_cell(r, col);
+                self.touch();
+                self.record(r, col);
+            }
+            Action::Check => self.check(),
+            Action::ToggleAutoCheck => self.auto_check = !self.auto_check,
+            Action::ToggleRapidEntry => {
+                self.rapid_entry = !self.rapid_entry;
+                self.toast = Some(Toast::info(match self.rapid_entry {
+                    true => "rapid entry on: writing moves on, Enter goes down a row",
+                    false => "rapid entry off",
+                }));
+            }
+            Action::ToggleCoach => {
+                self.coach = match self.coach {
+                    Some(_) => None,
+                    None => Some(CoachSummary::default()),
+                };
+                self.rated.clear();
+                self.last_move = None;
+            }
+            Action::CoachReport => self.coach_report(),
+            Action::Solve => {
+                self.prompt = Some(Prompt::Solve);
+                self.mode = Mode::Prompt;
+            }
+            // only the quit prompt can be answered with it, any other
+            // stays open
+            Action::Discard => {
+                if matches!(self.prompt, Some(Prompt::Quit)) {
+                    self.prompt = None;
+                    self.mode = Mode::Normal;
+                    self.exit = self.quit_without_saving();
+                }
+            }
+            Action::CommandLine => {
+                self.open_palette();
+                if let Some(palette) = &mut self.palette {
+                    palette.type_in(":");
+                }
+            }
+            // only the solve prompt can be answered with it, any other
+            // stays open
+            Action::WatchSolve => {
+                if matches!(self.prompt, Some(Prompt::Solve)) {
+                    self.prompt = None;
+                    self.mode = Mode::Normal;
+                    self.watch_solve();
+                }
+            }
+            Action::Reveal => self.reveal(),
+            Action::Preview => self.preview(),
+            Action::Hint => self.hint(),
+            Action::Submit => self.submit(),
+            Action::CycleHighlight if !self.selection.is_empty() => self.color_selection(),
+            Action::CycleHighlight => {
+                let (r, col) = self.cursor.cell();
+                self.remember(r, col);
+                let highlight = &mut self.highlights[r][col];
+                *highlight = (*highlight + 1) % (HIGHLIGHTS.len() as u8 + 1);
+                self.touch();
+            }
+            Action::BugReport if self.refuse_remote("bug reports") => {}
+            Action::BugReport => {
+                crash::note_board(self.board_for_report());
+                self.toast = Some(match Bundle::gather("asked for in the game").write() {
+                    Ok(dir) => Toast::info(format!("report written to {}", dir.display())),
+                    Err(err) => Toast::error(format!("{err:#}")),
+                });
+            }
+            Action::Replay => self.toggle_replay(),
+            Action::ReplayFaster | Action::ReplaySlower => {
+                if let Some(replay) = &mut self.replay {
+                    match action {
+                        Action::ReplayFaster => replay.faster(),
+                        _ => replay.slower(),
+                    }
+                    self.toast = Some(Toast::info(format!("replay at {}x", replay.speed())));
+                }
+            }
+            Action::PauseReplay => {
+                if let Some(replay) = &mut self.replay {
+                    replay.paused = !replay.paused;
+                }
+            }
+            Action::ClearHighlights => {
+                if self.highlights != [[0; 9]; 9] {
+                    let start = self.history.len();
+                    for (r, col) in (0..81).map(|i| (i / 9, i % 9)) {
+                        if self.highlights[r][col] != 0 {
+                            self.remember(r, col);
+                            self.highlights[r][col] = 0;
+                        }
+                    }
+                    self.close_bulk(start);
+                    self.touch();
+                    self.toast = Some(Toast::info("colors cleared"));
+                }
+            }
+            Action::Clear => self.clear(),
+            Action::NewGame => self.new_game(),
+            Action::Carve => self.carve(),
+            Action::ToggleHud => {
+                self.prefs.hud = !self.prefs.hud;
+                self.save_prefs();
+            }
+            Action::ToggleConflicts => {
+                self.prefs.hide_conflicts = !self.prefs.hide_conflicts;
+                self.save_prefs();
+            }
+            Action::NextTheme => self.next_color(),
+            Action::PreviousTheme => self.previous_color(),
+            Action::ToggleHelp => self.toggle_help(),
+            Action::Search => {
+                self.help.iter_mut().for_each(SearchableText::start_search);
+                self.library
+                    .iter_mut()
+                    .for_each(|library| library.start_typing(Typing::Filter));
+            }
+            Action::NextMatch => self.help.iter_mut().for_each(SearchableText::next_match),
+            Action::PreviousMatch => self
+                .help
+                .iter_mut()
+                .for_each(SearchableText::previous_match),
+            Action::ScrollUp => {
+                self.help.iter_mut().for_each(|help| help.scroll_by(-1));
+                self.key_editor
+                    .iter_mut()
+                    .for_each(|editor| editor.move_by(-1));
+                self.library
+                    .iter_mut()
+                    .for_each(|library| library.move_by(-1));
+                self.error.iter_mut().for_each(|error| error.scroll_by(-1));
+                self.preview_theme(-1);
+            }
+            Action::ScrollDown => {
+                self.help.iter_mut().for_each(|help| help.scroll_by(1));
+                self.key_editor
+                    .iter_mut()
+                    .for_each(|editor| editor.move_by(1));
+                self.library
+                    .iter_mut()
+                    .for_each(|library| library.move_by(1));
+                self.error.iter_mut().for_each(|error| error.scroll_by(1));
+                self.preview_theme(1);
+            }
+            Action::EditKeys => self.toggle_key_editor(),
+            Action::EditNote => self.open_note_editor(),
+            Action::Export => self.open_export(),
+            Action::ToggleLibrary => self.toggle_library(),
+            Action::ToggleThemes => self.toggle_theme_picker(),
+            Action::ApplyTheme => self.apply_theme(),
+            Action::MainMenu => self.show_menu(true),
+            Action::ToggleRace => self.toggle_race(),
+            Action::PlaySelected => self.play_selected(),
+            Action::ToggleMark => self.library.iter_mut().for_each(Library::toggle_mark),
+            Action::ToggleArchive => self
+                .library
+                .iter_mut()
+                .for_each(Library::toggle_archive_view),
+            Action::ArchiveMarked => {
+                let changes = self
+                    .library
+                    .as_mut()
+                    .map(Library::archive_marked)
+                    .unwrap_or_default();
+                self.store_library_changes(changes);
+            }
+            Action::PurgeMarked => {
+                let count = self.library.as_ref().map_or(0, Library::purge_count);
+                if count > 0 {
+                    self.prompt = Some(Prompt::Purge(count));
+                    self.mode = Mode::Prompt;
+                }
+            }
+            Action::ImportCollection => self.open_import_form(),
+            Action::TagMarked => self
+                .library
+                .iter_mut()
+                .for_each(|library| library.start_typing(Typing::Tag)),
+            Action::Rebind => self
+                .key_editor
+                .iter_mut()
+                .for_each(|editor| editor.set_capturing(true)),
+            Action::ResetBinding => self.rebind(None),
+            Action::NextPreset => self.pick_preset(self.config.keys.preset().next()),
+            Action::Retry => self.retry(),
+            Action::Continue => self.dismiss_error(),
+            Action::TogglePause => self.toggle_pause(),
+            Action::PlayGenerated => self.play_generated(),
+            Action::Accept => {
+                self.mode = Mode::Normal;
+                match self.prompt.take() {
+                    Some(Prompt::Suggestion(suggestion)) => {
+                        self.difficulty = suggestion.difficulty();
+                        self.answer_suggestion();
+                    }
+                    Some(Prompt::Solve) => self.solve(),
+                    Some(Prompt::Quit) => self.exit = self.save_and_quit(),
+                    Some(Prompt::Import(imported)) => self.play_import(&imported),
+                    Some(Prompt::Purge(_)) => {
+                        self.mode = Mode::Library;
+                        let changes = self
+                            .library
+                            .as_mut()
+                            .map(Library::purge_marked)
+                            .unwrap_or_default();
+                        self.store_library_changes(changes);
+                    }
+                    None => {}
+                }
+            }
+            Action::Decline => {
+                self.mode = Mode::Normal;
+                match self.prompt.take() {
+                    Some(Prompt::Suggestion(_)) => self.answer_suggestion(),
+                    Some(Prompt::Solve | Prompt::Quit) => {}
+                    Some(Prompt::Import(_)) => self.toast = Some(Toast::info("import cancelled")),
+                    Some(Prompt::Purge(_)) => self.mode = Mode::Library,
+                    None => {}
+                }
+            }
+        }
+        let moved = matches!(
+            action,
+            Action::Write(_)
+                | Action::Erase
+                | Action::Undo
+                | Action::Rollback
+                | Action::ToggleNote(_)
+                | Action::ToggleCenter(_)
+                | Action::ClearNotes
+                | Action::Reveal
+                | Action::CycleHighlight
+                | Action::ClearHighlights
+                | Action::Guess
+        );
+        if moved {
+            self.autosave();
+        }
+    }
+
+    /// Time spent on the current game, not counting pauses.
+    fn elapsed(&self) -> Duration {
+        match &self.paused {
+            Some((at, _)) => at.duration_since(self.started),
+            None => self.started.elapsed(),
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        if let Some((at, _)) = self.paused.take() {
+            self.started += at.elapsed();
+            self.mode = Mode::Normal;
+        } else {
+            let cover = self.config.pause.strategy(&self.data, &self.symbols);
+            self.paused = Some((Instant::now(), cover));
+            self.mode = Mode::Paused;
+        }
+    }
+
+    fn answer_suggestion(&mut self) {
+        self.stats.answered_suggestion();
+        self.save_stats();
+    }
+
+    /// Opens the error screen for `operation`, over whatever was open.
+    fn fail(&mut self, operation: Operation, err: color_eyre::Report) {
+        // a second failure still goes back to where the first one started
+        let resume = match self.error.take() {
+            Some(error) => error.resume,
+            None => self.mode,
+        };
+        self.error = Some(ErrorScreen::new(operation, &err, resume));
+        self.mode = Mode::Error;
+    }
+
+    fn retry(&mut self) {
+        let Some(error) = self.error.take() else {
+            return;
+        };
+        self.mode = error.resume;
+        match error.operation {
+            Operation::LoadPrefs => match Prefs::load() {
+                Ok(prefs) => self.use_prefs(prefs),
+                Err(err) => self.fail(Operation::LoadPrefs, err),
+            },
+            Operation::LoadStats => match Stats::load() {
+                Ok(stats) => {
+                    // games finished since keep their records
+                    let played = mem::take(&mut self.stats.games);
+                    self.stats = stats;
+                    self.stats.games.extend(played);
+                    self.stats_unread = false;
+                    self.save_stats();
+                }
+                Err(err) => self.fail(Operation::LoadStats, err),
+            },
+            Operation::SavePrefs => self.save_prefs(),
+            Operation::SaveStats => self.save_stats(),
+            Operation::SaveGame => self.autosave(),
+            Operation::Quit => self.exit = self.save_and_quit(),
+            Operation::ReloadConfig => self.reload_config(),
+            Operation::ImportCollection(path) => {
+                self.collection = Some(CollectionImport::spawn(path));
+            }
+        }
+    }
+
+    /// Carries on without what failed, quitting if it was the save on the
+    /// way out.
+    fn dismiss_error(&mut self) {
+        if let Some(error) = self.error.take() {
+            self.mode = error.resume;
+            self.exit = error.operation == Operation::Quit;
+        }
+    }
+
+    /// Quits, first asking whether to save when the game has changed
+    /// since it was last saved. `true` when it quit.
+    fn quit(&mut self) -> bool {
+        if !self.unsaved() {
+            return self.save_and_quit();
+        }
+        self.prompt = Some(Prompt::Quit);
+        self.mode = Mode::Prompt;
+        false
+    }
+
+    /// Saves everything on the way out. `false` when that failed and the
+    /// error screen is open instead.
+    fn save_and_quit(&mut self) -> bool {
+        match self.store_game().and_then(|()| self.store_prefs()) {
+            Ok(()) => true,
+            Err(err) => {
+                self.fail(Operation::Quit, err);
+                false
+            }
+        }
+    }
+
+    /// Quits leaving the last save of the game as it was.
+    fn quit_without_saving(&mut self) -> bool {
+        match self.store_prefs() {
+            Ok(()) => true,
+            Err(err) => {
+                self.fail(Operation::SavePrefs, err);
+                false
+            }
+        }
+    }
+
+    /// Runs a line typed after `:`, vim style. `true` when it quit.
+    fn command(&mut self, command: &str) -> bool {
+        match command {
+            "w" => {
+                match self.store_game() {
+                    Ok(()) => self.toast = Some(Toast::info("saved")),
+                    Err(err) => self.fail(Operation::SaveGame, err),
+                }
+                false
+            }
+            "q" => self.quit(),
+            "wq" | "x" => self.save_and_quit(),
+            "q!" => self.quit_without_saving(),
+            "" => false,
+            other => {
+                self.toast = Some(Toast::error(format!("unknown command :{other}")));
+                false
+            }
+        }
+    }
+
+    /// Whether the game differs from its last save, so quitting would lose
+    /// something. A finished or untouched game has nothing to save.
+    fn unsaved(&self) -> bool {
+        !self.finished && self.modified && self.dirty
+    }
+
+    /// Marks the game played, and changed since last saved.
+    fn touch(&mut self) {
+        self.modified = true;
+        self.dirty = true;
+    }
+
+    fn save_prefs(&mut self) {
+        if let Err(err) = self.store_prefs() {
+            self.fail(Operation::SavePrefs, err);
+        }
+    }
+
+    fn store_prefs(&self) -> color_eyre::Result<()> {
+        match self.prefs_unread {
+            true => Ok(()),
+            false => self.prefs.save(),
+        }
+    }
+
+    fn autosave(&mut self) {
+        if let Err(err) = self.store_game() {
+            self.fail(Operation::SaveGame, err);
+        }
+    }
+
+    /// Saves the game in progress, or forgets the save once the game is
+    /// over or nothing was played.
+    fn store_game(&mut self) -> Result {
+        if self.finished || !self.modified {
+            self.dirty = false;
+            SavedGame::remove()
+        } else {
+            self.saved_game().save()?;
+            self.dirty = false;
+            Ok(())
+        }
+    }
+
+    fn saved_game(&self) -> SavedGame {
+        SavedGame {
+            puzzle: formats::code(&self.puzzle),
+            board: formats::code(&self.data),
+            notes: self.notes,
+            center: self.center,
+            seconds: self.elapsed().as_secs(),
+            history: self.history.clone(),
+            difficulty: self.difficulty,
+            hints: self.hints_used,
+            mistakes: self.mistakes,
+            revealed: self.revealed,
+            highlights: self.highlights,
+            submissions: self.submissions,
+            curve: self.curve.clone(),
+            variant: self.puzzle.variant(),
+            moves: self.recording.moves.clone(),
+            game_mode: self.game_mode,
+            exchange: self.exchange.as_ref().map(Exchange::code),
+            bulk: self.bulk.clone(),
+            guesses: self
+                .guesses
+                .iter()
+                .map(|guess| SavedGuess {
+                    board: formats::code(&guess.data),
+                    notes: guess.notes,
+                    center: guess.center,
+                    revealed: guess.revealed,
+                    history: guess.history,
+                    cell: guess.cell,
+                })
+                .collect(),
+        }
+    }
+
+    fn save_stats(&mut self) {
+        if self.stats_unread {
+            return;
+        }
+        if let Err(err) = self.stats.save() {
+            self.fail(Operation::SaveStats, err);
+        }
+    }
+
+    /// Records the current game in the stats, once.
+    fn finish_game(&mut self, won: bool) {
+        if self.finished {
+            return;
+        }
+        // ticks stop recording once the game is finished, the last tenth
+        // would be left out
+        self.record_progress();
+        self.finished = true;
+        let seconds = self.elapsed().as_secs();
+        let code = formats::portable_code(&self.puzzle);
+        let event = won && self.stats.event_end(&code, daily::unix_seconds()).is_some();
+        self.stats.record(GameRecord {
+            difficulty: self.difficulty,
+            won,
+            seconds,
+            hints: self.hints_used,
+            coach: self.coach,
+            note: self.puzzle_note().map(String::from),
+            tags: self.stats.tags(&code),
+            curve: self.curve.clone(),
+            event,
+            puzzle: Some(code),
+            archived: false,
+        });
+        self.save_stats();
+        self.autosave();
+        if won {
+            let mut message = format!("solved in {}", locale::format_elapsed(self.elapsed()));
+            if let Some(summary) = self.coach {
+                message.push_str(&format!(": {summary}"));
+            }
+            match self.race.as_ref().map(Bot::finished) {
+                Some(Some(_)) => message.push_str(", after the bot"),
+                Some(None) => message.push_str(", ahead of the bot"),
+                None => {}
+            }
+            if event {
+                message.push_str(", in time for the event");
+            }
+            match &self.exchange {
+                Some(exchange) if exchange.matches(&self.data) => {
+                    message.push_str(", the author's solution")
+                }
+                Some(_) => message.push_str(", but not the author's solution"),
+                None => {}
+            }
+            self.toast = Some(Toast::info(message));
+        }
+    }
+
+    fn toggle_help(&mut self) {
+        if self.help.take().is_some() {
+            self.mode = Mode::Normal;
+        } else {
+            self.help = Some(SearchableText::new(
+                self.config.keys.help_lines(self.glyphs),
+            ));
+            self.help_title = " Help ";
+            self.mode = Mode::Help;
+        }
+    }
+
+    /// The current mode's actions the game mode allows, with their keys.
+    /// Actions taking a number are left out, there's no way to give one.
+    fn open_palette(&mut self) {
+        let rules = self.rules();
+        let entries = ActionRegistry::available(self.mode, &rules)
+            .filter(|info| info.id.is_some())
+            .map(|info| {
+                (
+                    self.config
+                        .keys
+                        .keys_label(self.mode, info.action, self.glyphs),
+                    info.action,
+                )
+            })
+            .collect();
+        self.palette = Some(Palette::new(entries));
+    }
+
+    fn open_export(&mut self) {
+        if self.refuse_remote("exports") {
+            return;
+        }
+        let what = ["board", "puzzle", "solution", "view", "report", "moves"]
+            .map(String::from)
+            .to_vec();
+        let fields = vec![
+            Field::select("export", what, 0),
+            Field::text("file", "").validate(|file| match file {
+                "" => Ok(()),
+                file => match Path::new(file).extension() {
+                    Some(_) => Ok(()),
+                    None => Err("the extension picks the format, e.g. .txt, .sdk or .ans".into()),
+                },
+            }),
+        ];
+        self.export = Some(Form::new(fields, &["Export", "Cancel"]));
+    }
+
+    /// Writes what the export form asks for to its file, or copies it as
+    /// an 81 character code when no file is given.
+    fn export(&mut self) {
+        let Some(form) = self.export.take() else {
+            return;
+        };
+        let file = form.field("file").map_or("", Field::text_value);
+        match form.field("export").and_then(Field::selected) {
+            Some(3) => return self.export_view(file),
+            Some(4) => return self.export_report(file),
+            Some(5) => return self.export_moves(file),
+            _ => {}
+        }
+        let board = match form.field("export").and_then(Field::selected) {
+            Some(1) => self.puzzle,
+            Some(2) => match self.puzzle.solve() {
+                Some(solution) => solution,
+                None => {
+                    self.toast = Some(Toast::error("the puzzle has no solution"));
+                    return;
+                }
+            },
+            _ => self.data,
+        };
+        let result = match file {
+            "" => self
+                .clipboard
+                .copy(formats::portable_code(&board))
+                .map(|()| "copied to the clipboard".to_string()),
+            file => FormatRegistry::default()
+                .export(&board, Path::new(file))
+                .map(|()| format!("wrote {file}")),
+        };
+        self.toast = Some(match result {
+            Ok(message) => Toast::info(message),
+            Err(err) => Toast::error(format!("{err:#}")),
+        });
+    }
+
+    /// Writes how the game went as Markdown to the file, or copies it.
+    fn export_report(&mut self, file: &str) {
+        let report = GameReport {
+            difficulty: self.difficulty,
+            puzzle: self.puzzle,
+            board: self.data,
+            solved: self.data.clue_count() == 81 && self.data.check(),
+            elapsed: self.elapsed(),
+            best: self.stats.summary(Some(self.difficulty)).best,
+            mistakes: self.mistakes,
+            hints: self.hints_used,
+            coach: self.coach,
+            techniques: rating::techniques(&self.puzzle),
+            curve: self.curve.clone(),
+            symbols: self.symbols.clone(),
+        }
+        .markdown();
+        let result = match file {
+            "" => self
+                .clipboard
+                .copy(report)
+                .map(|()| "copied to the clipboard".to_string()),
+            file => fs::write(file, report)
+                .wrap_err_with(|| format!("writing {file}"))
+                .map(|()| format!("wrote {file}")),
+        };
+        self.toast = Some(match result {
+            Ok(message) => Toast::info(message),
+            Err(err) => Toast::error(format!("{err:#}")),
+        });
+    }
+
+    /// Writes every move of the game so far as JSON to the file, or copies
+    /// it.
+    fn export_moves(&mut self, file: &str) {
+        let result = self
+            .recording
+            .json(&self.puzzle)
+            .wrap_err("writing the moves as JSON")
+            .and_then(|json| match file {
+                "" => self
+                    .clipboard
+                    .copy(json)
+                    .map(|()| "copied to the clipboard".to_string()),
+                file => fs::write(file, json)
+                    .wrap_err_with(|| format!("writing {file}"))
+                    .map(|()| format!("wrote {file}")),
+            });
+        self.toast = Some(match result {
+            Ok(message) => Toast::info(message),
+            Err(err) => Toast::error(format!("{err:#}")),
+        });
+    }
+
+    /// Draws the view again offscreen, without the toast, and writes it as
+    /// plain text to a `.txt` file or the clipboard and with its colors as
+    /// ANSI escapes to any other file.
+    fn export_view(&mut self, file: &str) {
+        self.toast = None;
+        let backend = TestBackend::new(self.area.width, self.area.height);
+        let buffer = Terminal::new(backend).and_then(|mut terminal| {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map(|frame| frame.buffer.clone())
+        });
+        let buffer = match buffer {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                self.toast = Some(Toast::error(err.to_string()));
+                return;
+            }
+        };
+        let path = Path::new(file);
+        let result = match file {
+            "" => self
+                .clipboard
+                .copy(snapshot::plain(&buffer))
+                .map(|()| "copied to the clipboard".to_string()),
+            _ => {
+                let text = match path.extension().is_some_and(|ext| ext == "txt") {
+                    true => snapshot::plain(&buffer),
+                    false => snapshot::ansi(&buffer),
+                };
+                fs::write(path, text)
+                    .wrap_err_with(|| format!("writing {file}"))
+                    .map(|()| format!("wrote {file}"))
+            }
+        };
+        self.toast = Some(match result {
+            Ok(message) => Toast::info(message),
+            Err(err) => Toast::error(format!("{err:#}")),
+        });
+    }
+
+    /// The note on the current puzzle, written this game or on an earlier
+    /// play of it.
+    fn puzzle_note(&self) -> Option<&str> {
+        match &self.note {
+            Some(note) => Some(note.as_str()).filter(|note| !note.is_empty()),
+            None => self.stats.note(&formats::portable_code(&self.puzzle)),
+        }
+    }
+
+    fn open_note_editor(&mut self) {
+        self.note_editor = Some(TextArea::new(self.puzzle_note().unwrap_or_default()));
+    }
+
+    /// Keeps what was written, on the past plays of the puzzle right away
+    /// and on this one when it's recorded.
+    fn close_note_editor(&mut self) {
+        let Some(editor) = self.note_editor.take() else {
+            return;
+        };
+        let value = editor.value();
+        let note = Some(value.trim()).filter(|note| !note.is_empty());
+        if note == self.puzzle_note() {
+            return;
+        }
+        self.note = Some(note.map(String::from).unwrap_or_default());
+        self.stats.set_note(
+            &formats::portable_code(&self.puzzle),
+            note.map(String::from),
+        );
+        self.save_stats();
+    }
+
+    fn toggle_library(&mut self) {
+        if self.library.take().is_some() {
+            self.mode = Mode::Normal;
+        } else {
+            if self.stats.expire(daily::unix_seconds()) > 0 {
+                self.save_stats();
+            }
+            self.library = Some(Library::new(&self.stats));
+            self.mode = Mode::Library;
+        }
+    }
+
+    fn open_import_form(&mut self) {
+        if self.refuse_remote("imports") {
+            return;
+        }
+        let fields = vec![
+            Field::text("file", "").validate(|file| match Path::new(file).is_file() {
+                true => Ok(()),
+                false => Err("no such file".into()),
+            }),
+            Field::text("tag", ""),
+        ];
+        self.import_form = Some(Form::new(fields, &["Import", "Cancel"]));
+    }
+
+    fn import_collection(&mut self) {
+        let Some(form) = self.import_form.take() else {
+            return;
+        };
+        let file = form.field("file").map_or("", Field::text_value);
+        self.collection = Some(CollectionImport::spawn(file.into()));
+        let tag = form.field("tag").map_or("", Field::text_value).trim();
+        self.import_tags = Some(tag.to_string())
+            .filter(|tag| !tag.is_empty())
+            .into_iter()
+            .collect();
+    }
+
+    fn poll_collection(&mut self) {
+        let Some(outcome) = self.collection.as_mut().and_then(CollectionImport::poll) else {
+            return;
+        };
+        let Some(import) = self.collection.take() else {
+            return;
+        };
+        let rejected = import.progress.rejected;
+        match outcome {
+            ImportOutcome::Done(puzzles) => {
+                let accepted = puzzles.len();
+                let added = self.stats.import(puzzles, &self.import_tags);
+                self.save_stats();
+                let mut message = format!(
+                    "imported {} puzzles",
+                    locale::format_count(&self.config.locale, added)
+                );
+                if added < accepted {
+                    message.push_str(&format!(", {} already there", accepted - added));
+                }
+                if rejected > 0 {
+                    message.push_str(&format!(", rejected {rejected}"));
+                }
+                self.toast = Some(Toast::info(message));
+                if self.library.is_some() {
+                    self.library = Some(Library::new(&self.stats));
+                }
+            }
+            ImportOutcome::Failed(err) => {
+                self.fail(Operation::ImportCollection(import.path.clone()), eyre!(err))
+            }
+        }
+    }
+
+    /// Stores changes made in the library with the puzzles' game records.
+    fn store_library_changes(&mut self, changes: Vec<Change>) {
+        if changes.is_empty() {
+            return;
+        }
+        for change in changes {
+            match change {
+                Change::Tags(puzzle, tags) => self.stats.set_tags(&puzzle, &tags),
+                Change::Archived(puzzle, archived) => self.stats.set_archived(&puzzle, archived),
+                Change::Purged(puzzle) => self.stats.purge(&puzzle),
+            }
+        }
+        self.save_stats();
+    }
+
+    /// Starts over on the puzzle highlighted in the library.
+    fn play_selected(&mut self) {
+        let Some(entry) = self.library.as_ref().and_then(Library::selected) else {
+            return;
+        };
+        let difficulty = entry.last().map_or(self.difficulty, |game| game.difficulty);
+        let puzzle = entry.puzzle;
+        self.toggle_library();
+        self.difficulty = difficulty;
+        self.start(puzzle);
+    }
+
+    fn toggle_key_editor(&mut self) {
+        if self.key_editor.take().is_some() {
+            self.mode = Mode::Normal;
+        } else {
+            self.key_editor = Some(KeyEditor::new());
+            self.mode = Mode::Keys;
+        }
+    }
+
+    /// The key pressed while the editor waits for one. Esc cancels, a key
+    /// that already does something else in that mode is refused.
+    fn capture_key(&mut self, key: KeyCode) {
+        let Some(editor) = &mut self.key_editor else {
+            return;
+        };
+        editor.set_capturing(false);
+        if key == KeyCode::Esc {
+            return;
+        }
+        let (mode, action) = editor.selected();
+        if let Some(other) = self.config.keys.conflict(mode, action, key) {
+            self.toast = Some(Toast::error(format!(
+                "{} already does \"{}\" in {} mode",
+                keymap::key_label(key, self.glyphs),
+                other.description(),
+                mode.label()
+            )));
+            return;
+        }
+        self.rebind(Some(key));
+    }
+
+    /// Binds the action selected in the editor to `key`, or back to its
+    /// defaults, and writes it to the config file.
+    fn rebind(&mut self, key: Option<KeyCode>) {
+        let Some(editor) = &self.key_editor else {
+            return;
+        };
+        let (mode, action) = editor.selected();
+        let name = action.name().expect("the editor only lists named actions");
+        let value = match key.map(keymap::key_name) {
+            Some(Some(value)) => Some(value),
+            Some(None) => {
+                self.toast = Some(Toast::error("that key can't be written to the config"));
+                return;
+            }
+            None => None,
+        };
+        let saved = self.edit_config(|table| {
+            let keys = table.entry("keys").or_insert_with(config::subtable);
+            let Some(keys) = keys.as_table_mut() else {
+                return;
+            };
+            let modes = keys.entry(mode.name()).or_insert_with(config::subtable);
+            let Some(modes) = modes.as_table_mut() else {
+                return;
+            };
+            match &value {
+                Some(value) => {
+                    modes.insert(name, toml_edit::value(value.clone()));
+                }
+                None => {
+                    modes.remove(name);
+                }
+            }
+        });
+        match saved {
+            Ok(()) => {
+                self.config.keys.set(mode, action, key);
+                self.toast = Some(Toast::info(match key {
+                    Some(key) => format!(
+                        "{} now does \"{}\"",
+                        keymap::key_label(key, self.glyphs),
+                        action.description()
+                    ),
+                    None => format!("\"{}\" is back to its default keys", action.description()),
+                }));
+            }
+            Err(err) => self.toast = Some(Toast::error(format!("{err:#}"))),
+        }
+    }
+
+    /// Switches the keys to `preset` and writes it to the config, keeping
+    /// the player's own bindings on top.
+    fn pick_preset(&mut self, preset: KeyPreset) {
+        let saved = self.edit_config(|table| {
+            let keys = table.entry("keys").or_insert_with(config::subtable);
+            let Some(keys) = keys.as_table_mut() else {
+                return;
+            };
+            match preset {
+                KeyPreset::Standard => keys.remove("preset"),
+                _ => keys.insert("preset", toml_edit::value(preset.label())),
+            };
+        });
+        match saved {
+            Ok(()) => {
+                self.config.keys.set_preset(preset);
+                self.toast = Some(Toast::info(format!("{} keys", preset.label())));
+            }
+            Err(err) => self.toast = Some(Toast::error(format!("{err:#}"))),
+        }
+    }
+
+    /// Toggles a corner mark, or a center mark when `center` is set.
+    fn toggle_note(&mut self, d: u8, center: bool) {
+        let (r, col) = self.cursor.cell();
+        let adding = match center {
+            true => !self.center.has(r, col, d),
+            false => !self.notes.has(r, col, d),
+        };
+        if adding
+            && self
+                .rules()
+                .refuses_note(&self.data, (r, col), d, self.config.note_conflicts)
+        {
+            self.animations.push(Effect::new(
+                r,
+                col,
+                FLASH,
+                Easing::Linear,
+                tailwind::RED.c700,
+            ));
+            return;
+        }
+        self.remember(r, col);
+        match center {
+            true => self.center.toggle(r, col, d),
+            false => self.notes.toggle(r, col, d),
+        }
+        self.touch();
+        self.record(r, col);
+    }
+
+    fn cell_kind(&self, r: usize, col: usize) -> CellKind {
+        self.puzzle.kind(
+            &self.data,
+            r,
+            col,
+            self.notes.get(r, col) | self.center.get(r, col) != 0,
+        )
+    }
+
+    fn write(&mut self, d: u8) {
+        let (r, col) = self.cursor.cell();
+        if self.cell_kind(r, col) == CellKind::Given {
+            self.animations.push(Effect::new(
+                r,
+                col,
+                FLASH,
+                Easing::Linear,
+                tailwind::RED.c700,
+            ));
+            self.toast = Some(Toast::info("clues of the puzzle can't be changed"));
+            return;
+        }
+        let contradiction = self.place(r, col, d);
+        self.settle(d != 0, contradiction);
+    }
+
+    /// Writes `d` at (r, col) the way every edit of a cell goes: rated by
+    /// the coach, counted as a mistake when it loses the solution, kept for
+    /// undo and flashed on a conflict. True when it left the board without
+    /// a solution.
+    fn place(&mut self, r: usize, col: usize, d: u8) -> bool {
+        if let Some(summary) = &mut self.coach
+            && d != 0
+        {
+            let quality = coach::classify(&self.data, r, col, d);
+            summary.record(quality);
+            self.rated.push(RatedMove {
+                row: r,
+                col,
+                digit: d,
+                quality,
+            });
+            self.last_move = Some(quality);
+        }
+        let was_solvable = d != 0 && self.data.solvable();
+        self.hint = None;
+        self.remember(r, col);
+        self.data.set(r as u8, col as u8, d.into());
+        let contradiction = d != 0 && !self.data.solvable();
+        if was_solvable && contradiction {
+            self.mistakes += 1;
+        }
+        self.revealed[r][col] = false;
+        self.touch();
+        self.record(r, col);
+        if self.rules().flashes(&self.data, r, col) {
+            self.animations.push(Effect::new(
+                r,
+                col,
+                FLASH,
+                Easing::Linear,
+                tailwind::RED.c700,
+            ));
+        }
+        for (row, col) in self.data.completed_houses(r, col) {
+            self.animations.push(Effect::new(
+                row,
+                col,
+                PULSE,
+                Easing::Pulse,
+                tailwind::EMERALD.c800,
+            ));
+        }
+        contradiction
+    }
+
+    /// What follows edits once the board is as they left it: finishing a
+    /// full board, the warnings and the check colors. `placed` when a digit
+    /// went in rather than out.
+    fn settle(&mut self, placed: bool, contradiction: bool) {
+        if self.data.clue_count() == 81 {
+            if self.rules().submit {
+                let keys = self
+                    .config
+                    .keys
+                    .keys_label(Mode::Normal, Action::Submit, self.glyphs);
+                self.toast = Some(Toast::info(format!("board full, {keys} submits it")));
+            } else if self.data.check() {
+                self.finish_game(true);
+            }
+        }
+        if self.config.warn_dead_ends
+            && placed
+            && let Some(reason) = self.data.dead_end()
+        {
+            self.toast = Some(Toast::error(format!("dead end: {reason}")));
+        }
+        if contradiction && !self.guesses.is_empty() {
+            let keys = self
+                .config
+                .keys
+                .keys_label(Mode::Normal, Action::Rollback, self.glyphs);
+            self.toast = Some(Toast::error(format!(
+                "contradiction, {keys} goes back to guess {}",
+                self.guesses.len()
+            )));
+        }
+        if self.auto_check {
+            self.check();
+        } else {
+            self.neautral_color();
+        }
+    }
+
+    /// Moves to the next cell in reading order that isn't a clue, from the
+    /// end of a row on to the start of the next.
+    fn advance(&mut self) {
+        let (r, col) = self.cursor.cell();
+        let next = (r * 9 + col + 1) % 81;
+        self.cursor.select(next / 9, next % 9);
+        self.skip_clues();
+    }
+
+    /// Moves on from a clue to the next cell that isn't one, if any is left.
+    fn skip_clues(&mut self) {
+        let (r, col) = self.cursor.cell();
+        let start = r * 9 + col;
+        if let Some(pos) = (start..start + 81)
+            .map(|pos| pos % 81)
+            .find(|&pos| self.cell_kind(pos / 9, pos % 9) != CellKind::Given)
+        {
+            self.cursor.select(pos / 9, pos % 9);
+        }
+    }
+
+    /// Puts the cell as it is on the undo history, before changing it.
+    fn remember(&mut self, r: usize, col: usize) {
+        self.history.push(Undo {
+            row: r,
+            col,
+            digit: self.data[r][col].map_or(0, NonZeroU8::get),
+            revealed: self.revealed[r][col],
+            notes: self.notes.get(r, col),
+            center: self.center.get(r, col),
+            highlight: self.highlights[r][col],
+        });
+        self.dirty = true;
+    }
+
+    /// Takes back the last placement, or the last change to a selection
+    /// as a whole.
+    fn undo(&mut self) {
+        let len = self.history.len();
+        let start = match self.bulk.last() {
+            Some(bulk) if bulk.end == len => bulk.start,
+            _ => len.saturating_sub(1),
+        };
+        if start == len {
+            return;
+        }
+        self.bulk.retain(|bulk| bulk.end <= start);
+        let undone: Vec<_> = self.history.drain(start..).rev().collect();
+        for undo in undone {
+            let (r, col) = (undo.row, undo.col);
+            self.data[r][col] = undo.digit.into();
+            self.revealed[r][col] = undo.revealed;
+            self.notes.set(r, col, undo.notes);
+            self.center.set(r, col, undo.center);
+            self.highlights[r][col] = undo.highlight;
+            self.cursor.select(r, col);
+            self.record(r, col);
+        }
+        self.hint = None;
+        self.touch();
+        // undoing past a guess takes the guess back too
+        self.guesses.retain(|guess| guess.history <= start);
+        self.neautral_color();
+    }
+
+    /// Empties every selected cell that isn't a clue, as one step to undo.
+    fn erase_selection(&mut self) {
+        let start = self.history.len();
+        let cells: Vec<_> = self
+            .selection
+            .cells()
+            .filter(|&(r, col)| {
+                self.cell_kind(r, col) != CellKind::Given && self.data[r][col].is_some()
+            })
+            .collect();
+        for &(r, col) in &cells {
+            self.place(r, col, 0);
+        }
+        self.close_bulk(start);
+        self.settle(false, false);
+    }
+
+    /// Makes the edits since the undo history was `start` long one step to
+    /// undo.
+    fn close_bulk(&mut self, start: usize) {
+        if self.history.len() > start {
+            self.bulk.push(start..self.history.len());
+        }
+    }
+
+    /// Adds `d` to the notes of every empty selected cell, or takes it off
+    /// them all if they all have it.
+    fn note_selection(&mut self, d: u8, center: bool) {
+        let cells: Vec<_> = self
+            .selection
+            .cells()
+            .filter(|&(r, col)| self.data[r][col].is_none())
+            .collect();
+        let has = |app: &Self, r: usize, col: usize| match center {
+            true => app.center.has(r, col, d),
+            false => app.notes.has(r, col, d),
+        };
+        let all = cells.iter().all(|&(r, col)| has(self, r, col));
+        let rules = self.rules();
+        let start = self.history.len();
+        for (r, col) in cells {
+            if has(self, r, col) != all {
+                continue;
+            }
+            if !all && rules.refuses_note(&self.data, (r, col), d, self.config.note_conflicts) {
+                self.animations.push(Effect::new(
+                    r,
+                    col,
+                    FLASH,
+                    Easing::Linear,
+                    tailwind::RED.c700,
+                ));
+                continue;
+            }
+            self.remember(r, col);
+            match center {
+                true => self.center.toggle(r, col, d),
+                false => self.notes.toggle(r, col, d),
+            }
+            self.record(r, col);
+        }
+        self.close_bulk(start);
+        self.touch();
+    }
+
+    /// Gives every selected cell the color after the cursor's.
+    fn color_selection(&mut self) {
+        let (r, col) = self.cursor.cell();
+        let highlight = (self.highlights[r][col] + 1) % (HIGHLIGHTS.len() as u8 + 1);
+        let start = self.history.len();
+        let cells: Vec<_> = self.selection.cells().collect();
+        for (r, col) in cells {
+            self.remember(r, col);
+            self.highlights[r][col] = highlight;
+        }
+        self.close_bulk(start);
+        self.touch();
+    }
+
+    /// Remembers the game as it is, so the placements that follow can be
+    /// rolled back together.
+    fn guess(&mut self) {
+        self.guesses.push(Guess {
+            data: self.data,
+            notes: self.notes,
+            center: self.center,
+            revealed: self.revealed,
+            history: self.history.len(),
+            cell: self.cursor.cell(),
+        });
+        self.dirty = true;
+        self.toast = Some(Toast::info(format!("guess {} marked", self.guesses.len())));
+    }
+
+    /// Goes back to where the latest guess was marked, notes included.
+    fn rollback(&mut self) {
+        let Some(guess) = self.guesses.pop() else {
+            self.toast = Some(Toast::info("no guess to roll back"));
+            return;
+        };
+        let before = (self.data, self.notes, self.center);
+        self.data = guess.data;
+        self.notes = guess.notes;
+        self.center = guess.center;
+        self.record_changes(before);
+        self.revealed = guess.revealed;
+        self.history.truncate(guess.history);
+        self.bulk.retain(|bulk| bulk.end <= guess.history);
+        self.hint = None;
+        self.touch();
+        let (r, col) = guess.cell;
+        self.cursor.select(r, col);
+        self.toast = Some(Toast::info(format!(
+            "rolled back guess {}",
+            self.guesses.len() + 1
+        )));
+        self.neautral_color();
+    }
+
+    /// Draws the board and whatever is open over it.
+    pub fn draw(&mut self, frame: &mut Frame) {
+        self.area = frame.area();
+        self.set_colors();
+        if let Some(needed) = self.too_small(frame.area()) {
+            self.layout = None;
+            self.draw_too_small(frame, needed);
+            return;
+        }
+        match &self.screen {
+            Screen::Game => self.draw_game(frame),
+            Screen::Menu(menu) => self.draw_menu(frame, menu),
+            Screen::Settings(form, _) => self.draw_settings(frame, form),
+            Screen::Duel(duel) => self.draw_duel(frame, duel),
+            Screen::Sized(SizedBoard::Six(game)) => self.draw_sized(frame, game),
+            Screen::Sized(SizedBoard::Sixteen(game)) => self.draw_sized(frame, game),
+            Screen::Daily(archive, _) => self.draw_archive(frame, archive),
+            Screen::Stats(_) => self.draw_stats(frame),
+        }
+
+        if let Some(error) = &self.error {
+            self.render_error(frame, error, frame.area());
+        }
+        if self.toast.as_ref().is_some_and(Toast::expired) {
+            self.toast = None;
+        }
+        if let Some(toast) = &self.toast {
+            frame.render_widget(
+                ToastView {
+                    toast,
+                    glyphs: self.glyphs,
+                },
+                frame.area(),
+            );
+        }
+    }
+
+    /// The terminal size the screen needs at the least, when it's smaller
+    /// than that. Checked on every frame, so resizing back brings the
+    /// screen back.
+    fn too_small(&self, area: Rect) -> Option<(u16, u16)> {
+        let (size, boards) = match &self.screen {
+            Screen::Sized(SizedBoard::Six(_)) => (6, 1),
+            Screen::Sized(SizedBoard::Sixteen(_)) => (16, 1),
+            Screen::Duel(_) => (9, 2),
+            _ => (9, 1),
+        };
+        let (width, height) = layout::min_size(size, boards);
+        (area.width < width || area.height < height).then_some((width, height))
+    }
+
+    fn draw_too_small(&self, frame: &mut Frame, (width, height): (u16, u16)) {
+        let area = frame.area();
+        let style = Style::new()
+            .fg(self.colors.row_fg)
+            .bg(self.colors.buffer_bg);
+        frame.render_widget(Block::new().style(style), area);
+        let text = format!(
+            "terminal too small\nneed {width}x{height}, have {}x{}",
+            area.width, area.height
+        );
+        let [middle] = Layout::vertical([Constraint::Length(2)])
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(
+            Paragraph::new(text).centered().wrap(Wrap { trim: true }),
+            middle,
+        );
+    }
+
+    fn draw_menu(&self, frame: &mut Frame, menu: &MainMenu) {
+        let area = frame.area();
+        let layout = ScreenLayout::new(area, self.config.cell, &self.config.layout);
+        frame.render_widget(Block::new().bg(self.colors.buffer_bg), area);
+        self.render_header(frame, layout.header);
+        let [menu_area] = Layout::horizontal([Constraint::Length(48)])
+            .flex(Flex::Center)
+            .areas(layout.board);
+        let [menu_area] = Layout::vertical([Constraint::Length(menu.height() + 2)])
+            .flex(Flex::Center)
+            .areas(menu_area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Menu ")
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(
+            MenuView {
+                menu,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+                selected_style: Style::new()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(self.colors.selected_cell_style_fg),
+                glyphs: self.glyphs,
+            },
+            block.inner(menu_area),
+        );
+        frame.render_widget(block, menu_area);
+        if let Some(form) = &menu.import {
+            let hint = " .sdk .sdm .txt or f-puzzles ";
+            self.render_form(frame, form, " Import puzzle ", hint, layout.board);
+        }
+    }
+
+    fn draw_archive(&self, frame: &mut Frame, archive: &Archive) {
+        let area = frame.area();
+        let layout = ScreenLayout::new(area, self.config.cell, &self.config.layout);
+        frame.render_widget(Block::new().bg(self.colors.buffer_bg), area);
+        self.render_header(frame, layout.header);
+        let [box_area] = Layout::horizontal([Constraint::Length(48)])
+            .flex(Flex::Center)
+            .areas(layout.board);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Daily puzzles ")
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(
+            ArchiveView {
+                archive,
+                locale: &self.config.locale,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+                selected_style: Style::new()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(self.colors.selected_cell_style_fg),
+                glyphs: self.glyphs,
+            },
+            block.inner(box_area),
+        );
+        frame.render_widget(block, box_area);
+    }
+
+    fn draw_stats(&self, frame: &mut Frame) {
+        let area = frame.area();
+        let layout = ScreenLayout::new(area, self.config.cell, &self.config.layout);
+        frame.render_widget(Block::new().bg(self.colors.buffer_bg), area);
+        self.render_header(frame, layout.header);
+        let [box_area] = Layout::horizontal([Constraint::Length(44)])
+            .flex(Flex::Center)
+            .areas(area);
+        let height = Difficulty::all().len() as u16 + 8;
+        let [box_area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(box_area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Statistics ")
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(
+            StatsView {
+                stats: &self.stats,
+                locale: &self.config.locale,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+                heading_style: Style::new()
+                    .fg(self.colors.header_fg)
+                    .bg(self.colors.header_bg),
+            },
+            block.inner(box_area),
+        );
+        frame.render_widget(block, box_area);
+    }
+
+    fn draw_settings(&self, frame: &mut Frame, form: &Form) {
+        let area = frame.area();
+        let layout = ScreenLayout::new(area, self.config.cell, &self.config.layout);
+        frame.render_widget(Block::new().bg(self.colors.buffer_bg), area);
+        self.render_header(frame, layout.header);
+        let hint = " saved for next time ";
+        self.render_form(frame, form, " Settings ", hint, layout.board);
+    }
+
+    /// Both boards of a hot-seat race side by side, each with its player's
+    /// progress over it and keys under it.
+    fn draw_duel(&self, frame: &mut Frame, duel: &Duel) {
+        let area = frame.area();
+        let layout = ScreenLayout::new(area, self.config.cell, &self.config.layout);
+        let width = area.width.saturating_sub(6) / 2;
+        let cell = layout::fit(self.config.cell, width, area.height.saturating_sub(5), 9)
+            .unwrap_or(COMPACT);
+        frame.render_widget(Block::new().bg(self.colors.buffer_bg), area);
+        self.render_header(frame, layout.header);
+        let [_, middle, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(cell.board_height() + 2),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        let width = cell.board_width();
+        let boards: [Rect; 2] = Layout::horizontal([Constraint::Length(width); 2])
+            .spacing(4)
+            .flex(Flex::Center)
+            .areas(middle);
+        let text = Style::new().fg(self.colors.row_fg);
+        for (i, (seat, area)) in duel.seats.iter().zip(boards).enumerate() {
+            let [title, board, keys] = Layout::vertical([
+                Constraint::Length(1),
+                Constraint::Length(cell.board_height()),
+                Constraint::Length(1),
+            ])
+            .areas(area);
+            let (filled, to_fill) = seat.progress(&duel.puzzle);
+            let mut heading = format!("player {}  {filled}/{to_fill}", i + 1);
+            let mut heading_style = text.bold();
+            if let Some(clock) = &duel.clock {
+                let left = locale::format_elapsed(clock.left(i));
+                heading.push_str(&format!("  {left}"));
+                if clock.running() && clock.turn == i {
+                    heading_style = heading_style
+                        .add_modifier(Modifier::REVERSED)
+                        .fg(self.colors.selected_cell_style_fg);
+                }
+            }
+            if let Some((winner, _)) = duel.winner {
+                heading.push_str(if winner == i { "  won" } else { "  lost" });
+            }
+            frame.render_widget(
+                Line::from(Span::styled(format!(" {heading} "), heading_style)).centered(),
+                title,
+            );
+            let mut cursor = seat.cursor;
+            frame.render_stateful_widget(self.duel_grid(duel, seat, cell), board, &mut cursor);
+            frame.render_widget(
+                Line::styled(duel::KEY_HINTS[i], text.add_modifier(Modifier::DIM)).centered(),
+                keys,
+            );
+        }
+        let status = match duel.clock {
+            Some(_) => format!(" chess clock  {}  Esc leave", duel.difficulty),
+            None => format!(
+                " hot seat  {}  {}  Esc leave",
+                duel.difficulty,
+                locale::format_elapsed(duel.elapsed())
+            ),
+        };
+        frame.render_widget(
+            Line::styled(
+                status,
+                Style::new()
+                    .fg(self.colors.header_fg)
+                    .bg(self.colors.header_bg),
+            ),
+            layout.status,
+        );
+    }
+
+    /// One player's board in a hot-seat race: givens in bold, clashes in red
+    /// and none of the aids of a normal game.
+    fn duel_grid(&self, duel: &Duel, seat: &Seat, cell: CellConfig) -> SudokuGrid<'static> {
+        let renderer = self.config.digits.renderer(
+            &self.config.glyphs,
+            self.glyphs.digits,
+            &self.symbols,
+            cell.width,
+            cell.height,
+        );
+        let conflicts = seat.board.conflicts();
+        let cells = (0..81)
+            .map(|pos| {
+                let (r, col) = (pos / 9, pos % 9);
+                let content = seat.board[r][col];
+                let lines: Vec<Line> = renderer
+                    .render(content, cell.width as usize, cell.height as usize)
+                    .into_iter()
+                    .map(Line::from)
+                    .collect();
+                let bg = if duel.puzzle.variant().in_region(r, col) {
+                    self.colors.header_bg
+                } else if self.config.shading.is_alt(r, col) {
+                    self.colors.alt_row_color
+                } else {
+                    self.colors.normal_row_color
+                };
+                let style = Style::new().bg(bg);
+                let style = if conflicts[r][col] {
+                    style.fg(CONFLICT_COLOR)
+                } else if duel.puzzle[r][col].is_some() {
+                    style.bold()
+                } else {
+                    style
+                };
+                GridCell {
+                    content,
+                    text: Text::from(lines),
+                    style,
+                }
+            })
+            .collect();
+        SudokuGrid {
+            size: 9,
+            cells,
+            cell,
+            style: Style::new()
+                .fg(self.colors.row_fg)
+                .bg(self.colors.buffer_bg),
+            divider_style: Style::new().fg(self.colors.footer_border_color),
+            lines: self.glyphs.line(),
+            row_highlight_style: Style::new(),
+            column_highlight_style: Style::new(),
+            cell_highlight_style: Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(self.colors.selected_cell_style_fg),
+            same_digit_style: None,
+            selection: Vec::new(),
+            selection_style: Style::new(),
+        }
+    }
+
+    /// A 6x6 or 16x16 board with its progress over it and keys under it.
+    fn draw_sized<const N: usize>(&self, frame: &mut Frame, game: &SizedGame<N>) {
+        let area = frame.area();
+        let layout = ScreenLayout::new(area, self.config.cell, &self.config.layout);
+        let (width, height) = (area.width.saturating_sub(2), area.height.saturating_sub(5));
+        let cell = layout::fit(self.config.cell, width, height, N).unwrap_or(COMPACT);
+        frame.render_widget(Block::new().bg(self.colors.buffer_bg), area);
+        self.render_header(frame, layout.header);
+        let [_, middle, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(cell.board_height_of(N) + 2),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        let [middle] = Layout::horizontal([Constraint::Length(cell.board_width_of(N))])
+            .flex(Flex::Center)
+            .areas(middle);
+        let [title, board, keys] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(cell.board_height_of(N)),
+            Constraint::Length(1),
+        ])
+        .areas(middle);
+        let text = Style::new().fg(self.colors.row_fg);
+        let (filled, to_fill) = game.progress();
+        let mut heading = format!("{filled}/{to_fill}");
+        if game.solved.is_some() {
+            heading.push_str("  solved");
+        }
+        frame.render_widget(
+            Line::from(Span::styled(format!(" {heading} "), text.bold())).centered(),
+            title,
+        );
+        let mut cursor = game.cursor;
+        frame.render_stateful_widget(self.sized_grid(game, cell), board, &mut cursor);
+        frame.render_widget(
+            Line::styled(game.key_hint(), text.add_modifier(Modifier::DIM)).centered(),
+            keys,
+        );
+        frame.render_widget(
+            Line::styled(
+                format!(" {N}x{N}  {}", locale::format_elapsed(game.elapsed())),
+                Style::new()
+                    .fg(self.colors.header_fg)
+                    .bg(self.colors.header_bg),
+            ),
+            layout.status,
+        );
+    }
+
+    /// The board of a 6x6 or 16x16 game, drawn like a hot-seat board.
+    fn sized_grid<const N: usize>(
+        &self,
+        game: &SizedGame<N>,
+        cell: CellConfig,
+    ) -> SudokuGrid<'static> {
+        let symbols = Symbols::sized(N as u8).with_glyphs(self.glyphs);
+        let renderer = self.config.digits.renderer(
+            &self.config.glyphs,
+            self.glyphs.digits,
+            &symbols,
+            cell.width,
+            cell.height,
+        );
+        let conflicts = game.board.conflicts();
+        let cells = (0..N * N)
+            .map(|pos| {
+                let (r, col) = (pos / N, pos % N);
+                let content = game.board[r][col];
+                let lines: Vec<Line> = renderer
+                    .render(content, cell.width as usize, cell.height as usize)
+                    .into_iter()
+                    .map(Line::from)
+                    .collect();
+                let bg = match self.config.shading.is_alt(r, col) {
+                    true => self.colors.alt_row_color,
+                    false => self.colors.normal_row_color,
+                };
+                let style = Style::new().bg(bg);
+                let style = if conflicts[r][col] {
+                    style.fg(CONFLICT_COLOR)
+                } else if game.puzzle[r][col].is_some() {
+                    style.bold()
+                } else {
+                    style
+                };
+                GridCell {
+                    content,
+                    text: Text::from(lines),
+                    style,
+                }
+            })
+            .collect();
+        SudokuGrid {
+            size: N,
+            cells,
+            cell,
+            style: Style::new()
+                .fg(self.colors.row_fg)
+                .bg(self.colors.buffer_bg),
+            divider_style: Style::new().fg(self.colors.footer_border_color),
+            lines: self.glyphs.line(),
+            row_highlight_style: Style::new(),
+            column_highlight_style: Style::new(),
+            cell_highlight_style: Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(self.colors.selected_cell_style_fg),
+            same_digit_style: None,
+            selection: Vec::new(),
+            selection_style: Style::new(),
+        }
+    }
+
+    fn draw_game(&mut self, frame: &mut Frame) {
+        let layout = ScreenLayout::new(frame.area(), self.config.cell, &self.config.layout);
+        self.layout = Some(layout);
+
+        self.animations.tick();
+        if self
+            .preview
+            .is_some_and(|(_, shown)| shown.elapsed() >= PREVIEW)
+        {
+            self.preview = None;
+        }
+
+        self.render_header(frame, layout.header);
+        match &self.paused {
+            Some((_, cover)) => {
+                let style = Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg);
+                cover.render(layout.board, frame.buffer_mut(), style);
+            }
+            None => self.render_grid(frame, layout.board, layout.cell),
+        }
+        if self.prefs.hud {
+            self.render_hud(frame, layout.board);
+        }
+        // self.render_scrollbar(frame, rects[0]);
+        match self.config.profile {
+            Profile::Keyboard => self.buttons = self.render_footer(frame, layout.footer),
+            Profile::Touch => frame.render_widget(
+                &Pad {
+                    style: Style::new()
+                        .fg(self.colors.row_fg)
+                        .bg(self.colors.buffer_bg),
+                    border_style: Style::new().fg(self.colors.footer_border_color),
+                    glyphs: self.glyphs,
+                    digit_keys: std::array::from_fn(|i| self.config.keys.digit_label(i as u8 + 1)),
+                },
+                layout.footer,
+            ),
+        }
+        self.render_status(frame, layout.status);
+
+        if let Some(help) = &self.help {
+            self.render_help(frame, help, frame.area());
+        }
+        if let Some(editor) = &self.key_editor {
+            self.render_key_editor(frame, editor, frame.area());
+        }
+        if let Some(library) = &self.library {
+            self.render_library(frame, library, frame.area());
+        }
+        if let Some((selected, _)) = self.theme_picker {
+            self.render_themes(frame, selected, frame.area());
+        }
+        if let Some(form) = &self.export {
+            let hint = " empty file copies to the clipboard ";
+            self.render_form(frame, form, " Export ", hint, layout.board);
+        }
+        if let Some(form) = &self.import_form {
+            let hint = " one puzzle per line ";
+            self.render_form(frame, form, " Import collection ", hint, layout.board);
+        }
+        if let Some(import) = &self.collection {
+            self.render_import(frame, import, layout.board);
+        }
+        if let Some(editor) = &self.note_editor {
+            self.render_note_editor(frame, editor, layout.board);
+        }
+        if let Some(palette) = &self.palette {
+            self.render_palette(frame, palette, frame.area());
+        }
+        if let Some(prompt) = &self.prompt {
+            self.render_prompt(frame, &prompt.message(), layout.board);
+        }
+    }
+
+    fn render_grid(&mut self, frame: &mut Frame, area: Rect, cell: CellConfig) {
+        let selected_row_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_row_style_fg);
+        let selected_col_style = Style::default().fg(self.colors.selected_column_style_fg);
+        let selected_cell_style = Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.colors.selected_cell_style_fg);
+
+        let shading = self.config.shading;
+        let rules = self.rules();
+        let note_style = Style::new().add_modifier(Modifier::DIM);
+        let flag_style = Style::new().fg(tailwind::RED.c400);
+        let ghost_style = Style::new().add_modifier(Modifier::DIM | Modifier::ITALIC);
+        // contest software puts notes in the corners
+        let note_layout = match self.game_mode {
+            GameMode::Competition => NoteLayout::Corner,
+            _ => self.config.note_layout,
+        };
+        let preview = self.preview.map(|(solution, _)| solution);
+        let conflicts = rules.conflicts(&self.data, self.prefs.hide_conflicts);
+        let renderer = self.config.digits.renderer(
+            &self.config.glyphs,
+            self.glyphs.digits,
+            &self.symbols,
+            cell.width,
+            cell.height,
+        );
+        let width = cell.width as usize;
+        let cells = self
+            .data
+            .iter()
+            .enumerate()
+            .flat_map(|(r, data)| {
+                data.iter()
+                    .enumerate()
+                    .map(move |(col, content)| (r, col, content))
+            })
+            .map(|(r, col, content)| {
+                let marks = self.notes.get(r, col);
+                let center = self.center.get(r, col);
+                let ghost = preview.filter(|_| content.is_none());
+                let lines = if let Some(solution) = ghost {
+                    renderer
+                        .render(solution[r][col], width, cell.height as usize)
+                        .into_iter()
+                        .map(|line| Line::styled(line, ghost_style))
+                        .collect()
+                } else if content.is_none() && marks | center != 0 {
+                    let flagged = rules.flagged_notes(
+                        &self.data,
+                        (r, col),
+                        marks | center,
+                        self.config.note_conflicts,
+                    );
+                    render_notes(
+                        Marks {
+                            corner: marks,
+                            center,
+                            flagged,
+                        },
+                        note_layout,
+                        &self.symbols,
+                        width,
+                        cell.height as usize,
+                        note_style,
+                        flag_style,
+                    )
+                } else {
+                    renderer
+                        .render(*content, width, cell.height as usize)
+                        .into_iter()
+                        .map(Line::from)
+                        .collect()
+                };
+                let highlight = self.highlights[r][col];
+                let bg = if highlight > 0 {
+                    HIGHLIGHTS[highlight as usize - 1]
+                } else if self.data.variant().in_region(r, col) {
+                    self.colors.header_bg
+                } else if shading.is_alt(r, col) {
+                    self.colors.alt_row_color
+                } else {
+                    self.colors.normal_row_color
+                };
+                let style = Style::new().bg(self.animations.bg(r, col, bg));
+                let style = if conflicts[r][col] {
+                    style.fg(CONFLICT_COLOR)
+                } else {
+                    match (&self.hint, self.revealed[r][col], self.cell_kind(r, col)) {
+                        (Some(step), ..) if (step.row, step.col) == (r, col) => {
+                            style.fg(HINT_COLOR).bold()
+                        }
+                        (Some(step), ..) if step.cells.contains(&(r, col)) => style.fg(HINT_COLOR),
+                        (_, true, _) => style.fg(HINT_COLOR),
+                        (_, false, CellKind::Given) => style.bold(),
+                        (_, false, _) => style,
+                    }
+                };
+                GridCell {
+                    content: *content,
+                    text: Text::from(lines),
+                    style,
+                }
+            })
+            .collect();
+        let grid = SudokuGrid {
+            size: 9,
+            cells,
+            cell,
+            style: Style::new()
+                .fg(self.colors.row_fg)
+                .bg(self.colors.buffer_bg),
+            divider_style: Style::new().fg(self.colors.footer_border_color),
+            lines: self.glyphs.line(),
+            row_highlight_style: selected_row_style,
+            column_highlight_style: selected_col_style,
+            cell_highlight_style: selected_cell_style,
+            same_digit_style: (!self.prefs.hide_same_digits).then(|| {
+                Style::new()
+                    .fg(self.colors.selected_cell_style_fg)
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            }),
+            selection: self.selection.cells().collect(),
+            selection_style: Style::new().bg(self.colors.header_bg).underlined(),
+        };
+        frame.render_stateful_widget(grid, area, &mut self.cursor);
+    }
+
+    #[allow(dead_code)]
+    fn render_scrollbar(&mut self, frame: &mut Frame, area: Rect) {
+        frame.render_stateful_widget(
+            Scrollbar::default()
+                .orientation(ScrollbarOrientation::VerticalRight)
+                .symbols(self.glyphs.scrollbar())
+                .begin_symbol(None)
+                .end_symbol(None),
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            }),
+            &mut self.scroll_state,
+        );
+    }
+
+    fn render_header(&self, frame: &mut Frame, area: Rect) {
+        let header_style = Style::default()
+            .fg(self.colors.header_fg)
+            .bg(self.colors.header_bg);
+        let header = match (self.warning, self.grade) {
+            (Some(warning), _) => Paragraph::new(format!("Soduku - {warning}"))
+                .style(header_style.bg(tailwind::RED.c700)),
+            (None, Some(Grade::Singles)) => {
+                Paragraph::new("Soduku - solves with singles").style(header_style)
+            }
+            (None, Some(grade)) => {
+                Paragraph::new(format!("Soduku - needs {grade}")).style(header_style)
+            }
+            (None, None) => Paragraph::new("Soduku").style(header_style),
+        };
+        frame.render_widget(header.add_modifier(Modifier::BOLD).centered(), area);
+        if let Some(diagnostics) = &self.diagnostics {
+            frame.render_widget(Line::from(diagnostics.line()).right_aligned(), area);
+        }
+    }
+    fn render_help(&self, frame: &mut Frame, help: &SearchableText, area: Rect) {
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(self.help_title)
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            SearchView {
+                text: help,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+                match_style: Style::new()
+                    .fg(self.colors.header_fg)
+                    .bg(self.colors.header_bg),
+                current_style: Style::new()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(self.colors.selected_cell_style_fg),
+            },
+            block.inner(area),
+        );
+        frame.render_widget(block, area);
+    }
+
+    /// The stats panel, right of the board when there's room for it.
+    fn render_hud(&self, frame: &mut Frame, board: Rect) {
+        const WIDTH: u16 = 36;
+        let x = board.right() + 2;
+        let width = WIDTH.min(frame.area().right().saturating_sub(x));
+        if width < 20 {
+            return;
+        }
+        let note = self.puzzle_note().map(String::from);
+        let height = if note.is_some() { 14 } else { 9 };
+        let area = Rect::new(x, board.y, width, board.height.min(height));
+        let elapsed = self.elapsed();
+        let to_fill = 81 - self.puzzle.clue_count();
+        let filled = self
+            .data
+            .clue_count()
+            .saturating_sub(self.puzzle.clue_count());
+        let mut hud = Hud {
+            filled,
+            to_fill,
+            mistakes: self.mistakes,
+            hints: self.hints_used,
+            elapsed,
+            faster_than: None,
+            note,
+            style: Style::new()
+                .fg(self.colors.row_fg)
+                .bg(self.colors.buffer_bg),
+            border_style: Style::new().fg(self.colors.footer_border_color),
+            glyphs: self.glyphs,
+        };
+        hud.faster_than = hud
+            .projected()
+            .and_then(|projected| self.stats.faster_than(self.difficulty, projected.as_secs()));
+        frame.render_widget(Clear, area);
+        frame.render_widget(hud, area);
+    }
+
+    fn render_library(&self, frame: &mut Frame, library: &Library, area: Rect) {
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(format!(
+                " Library, {} puzzles ",
+                locale::format_count(&self.config.locale, library.puzzles())
+            ))
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            LibraryView {
+                library,
+                symbols: &self.symbols,
+                locale: &self.config.locale,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+                selected_style: Style::new()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(self.colors.selected_cell_style_fg),
+                border_style: Style::new().fg(self.colors.footer_border_color),
+                glyphs: self.glyphs,
+            },
+            block.inner(area),
+        );
+        frame.render_widget(block, area);
+    }
+
+    /// The themes down the right edge, out of the way of the board so it
+    /// shows the one selected.
+    fn render_themes(&self, frame: &mut Frame, selected: usize, area: Rect) {
+        let [area] = Layout::horizontal([Constraint::Length(26)])
+            .flex(Flex::End)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(self.themes.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        let lines: Vec<Line> = self
+            .themes
+            .iter()
+            .enumerate()
+            .map(|(i, theme)| {
+                let swatch = |color: Color| Span::styled("  ", Style::new().bg(color));
+                let mut name = Span::raw(format!(" {:<16}", theme.name));
+                if i == selected {
+                    name = name.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(vec![
+                    swatch(theme.colors.header_bg),
+                    swatch(theme.colors.alt_row_color),
+                    name,
+                ])
+            })
+            .collect();
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Themes ")
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(block).style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            ),
+            area,
+        );
+    }
+
+    fn render_import(&self, frame: &mut Frame, import: &CollectionImport, board: Rect) {
+        let [area] = Layout::horizontal([Constraint::Max(50)])
+            .flex(Flex::Center)
+            .areas(board);
+        let [area] = Layout::vertical([Constraint::Length(6)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Importing ")
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            ImportView {
+                import,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+                gauge_style: Style::new()
+                    .fg(self.colors.selected_cell_style_fg)
+                    .bg(self.colors.buffer_bg),
+            },
+            block.inner(area),
+        );
+        frame.render_widget(block, area);
+    }
+
+    fn render_form(&self, frame: &mut Frame, form: &Form, title: &str, hint: &str, board: Rect) {
+        let [area] = Layout::horizontal([Constraint::Max(50)])
+            .flex(Flex::Center)
+            .areas(board);
+        let [area] = Layout::vertical([Constraint::Length(form.height() + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(title)
+            .title_bottom(Line::from(hint).right_aligned())
+            .border_style(Style::new().fg(self.colors.footer_border_color))
+            .style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            );
+        frame.render_widget(Clear, area);
+        frame.render_widget(form, block.inner(area));
+        frame.render_widget(block, area);
+    }
+
+    fn render_note_editor(&self, frame: &mut Frame, editor: &TextArea, board: Rect) {
+        let [area] = Layout::horizontal([Constraint::Max(50)])
+            .flex(Flex::Center)
+            .areas(board);
+        let [area] = Layout::vertical([Constraint::Max(8)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Puzzle note ")
+            .title_bottom(Line::from(" Esc save ").right_aligned())
+            .border_style(Style::new().fg(self.colors.footer_border_color))
+            .style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            );
+        frame.render_widget(Clear, area);
+        frame.render_widget(editor, block.inner(area));
+        frame.render_widget(block, area);
+    }
+
+    fn render_palette(&self, frame: &mut Frame, palette: &Palette, area: Rect) {
+        let [area] = Layout::horizontal([Constraint::Max(60)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [_, area] = Layout::vertical([Constraint::Length(2), Constraint::Max(14)]).areas(area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Commands ")
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            PaletteView {
+                palette,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+                selected_style: Style::new()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(self.colors.selected_cell_style_fg),
+            },
+            block.inner(area),
+        );
+        frame.render_widget(block, area);
+    }
+
+    fn render_key_editor(&self, frame: &mut Frame, editor: &KeyEditor, area: Rect) {
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Key bindings ")
+            .border_style(Style::new().fg(self.colors.footer_border_color));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            KeyEditorView {
+                editor,
+                keymap: &self.config.keys,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+                selected_style: Style::new()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(self.colors.selected_cell_style_fg),
+                rebound_style: Style::new()
+                    .fg(self.colors.header_fg)
+                    .bg(self.colors.header_bg),
+                glyphs: self.glyphs,
+            },
+            block.inner(area),
+        );
+        frame.render_widget(block, area);
+    }
+
+    fn render_error(&self, frame: &mut Frame, error: &ErrorScreen, area: Rect) {
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(area);
+        let block = self
+            .glyphs
+            .block(BorderType::Double)
+            .title(" Error ")
+            .title_bottom(Line::from(" r retry | Enter continue ").right_aligned())
+            .border_style(Style::new().fg(tailwind::RED.c400));
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            ErrorView {
+                screen: error,
+                style: Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            },
+            block.inner(area),
+        );
+        frame.render_widget(block, area);
+    }
+
+    fn render_prompt(&self, frame: &mut Frame, message: &str, area: Rect) {
+        let [area] = Layout::horizontal([Constraint::Max(40)])
+            .flex(Flex::Center)
+            .areas(area);
+        // border plus every message line, counting the rows it wraps onto
+        let inner = area.width.saturating_sub(2).max(1) as usize;
+        let rows: usize = message
+            .lines()
+            .map(|line| line.width().div_ceil(inner).max(1))
+            .sum();
+        let [area] = Layout::vertical([Constraint::Length(rows.max(3) as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(message)
+                .wrap(Wrap { trim: true })
+                .centered()
+                .style(
+                    Style::new()
+                        .fg(self.colors.row_fg)
+                        .bg(self.colors.buffer_bg),
+                )
+                .block(
+                    self.glyphs
+                        .block(BorderType::Double)
+                        .border_style(Style::new().fg(self.colors.footer_border_color)),
+                ),
+            area,
+        );
+    }
+
+    fn render_status(&self, frame: &mut Frame, area: Rect) {
+        let status = StatusLine {
+            segments: &self.config.statusline.segments,
+            status: Status {
+                mode: self.mode,
+                puzzle: (self.game_mode != GameMode::Casual).then(|| self.game_mode.label()),
+                difficulty: Some(self.difficulty.label()),
+                elapsed: self.elapsed(),
+                hints_used: self.hints_used,
+                hint_budget: None,
+                guesses: self.guesses.len(),
+                modified: self.unsaved(),
+                coach: self.last_move.filter(|_| self.coach.is_some()),
+                race: self.race.as_ref().map(|bot| {
+                    let (filled, to_fill) = bot.progress();
+                    (filled, to_fill, bot.finished())
+                }),
+            },
+            style: Style::new()
+                .fg(self.colors.header_fg)
+                .bg(self.colors.alt_row_color),
+            mode_style: Style::new()
+                .fg(self.colors.header_fg)
+                .bg(self.colors.header_bg),
+            glyphs: self.glyphs,
+        };
+        frame.render_widget(status, area);
+    }
+
+    /// Draws the key hints, returning where each one went.
+    fn render_footer(&self, frame: &mut Frame, area: Rect) -> Vec<(Rect, Action)> {
+        // pack whole hints into lines so a hint never wraps in the middle
+        let width = area.width.saturating_sub(2) as usize;
+        let mut lines: Vec<Line> = Vec::new();
+        let mut current: Vec<Span> = Vec::new();
+        let mut current_width = 0;
+        // line, start column and width of every hint
+        let mut placed: Vec<(usize, usize, usize, Action)> = Vec::new();
+        let rules = self.rules();
+        for (keys, action) in self.config.keys.hints(self.mode, self.glyphs) {
+            if !rules.allows(action) {
+                continue;
+            }
+            let description = match action {
+                Action::ToggleAutoCheck if self.auto_check => "turn auto check off",
+                Action::ToggleAutoCheck => "turn auto check on",
+                Action::ToggleRapidEntry if self.rapid_entry => "turn rapid entry off",
+                Action::ToggleRapidEntry => "turn rapid entry on",
+                Action::ToggleConflicts if self.prefs.hide_conflicts => "show conflicts",
+                Action::ToggleConflicts => "hide conflicts",
+                _ => action.description(),
+            };
+            let hint = format!("({keys}) {description}");
+            let hint_width = hint.width();
+            if !current.is_empty() && current_width + 3 + hint_width > width {
+                lines.push(Line::from(std::mem::take(&mut current)));
+                current_width = 0;
+            }
+            if !current.is_empty() {
+                current.push(Span::raw(" | "));
+                current_width += 3;
+            }
+            placed.push((lines.len(), current_width, hint_width, action));
+            current_width += hint_width;
+            current.push(Span::from(hint));
+        }
+        lines.push(Line::from(current));
+        let mut first = 0;
+        if let Some(step) = &self.hint {
+            let keys = self
+                .config
+                .keys
+                .keys_label(Mode::Normal, Action::Hint, self.glyphs);
+            lines.insert(
+                0,
+                Line::from(format!("hint: {step}, {keys} explains")).fg(HINT_COLOR),
+            );
+            first = 1;
+        }
+        let inner = area.inner(Margin::new(1, 1));
+        let buttons = placed
+            .into_iter()
+            .map(|(line, start, hint_width, action)| {
+                let line_width = lines[line + first].width();
+                // lines are centered the way the paragraph does it
+                let left = inner.width.saturating_sub(line_width as u16) / 2;
+                let rect = Rect {
+                    x: inner.x + left + start as u16,
+                    y: inner.y + (line + first) as u16,
+                    width: hint_width as u16,
+                    height: 1,
+                };
+                (rect.intersection(inner), action)
+            })
+            .collect();
+        let info_footer = Paragraph::new(Text::from(lines))
+            .style(
+                Style::new()
+                    .fg(self.colors.row_fg)
+                    .bg(self.colors.buffer_bg),
+            )
+            .centered()
+            .block(
+                self.glyphs
+                    .block(BorderType::Double)
+                    .border_style(Style::new().fg(self.colors.footer_border_color)),
+            );
+        frame.render_widget(info_footer, area);
+        buttons
+    }
+
+    fn solve(&mut self) {
+        let Some(solution) = self.data.solve() else {
+            self.bad_color();
+            return;
+        };
+        self.finish_game(false);
+        let mut delay = Duration::ZERO;
+        for (r, row) in self.data.iter().enumerate() {
+            for (col, cell) in row.iter().enumerate() {
+                if cell.is_none() {
+                    let effect =
+                        Effect::new(r, col, FILL_IN, Easing::EaseInOut, self.colors.header_bg);
+                    self.animations.push(effect.delayed(delay));
+                    delay += FILL_IN_STAGGER;
+                }
+            }
+        }
+        let before = (self.data, self.notes, self.center);
+        self.data = solution;
+        self.touch();
+        self.record_changes(before);
+    }
+
+    /// Gives up like [`Self::solve`], then writes the solution in one cell
+    /// per step so it can be watched.
+    fn watch_solve(&mut self) {
+        let Some(solution) = self.data.solve() else {
+            self.bad_color();
+            return;
+        };
+        self.finish_game(false);
+        self.unfolding = Some(Unfold::new(&self.data, &solution, UNFOLD_STEP));
+    }
+
+    /// Plays the finished game back from the puzzle, or stops the replay
+    /// and puts the board back as the game ended.
+    fn toggle_replay(&mut self) {
+        if let Some(replay) = self.replay.take() {
+            (self.data, self.notes, self.center) = replay.ending;
+            self.mode = Mode::Normal;
+            return;
+        }
+        if !self.finished {
+            self.toast = Some(Toast::info("a game can be replayed once it's over"));
+            return;
+        }
+        if self.recording.moves.is_empty() {
+            self.toast = Some(Toast::info("this game wasn't recorded"));
+            return;
+        }
+        let ending = (self.data, self.notes, self.center);
+        self.replay = Some(Replay::new(&self.recording, ending));
+        self.data = self.puzzle;
+        self.notes = Notes::default();
+        self.center = Notes::default();
+        self.animations.clear();
+        self.mode = Mode::Replay;
+        self.toast = Some(Toast::info("replaying, + and - change the speed"));
+    }
+
+    /// Makes the moves of the replay that are due, and stops it after the
+    /// last one.
+    fn play_replay(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        for step in replay.step() {
+            let (r, col) = (step.row as usize, step.col as usize);
+            self.data[r][col] = step.digit.into();
+            self.notes.set(r, col, step.notes);
+            self.center.set(r, col, step.center);
+            let effect = Effect::new(r, col, FILL_IN, Easing::EaseInOut, self.colors.header_bg);
+            self.animations.push(effect);
+        }
+        if replay.is_done() {
+            self.toggle_replay();
+            self.toast = Some(Toast::info("end of the replay"));
+        }
+    }
+
+    /// Notes down the cell as the edit left it, for the replay.
+    fn record(&mut self, r: usize, col: usize) {
+        if self.replay.is_some() || !matches!(self.screen, Screen::Game) {
+            return;
+        }
+        let at = self.elapsed();
+        self.recording
+            .record(at, (r, col), &self.data, &self.notes, &self.center);
+    }
+
+    /// Notes down every cell that isn't as it was `before`, for the replay.
+    fn record_changes(&mut self, before: (BoardState, Notes, Notes)) {
+        let (data, notes, center) = before;
+        for (r, col) in (0..81).map(|i| (i / 9, i % 9)) {
+            if *data[r][col] != *self.data[r][col]
+                || notes.get(r, col) != self.notes.get(r, col)
+                || center.get(r, col) != self.center.get(r, col)
+            {
+                self.record(r, col);
+            }
+        }
+    }
+
+    /// Writes the next cell of the unfolding solution once it's due.
+    fn unfold(&mut self) {
+        let Some(unfold) = &mut self.unfolding else {
+            return;
+        };
+        let step = unfold.step();
+        if unfold.is_done() {
+            self.unfolding = None;
+        }
+        if let Some((r, col, value)) = step {
+            self.data[r][col] = value;
+            self.touch();
+            self.record(r, col);
+            let effect = Effect::new(r, col, FILL_IN, Easing::EaseInOut, self.colors.header_bg);
+            self.animations.push(effect);
+        }
+    }
+
+    /// Fills the selected cell from the solution, over a wrong digit too.
+    /// Counts as [`REVEAL_COST`] hints.
+    fn reveal(&mut self) {
+        let (r, col) = self.cursor.cell();
+        // wrong digits on the board would lead its solve astray
+        let Some(solution) = self.puzzle.solve() else {
+            self.bad_color();
+            self.toast = Some(Toast::error("the puzzle has no solution to reveal from"));
+            return;
+        };
+        if self.data[r][col] == solution[r][col] {
+            self.toast = Some(Toast::info("that digit is already right"));
+            return;
+        }
+        self.remember(r, col);
+        self.data[r][col] = solution[r][col];
+        self.revealed[r][col] = true;
+        self.hints_used += REVEAL_COST;
+        self.touch();
+        self.record(r, col);
+        self.animations
+            .push(Effect::new(r, col, FILL_IN, Easing::EaseInOut, HINT_COLOR));
+    }
+
+    /// Explains the next logical step and selects its cell, without
+    /// placing anything.
+    fn hint(&mut self) {
+        // asked again before placing it, the hint opens up into a panel
+        // explaining it
+        if let Some(step) = &self.hint {
+            self.help = Some(SearchableText::new(step.explanation()));
+            self.help_title = " Hint ";
+            self.mode = Mode::Help;
+            return;
+        }
+        if !self.data.check() {
+            self.toast = Some(Toast::error(
+                "the board has a conflict, fix the conflict first",
+            ));
+            return;
+        }
+        match hints::next_step(&self.data) {
+            Some(step) => {
+                self.cursor.select(step.row, step.col);
+                self.hint = Some(step);
+                self.hints_used += 1;
+                self.touch();
+            }
+            None => {
+                self.toast = Some(Toast::info(
+                    "no step found with singles, pointing pairs, box/line reductions, naked pairs, \
+                     x-wings or swordfish",
+                ))
+            }
+        }
+    }
+
+    /// Opens the coach's analysis of the game in the help panel.
+    fn coach_report(&mut self) {
+        let Some(summary) = &self.coach else {
+            let keys = self
+                .config
+                .keys
+                .keys_label(Mode::Normal, Action::ToggleCoach, self.glyphs);
+            self.toast = Some(Toast::info(format!("the coach is off, {keys} turns it on")));
+            return;
+        };
+        self.help = Some(SearchableText::new(coach::analysis(
+            summary,
+            &self.rated,
+            self.glyphs,
+        )));
+        self.help_title = " Coach ";
+        self.mode = Mode::Help;
+    }
+
+    /// What a wrong submission gets told. Outside contests that's how many
+    /// cells are wrong, and they flash.
+    fn wrong_answer(&mut self) -> String {
+        let contest = format!("wrong answer, submission {}", self.submissions);
+        if !self.rules().points_out_mistakes() {
+            return contest;
+        }
+        let Some(solution) = self.puzzle.solve() else {
+            return contest;
+        };
+        let mut wrong = 0;
+        for (r, col) in (0..81).map(|i| (i / 9, i % 9)) {
+            if *self.data[r][col] != *solution[r][col] {
+                wrong += 1;
+                self.animations.push(Effect::new(
+                    r,
+                    col,
+                    FLASH,
+                    Easing::Linear,
+                    tailwind::RED.c700,
+                ));
+            }
+        }
+        match wrong {
+            1 => "not quite, 1 cell is wrong".to_string(),
+            _ => format!("not quite, {wrong} cells are wrong"),
+        }
+    }
+
+    /// Ends the game if the full board is right, with a certificate in
+    /// competitions, otherwise counts a wrong submission.
+    fn submit(&mut self) {
+        if self.finished {
+            return;
+        }
+        if self.data.clue_count() < 81 {
+            self.toast = Some(Toast::info("fill every cell before submitting"));
+            return;
+        }
+        self.submissions += 1;
+        if !self.data.check() {
+            self.mistakes += 1;
+            self.toast = Some(Toast::error(self.wrong_answer()));
+            self.autosave();
+            return;
+        }
+        if self.game_mode != GameMode::Competition {
+            self.finish_game(true);
+            return;
+        }
+        let certificate = certificate::certificate(
+            &formats::portable_code(&self.puzzle),
+            self.elapsed(),
+            self.submissions,
+        );
+        self.finish_game(true);
+        let mut message = format!("solved in {}", locale::format_elapsed(self.elapsed()));
+        if self.clipboard.copy(certificate.clone()).is_ok() {
+            message.push_str(", certificate copied");
+        }
+        self.toast = Some(Toast::info(message));
+        self.certificate = Some(certificate);
+    }
+
+    /// Proof of the last competition solve, to show once the terminal is
+    /// back.
+    pub fn certificate(&self) -> Option<&str> {
+        self.certificate.as_deref()
+    }
+
+    /// Shows the solution for a moment without touching the board. It's
+    /// a bigger help than revealing one cell, so it costs several hints.
+    fn preview(&mut self) {
+        let Some(solution) = self.puzzle.solve() else {
+            self.bad_color();
+            self.toast = Some(Toast::error("the puzzle has no solution to preview"));
+            return;
+        };
+        self.preview = Some((solution, Instant::now()));
+        self.hints_used += PREVIEW_COST;
+        self.touch();
+    }
+
+    fn new_game(&mut self) {
+        self.work(Work::NewGame(self.difficulty, self.variant));
+    }
+
+    /// Has a worker generate a puzzle, or generates it right here before
+    /// the event loop runs. A new request replaces one still going.
+    fn work(&mut self, work: Work) {
+        self.work_id += 1;
+        let Some(workers) = &self.workers else {
+            if let Some(done) = work.run(&mut self.rng, &AtomicBool::new(false)) {
+                self.worked(work, done);
+            }
+            return;
+        };
+        let rng = Rng::new(self.rng.next_u64());
+        self.worker = Some(Worker::spawn(self.work_id, work, rng, workers.clone()));
+        self.toast = Some(Toast::info("generating a puzzle"));
+    }
+
+    fn worked(&mut self, work: Work, done: Done) {
+        self.worker = None;
+        self.toast = None;
+        let puzzle = match done {
+            Done::Puzzle(puzzle) => puzzle,
+            Done::Sized(puzzle) => {
+                self.screen = Screen::Sized(SizedBoard::new(puzzle));
+                return;
+            }
+            Done::Daily(puzzles) => {
+                // unless the player left the menu while they were dug
+                self.screen = match mem::replace(&mut self.screen, Screen::Game) {
+                    Screen::Menu(menu) => Screen::Daily(Archive::new(puzzles, &self.stats), menu),
+                    screen => screen,
+                };
+                return;
+            }
+        };
+        match work {
+            Work::NewGame(..) => self.start(puzzle),
+            Work::Carve(..) => {
+                // typing in the grid was setting up, not playing
+                self.modified = false;
+                self.start(puzzle);
+                self.toast = Some(Toast::info(format!(
+                    "carved a {} puzzle with {} clues",
+                    self.difficulty,
+                    puzzle.clue_count()
+                )));
+            }
+            Work::HotSeat(difficulty, _, turns) => {
+                let clock = turns.then_some(self.config.chess_clock);
+                self.screen = Screen::Duel(Box::new(Duel::new(puzzle, difficulty, clock)));
+            }
+            // come as a sized puzzle and the archive
+            Work::Sized(_) | Work::Daily(_) => {}
+        }
+    }
+
+    /// Turns a filled in grid into a puzzle of the current difficulty with
+    /// that grid as its solution.
+    fn carve(&mut self) {
+        if self.data.clue_count() < 81 || !self.data.check() {
+            self.bad_color();
+            self.toast = Some(Toast::error("fill in a complete, valid grid to carve from"));
+            return;
+        }
+        self.work(Work::Carve(
+            self.data,
+            self.difficulty,
+            self.config.symmetry,
+        ));
+    }
+
+    fn clear(&mut self) {
+        self.start(BoardState::empty(self.variant));
+    }
+
+    /// Starts over on `puzzle`, counting an abandoned game as a loss.
+    fn start(&mut self, puzzle: BoardState) {
+        if self.modified {
+            self.finish_game(false);
+        }
+        self.finished = false;
+        self.exchange = None;
+        self.unfolding = None;
+        self.data = puzzle;
+        self.puzzle = puzzle;
+        self.recording = Recording::default();
+        self.history.clear();
+        self.bulk.clear();
+        self.selection.clear();
+        self.guesses.clear();
+        self.preview = None;
+        self.hint = None;
+        self.note = None;
+        self.warning = None;
+        self.grade = None;
+        self.notes = Notes::default();
+        self.center = Notes::default();
+        self.revealed = [[false; 9]; 9];
+        self.hints_used = 0;
+        self.mistakes = 0;
+        self.highlights = [[0; 9]; 9];
+        self.submissions = 0;
+        self.curve.clear();
+        if let Some(summary) = &mut self.coach {
+            *summary = CoachSummary::default();
+        }
+        self.rated.clear();
+        self.last_move = None;
+        self.animations.clear();
+        self.prompt = self
+            .stats
+            .suggestion(self.difficulty)
+            .map(Prompt::Suggestion);
+        if self.prompt.is_some() {
+            self.mode = Mode::Prompt;
+        }
+        self.paused = None;
+        self.started = Instant::now();
+        self.modified = false;
+        self.neautral_color();
+        if let Some(bot) = &self.race {
+            let rng = Rng::new(self.rng.next_u64());
+            let curve = self.stats.curve(self.difficulty);
+            self.race = Some(Bot::new(puzzle, bot.skill(), curve, rng, Duration::ZERO));
+        }
+    }
+
+    /// Races a bot of `skill` through the game from the start.
+    pub fn with_race(mut self, skill: Skill) -> Self {
+        let rng = Rng::new(self.rng.next_u64());
+        let curve = self.stats.curve(self.difficulty);
+        self.race = Some(Bot::new(self.puzzle, skill, curve, rng, Duration::ZERO));
+        self
+    }
+
+    /// Starts racing a bot of the configured skill from where the clock
+    /// is, or calls the race off.
+    fn toggle_race(&mut self) {
+        if self.race.take().is_some() {
+            self.toast = Some(Toast::info("race called off"));
+            return;
+        }
+        let skill = self.config.bot;
+        let rng = Rng::new(self.rng.next_u64());
+        let curve = self.stats.curve(self.difficulty);
+        self.race = Some(Bot::new(self.puzzle, skill, curve, rng, self.elapsed()));
+        self.toast = Some(Toast::info(format!("racing a {} bot", skill.label())));
+    }
+
+    /// Notes the game time filled cells first reach another tenth of the
+    /// empty ones by, what calibrated bots are paced after.
+    fn record_progress(&mut self) {
+        if self.finished || self.paused.is_some() || !matches!(self.screen, Screen::Game) {
+            return;
+        }
+        let clues = self.puzzle.clue_count();
+        let filled = self.data.clue_count().saturating_sub(clues);
+        let reached = (filled * 10).checked_div(81 - clues).unwrap_or(0);
+        let seconds = self.elapsed().as_secs() as u32;
+        while self.curve.len() < reached {
+            self.curve.push(seconds);
+        }
+    }
+
+    /// Lets the bot make the moves it's due, while the game is on.
+    fn advance_race(&mut self) {
+        if self.finished || self.paused.is_some() || !matches!(self.screen, Screen::Game) {
+            return;
+        }
+        let elapsed = self.elapsed();
+        if let Some(bot) = &mut self.race
+            && bot.advance(elapsed)
+        {
+            self.toast = Some(Toast::info(format!(
+                "the bot got there first, in {}",
+                locale::format_elapsed(elapsed)
+            )));
+        }
+    }
+
+    /// Opens on the main menu instead of the board, with the game that was
+    /// picked up waiting behind Continue.
+    pub fn with_menu(mut self) -> Self {
+        self.show_menu(self.modified);
+        self
+    }
+
+    /// Leaves the board for the main menu, stopping the clock until the
+    /// game is continued.
+    fn show_menu(&mut self, can_continue: bool) {
+        if self.paused.is_none() {
+            let cover = self.config.pause.strategy(&self.data, &self.symbols);
+            self.paused = Some((Instant::now(), cover));
+        }
+        self.screen = Screen::Menu(MainMenu::new(can_continue, self.difficulty));
+    }
+
+    fn show_game(&mut self) {
+        if let Some((at, _)) = self.paused.take() {
+            self.started += at.elapsed();
+        }
+        if self.mode == Mode::Paused {
+            self.mode = Mode::Normal;
+        }
+        self.screen = Screen::Game;
+    }
+
+    /// Returns true when the app should exit.
+    fn menu_event(&mut self, event: MenuEvent) -> bool {
+        match event {
+            MenuEvent::NewGame(difficulty) => {
+                self.difficulty = difficulty;
+                self.show_game();
+                self.new_game();
+            }
+            MenuEvent::Continue => self.show_game(),
+            MenuEvent::Daily => self.work(Work::Daily(Date::today())),
+            MenuEvent::HotSeat(difficulty, turns) => {
+                self.work(Work::HotSeat(difficulty, self.variant, turns))
+            }
+            MenuEvent::Sized(size) => self.work(Work::Sized(size)),
+            MenuEvent::Import(_) if self.refuse_remote("imports") => {}
+            MenuEvent::Import(path) => match FormatRegistry::default().import(&path) {
+                Ok(imported) if imported.report.dropped_rules().next().is_some() => {
+                    self.show_game();
+                    self.prompt = Some(Prompt::Import(Box::new(imported)));
+                    self.mode = Mode::Prompt;
+                }
+                Ok(imported) => {
+                    self.show_game();
+                    self.play_import(&imported);
+                }
+                Err(err) => self.toast = Some(Toast::error(format!("{err:#}"))),
+            },
+            MenuEvent::Stats => {
+                if let Screen::Menu(menu) = std::mem::replace(&mut self.screen, Screen::Game) {
+                    self.screen = Screen::Stats(menu);
+                }
+            }
+            MenuEvent::Settings => self.open_settings(),
+            MenuEvent::Quit => return self.save_and_quit(),
+        }
+        false
+    }
+
+    fn open_settings(&mut self) {
+        let Screen::Menu(menu) = std::mem::replace(&mut self.screen, Screen::Game) else {
+            return;
+        };
+        let themes = self.themes.iter().map(|theme| theme.name.clone()).collect();
+        let fields = vec![
+            Field::select("theme", themes, self.theme),
+            Field::toggle("stats panel", self.prefs.hud),
+            Field::toggle("hide conflicts", self.prefs.hide_conflicts),
+            Field::toggle("hide same digits", self.prefs.hide_same_digits),
+            Field::select(
+                "keys",
+                KeyPreset::ALL
+                    .map(|preset| preset.label().to_string())
+                    .to_vec(),
+                KeyPreset::ALL
+                    .iter()
+                    .position(|preset| *preset == self.config.keys.preset())
+                    .unwrap_or(0),
+            ),
+            Field::select(
+                "shading",
+                Shading::ALL
+                    .map(|shading| shading.label().to_string())
+                    .to_vec(),
+                Shading::ALL
+                    .iter()
+                    .position(|shading| *shading == self.config.shading)
+                    .unwrap_or(0),
+            ),
+            Field::select(
+                "pause cover",
+                self.pause_covers()
+                    .iter()
+                    .map(|cover| cover.label().to_string())
+                    .collect(),
+                self.pause_covers()
+                    .iter()
+                    .position(|cover| *cover == self.config.pause)
+                    .unwrap_or(0),
+            ),
+        ];
+        let form = Form::new(fields, &["Save", "Back"]);
+        self.screen = Screen::Settings(form, menu);
+    }
+
+    fn close_archive(&mut self) {
+        if let Screen::Daily(_, menu) = std::mem::replace(&mut self.screen, Screen::Game) {
+            self.screen = Screen::Menu(menu);
+        }
+    }
+
+    fn close_stats(&mut self) {
+        if let Screen::Stats(menu) = std::mem::replace(&mut self.screen, Screen::Game) {
+            self.screen = Screen::Menu(menu);
+        }
+    }
+
+    fn close_settings(&mut self) {
+        if let Screen::Settings(_, menu) = std::mem::replace(&mut self.screen, Screen::Game) {
+            self.screen = Screen::Menu(menu);
+        }
+    }
+
+    fn save_settings(&mut self) {
+        let Screen::Settings(form, menu) = std::mem::replace(&mut self.screen, Screen::Game) else {
+            return;
+        };
+        if let Some(theme) = form.field("theme").and_then(Field::selected) {
+            self.pick_theme(theme);
+        }
+        self.prefs.hud = form.field("stats panel").is_some_and(Field::is_on);
+        self.prefs.hide_conflicts = form.field("hide conflicts").is_some_and(Field::is_on);
+        self.prefs.hide_same_digits = form.field("hide same digits").is_some_and(Field::is_on);
+        if let Some(preset) = form.field("keys").and_then(Field::selected)
+            && KeyPreset::ALL[preset] != self.config.keys.preset()
+        {
+            self.pick_preset(KeyPreset::ALL[preset]);
+        }
+        if let Some(shading) = form.field("shading").and_then(Field::selected)
+            && Shading::ALL[shading] != self.config.shading
+        {
+            self.pick_shading(Shading::ALL[shading]);
+        }
+        if let Some(cover) = form.field("pause cover").and_then(Field::selected)
+            && let Some(cover) = self.pause_covers().get(cover).cloned()
+            && cover != self.config.pause
+        {
+            self.pick_pause_cover(cover);
+        }
+        self.screen = Screen::Menu(menu);
+        self.save_prefs();
+    }
+
+    /// The pause covers settings offers. Art takes a file, so it's only
+    /// there once the config names one.
+    fn pause_covers(&self) -> Vec<PauseCover> {
+        let mut covers = vec![PauseCover::Blank, PauseCover::Blur];
+        if let PauseCover::Art { .. } = self.config.pause {
+            covers.push(self.config.pause.clone());
+        }
+        covers
+    }
+
+    fn pick_shading(&mut self, shading: Shading) {
+        let saved = self.edit_config(|table| {
+            table.insert("shading", toml_edit::value(shading.label()));
+        });
+        match saved {
+            Ok(()) => self.config.shading = shading,
+            Err(err) => self.toast = Some(Toast::error(format!("{err:#}"))),
+        }
+    }
+
+    /// Writes `cover` to the `[pause]` table, which art never is since its
+    /// file can only be named there.
+    fn pick_pause_cover(&mut self, cover: PauseCover) {
+        let saved = self.edit_config(|table| {
+            let pause = table.entry("pause").or_insert_with(config::subtable);
+            let Some(pause) = pause.as_table_mut() else {
+                return;
+            };
+            pause.insert("cover", toml_edit::value(cover.label()));
+            pause.remove("path");
+        });
+        match saved {
+            Ok(()) => self.config.pause = cover,
+            Err(err) => self.toast = Some(Toast::error(format!("{err:#}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undoing_back_to_the_save_still_counts_as_unsaved() {
+        // saves go to a profile of their own, not the player's
+        config::set_profile(&format!("test-unsaved-{}", std::process::id()));
+        let mut app = App::new();
+        let empty: Vec<_> = (0..81)
+            .map(|i| (i / 9, i % 9))
+            .filter(|&(r, col)| app.data[r][col].is_none())
+            .take(2)
+            .collect();
+        assert!(!app.unsaved());
+
+        app.cursor.select(empty[0].0, empty[0].1);
+        app.write(1);
+        assert!(app.unsaved());
+        app.store_game().unwrap();
+        assert!(!app.unsaved());
+
+        app.write(2);
+        app.undo();
+        assert!(app.unsaved());
+        app.store_game().unwrap();
+
+        for &(r, col) in &empty {
+            app.selection.toggle(r, col);
+        }
+        app.note_selection(3, false);
+        app.undo();
+        assert!(app.unsaved());
+
+        if let Some(dir) = config::state_dir() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
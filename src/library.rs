@@ -0,0 +1,522 @@
+use std::{
+    collections::BTreeSet,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    analyze,
+    charset::GlyphSet,
+    daily::unix_seconds,
+    form::TextInput,
+    formats::{self, grid},
+    locale::{Locale, format_count, format_elapsed, format_moment},
+    soduko::BoardState,
+    stats::{GameRecord, Stats},
+    symbols::Symbols,
+};
+
+/// A puzzle from the game records or an imported collection, with every
+/// attempt at it.
+pub struct LibraryEntry {
+    /// the givens as a [`formats::portable_code`]
+    pub code: String,
+    pub puzzle: BoardState,
+    /// oldest first, none for an imported puzzle not played yet
+    pub attempts: Vec<GameRecord>,
+    pub tags: Vec<String>,
+    /// moved out of the way, kept until purged
+    pub archived: bool,
+    /// when the puzzle's event ends, in seconds since the Unix epoch
+    pub expires: Option<u64>,
+    /// worked out on its own thread when the entry is first selected, the
+    /// solver can take seconds on hard puzzles
+    rating: Option<u32>,
+}
+
+impl LibraryEntry {
+    pub fn last(&self) -> Option<&GameRecord> {
+        self.attempts.last()
+    }
+
+    pub fn best(&self) -> Option<u64> {
+        self.attempts
+            .iter()
+            .filter(|game| game.won)
+            .map(|game| game.seconds)
+            .min()
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.attempts
+            .iter()
+            .rev()
+            .find_map(|game| game.note.as_deref())
+    }
+}
+
+/// What's being typed at the bottom of the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Typing {
+    /// a tag to put on the marked puzzles, or take off if they all have it
+    Tag,
+    /// a tag to show only the puzzles with
+    Filter,
+}
+
+/// A change made in the library that belongs in the game records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Tags(String, Vec<String>),
+    Archived(String, bool),
+    Purged(String),
+}
+
+/// Every puzzle played, most recent first, one selected.
+pub struct Library {
+    entries: Vec<LibraryEntry>,
+    /// indices into `entries` passing the filter
+    visible: Vec<usize>,
+    /// position in `visible`
+    selected: usize,
+    /// indices into `entries`
+    marked: BTreeSet<usize>,
+    filter: Option<String>,
+    /// showing the archived puzzles instead of the others
+    archive: bool,
+    typing: Option<(Typing, TextInput)>,
+    /// the code of the entry being rated, and where its rating comes
+    rating: Option<(String, Receiver<u32>)>,
+}
+
+impl Library {
+    /// Groups the recorded games by puzzle, followed by the imported
+    /// puzzles never played. Puzzles of an event still on go first, ending
+    /// soonest at the top. Games recorded before puzzles were kept are left
+    /// out.
+    pub fn new(stats: &Stats) -> Self {
+        let now = unix_seconds();
+        let mut entries: Vec<LibraryEntry> = Vec::new();
+        for game in stats.games.iter().rev() {
+            let Some(code) = &game.puzzle else {
+                continue;
+            };
+            match entries.iter_mut().find(|entry| entry.code == *code) {
+                Some(entry) => entry.attempts.insert(0, game.clone()),
+                None => entries.push(LibraryEntry {
+                    tags: game.tags.clone(),
+                    archived: game.archived,
+                    expires: stats.event_end(code, now),
+                    code: code.clone(),
+                    puzzle: match formats::parse_share_code(code) {
+                        Ok(puzzle) => puzzle,
+                        Err(_) => continue,
+                    },
+                    attempts: vec![game.clone()],
+                    rating: None,
+                }),
+            }
+        }
+        for imported in &stats.library {
+            if entries.iter().any(|entry| entry.code == imported.puzzle) {
+                continue;
+            }
+            let Ok(puzzle) = formats::parse_share_code(&imported.puzzle) else {
+                continue;
+            };
+            entries.push(LibraryEntry {
+                code: imported.puzzle.clone(),
+                puzzle,
+                attempts: Vec::new(),
+                tags: imported.tags.clone(),
+                archived: imported.archived,
+                expires: stats.event_end(&imported.puzzle, now),
+                rating: None,
+            });
+        }
+        entries.sort_by_key(|entry| entry.expires.unwrap_or(u64::MAX));
+        let mut library = Self {
+            visible: Vec::new(),
+            entries,
+            selected: 0,
+            marked: BTreeSet::new(),
+            filter: None,
+            archive: false,
+            typing: None,
+            rating: None,
+        };
+        library.refilter();
+        library
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.visible.len().saturating_sub(1));
+        self.rate_selected();
+    }
+
+    pub fn selected(&self) -> Option<&LibraryEntry> {
+        self.entries.get(*self.visible.get(self.selected)?)
+    }
+
+    /// Marks the selected puzzle for tagging, or unmarks it.
+    pub fn toggle_mark(&mut self) {
+        if let Some(&i) = self.visible.get(self.selected)
+            && !self.marked.remove(&i)
+        {
+            self.marked.insert(i);
+        }
+        self.move_by(1);
+    }
+
+    pub fn start_typing(&mut self, typing: Typing) {
+        let value = match typing {
+            Typing::Tag => "",
+            Typing::Filter => self.filter.as_deref().unwrap_or_default(),
+        };
+        self.typing = Some((typing, TextInput::new(value)));
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.typing.is_some()
+    }
+
+    /// Keys while typing a tag or filter. Enter applies it and Esc drops it.
+    pub fn handle_typing_key(&mut self, key: KeyEvent) -> Vec<Change> {
+        let Some((typing, input)) = &mut self.typing else {
+            return Vec::new();
+        };
+        match key.code {
+            KeyCode::Esc => self.typing = None,
+            KeyCode::Enter => {
+                let (typing, value) = (*typing, input.value().trim().to_string());
+                self.typing = None;
+                match typing {
+                    Typing::Tag if !value.is_empty() => return self.tag(value),
+                    Typing::Tag => {}
+                    Typing::Filter => {
+                        self.filter = Some(value).filter(|tag| !tag.is_empty());
+                        self.refilter();
+                    }
+                }
+            }
+            _ => {
+                input.handle_key(key);
+            }
+        }
+        Vec::new()
+    }
+
+    /// How many puzzles there are, archived or not.
+    pub fn puzzles(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The marked puzzles, or the selected one when none are marked.
+    fn targets(&self) -> Vec<usize> {
+        match self.marked.is_empty() {
+            true => self
+                .visible
+                .get(self.selected)
+                .copied()
+                .into_iter()
+                .collect(),
+            false => self.marked.iter().copied().collect(),
+        }
+    }
+
+    fn tag(&mut self, tag: String) -> Vec<Change> {
+        let targets = self.targets();
+        let remove = targets.iter().all(|&i| self.entries[i].tags.contains(&tag));
+        let mut changed = Vec::new();
+        for i in targets {
+            let entry = &mut self.entries[i];
+            if remove {
+                entry.tags.retain(|other| *other != tag);
+            } else if !entry.tags.contains(&tag) {
+                entry.tags.push(tag.clone());
+            } else {
+                continue;
+            }
+            changed.push(Change::Tags(entry.code.clone(), entry.tags.clone()));
+        }
+        self.marked.clear();
+        self.refilter();
+        changed
+    }
+
+    pub fn toggle_archive_view(&mut self) {
+        self.archive = !self.archive;
+        self.marked.clear();
+        self.selected = 0;
+        self.refilter();
+    }
+
+    /// Moves the marked puzzles to the archive, or back out of it when
+    /// looking at the archive.
+    pub fn archive_marked(&mut self) -> Vec<Change> {
+        let archived = !self.archive;
+        let changed = self
+            .targets()
+            .into_iter()
+            .map(|i| {
+                self.entries[i].archived = archived;
+                Change::Archived(self.entries[i].code.clone(), archived)
+            })
+            .collect();
+        self.marked.clear();
+        self.refilter();
+        changed
+    }
+
+    /// How many puzzles [`Library::purge_marked`] would forget.
+    pub fn purge_count(&self) -> usize {
+        match self.archive {
+            true => self.targets().len(),
+            false => 0,
+        }
+    }
+
+    /// Forgets the marked puzzles and their games for good. Only puzzles
+    /// already in the archive can go.
+    pub fn purge_marked(&mut self) -> Vec<Change> {
+        if !self.archive {
+            return Vec::new();
+        }
+        let mut targets = self.targets();
+        targets.sort_unstable();
+        let changed = targets
+            .iter()
+            .rev()
+            .map(|&i| Change::Purged(self.entries.remove(i).code))
+            .collect();
+        self.marked.clear();
+        self.refilter();
+        changed
+    }
+
+    fn refilter(&mut self) {
+        self.visible = (0..self.entries.len())
+            .filter(|&i| {
+                let entry = &self.entries[i];
+                entry.archived == self.archive
+                    && self
+                        .filter
+                        .as_ref()
+                        .is_none_or(|tag| entry.tags.contains(tag))
+            })
+            .collect();
+        self.selected = self.selected.min(self.visible.len().saturating_sub(1));
+        self.rate_selected();
+    }
+
+    /// Starts rating the selected entry on its own thread, unless another
+    /// one is still being rated.
+    fn rate_selected(&mut self) {
+        if self.rating.is_some() {
+            return;
+        }
+        if let Some(&i) = self.visible.get(self.selected)
+            && let entry = &self.entries[i]
+            && entry.rating.is_none()
+        {
+            let (tx, rx) = mpsc::channel();
+            let puzzle = entry.puzzle;
+            thread::spawn(move || {
+                let mut nodes = 0;
+                puzzle.solve_counting(&mut nodes);
+                let _ = tx.send(analyze::rating(nodes));
+            });
+            self.rating = Some((entry.code.clone(), rx));
+        }
+    }
+
+    /// Takes in the rating being worked out once it's done, and goes on to
+    /// the entry selected by then.
+    pub fn poll_rating(&mut self) {
+        let Some((code, rx)) = &self.rating else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(rating) => {
+                if let Some(entry) = self.entries.iter_mut().find(|entry| entry.code == *code) {
+                    entry.rating = Some(rating);
+                }
+            }
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {}
+        }
+        self.rating = None;
+        self.rate_selected();
+    }
+}
+
+fn attempt_line(game: &GameRecord) -> Line<'static> {
+    let outcome = if game.won { "solved " } else { "gave up" };
+    let elapsed = format_elapsed(Duration::from_secs(game.seconds));
+    let mut text = format!(" {outcome} {elapsed:>8}");
+    if game.hints > 0 {
+        text.push_str(&format!("  {} hints", game.hints));
+    }
+    if game.event {
+        text.push_str("  event");
+    }
+    Line::from(text)
+}
+
+/// Time left as its two largest units, `2d 5h`, `5h 12m` or `12m`.
+fn format_left(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86_400, seconds / 3600 % 24, seconds / 60 % 60);
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h"),
+    }
+}
+
+pub struct LibraryView<'a> {
+    pub library: &'a Library,
+    pub symbols: &'a Symbols,
+    pub locale: &'a Locale,
+    pub style: Style,
+    pub selected_style: Style,
+    pub border_style: Style,
+    pub glyphs: GlyphSet,
+}
+
+impl LibraryView<'_> {
+    fn render_list(&self, area: Rect, buf: &mut Buffer) {
+        let library = self.library;
+        let height = area.height.saturating_sub(1) as usize;
+        let scroll = library.selected.saturating_sub(height.saturating_sub(1));
+        let mut lines: Vec<Line> = Vec::new();
+        let now = unix_seconds();
+        for (n, &i) in library.visible.iter().enumerate().skip(scroll).take(height) {
+            let entry = &library.entries[i];
+            let best = entry.best().map_or("unsolved".to_string(), |seconds| {
+                format_elapsed(Duration::from_secs(seconds))
+            });
+            let mark = if library.marked.contains(&i) {
+                '*'
+            } else {
+                ' '
+            };
+            let mut text = format!(
+                "{mark}{:<7} {:>2} clues {best:>8}",
+                entry.last().map_or("new", |game| game.difficulty.label()),
+                entry.puzzle.clue_count()
+            );
+            // the event can end while the library is open
+            if let Some(end) = entry.expires.filter(|&end| now < end) {
+                text.push_str(&format!("  ends in {}", format_left(end - now)));
+            }
+            if !entry.tags.is_empty() {
+                text.push_str(&format!("  #{}", entry.tags.join(" #")));
+            }
+            let style = match n == library.selected {
+                true => self.selected_style,
+                false => self.style,
+            };
+            lines.push(Line::styled(text, style));
+        }
+        if library.entries.is_empty() {
+            lines.push(Line::from(" no games recorded yet"));
+        } else if library.visible.is_empty() && library.filter.is_some() {
+            lines.push(Line::from(" no puzzles with that tag"));
+        } else if library.visible.is_empty() && library.archive {
+            lines.push(Line::from(" the archive is empty"));
+        }
+        while lines.len() < height {
+            lines.push(Line::default());
+        }
+        let bottom = match (&library.typing, &library.filter) {
+            (Some((Typing::Tag, input)), _) => Line::from(format!(" tag: {}", input.value())),
+            (Some((Typing::Filter, input)), _) => Line::from(format!(" filter: {}", input.value())),
+            (None, Some(tag)) => Line::from(format!(" showing #{tag} | / change filter")),
+            (None, None) if library.archive => {
+                Line::from(" d restore | X purge | a back to the library")
+            }
+            (None, None) => {
+                Line::from(" Enter play | Space mark | t tag | / filter | d archive | i import")
+            }
+        };
+        lines.push(bottom);
+        Paragraph::new(lines).style(self.style).render(area, buf);
+    }
+
+    fn render_detail(&self, entry: &LibraryEntry, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line> = grid(&entry.puzzle, self.symbols)
+            .lines()
+            .map(|line| Line::from(format!(" {line}")))
+            .collect();
+        lines.push(Line::default());
+        if let Some(last) = entry.last() {
+            lines.push(Line::from(format!(" difficulty {}", last.difficulty)));
+        }
+        lines.push(Line::from(format!(
+            " clues      {}",
+            entry.puzzle.clue_count()
+        )));
+        match entry.rating {
+            Some(rating) => lines.push(Line::from(format!(" rating     {rating}"))),
+            None => lines.push(Line::from(" rating…")),
+        }
+        let now = unix_seconds();
+        if let Some(end) = entry.expires.filter(|&end| now < end) {
+            lines.push(Line::from(format!(
+                " event ends in {}, {}",
+                format_left(end - now),
+                format_moment(self.locale, end)
+            )));
+        }
+        if !entry.tags.is_empty() {
+            lines.push(Line::from(format!(" tags       {}", entry.tags.join(", "))));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from(format!(
+            " attempts   {}",
+            format_count(self.locale, entry.attempts.len())
+        )));
+        lines.extend(entry.attempts.iter().rev().map(attempt_line));
+        if let Some(note) = entry.note() {
+            lines.push(Line::default());
+            lines.extend(
+                note.lines()
+                    .map(|line| Line::from(format!(" {line}")).italic()),
+            );
+        }
+        let block = Block::new()
+            .borders(Borders::LEFT)
+            .border_set(self.glyphs.border(BorderType::Plain))
+            .border_style(self.border_style);
+        Paragraph::new(lines)
+            .style(self.style)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+impl Widget for LibraryView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [list, detail] =
+            Layout::horizontal([Constraint::Min(28), Constraint::Length(32)]).areas(area);
+        self.render_list(list, buf);
+        match self.library.selected() {
+            Some(entry) => self.render_detail(entry, detail, buf),
+            None => Paragraph::new("").style(self.style).render(detail, buf),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::daily::Date;
+
+/// How times of day, dates and large counts are written, from the
+/// `[locale]` config table:
+///
+/// ```toml
+/// [locale]
+/// clock = "12h"
+/// dates = "dmy"
+/// thousands = ","
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Locale {
+    pub clock: Clock,
+    pub dates: DateOrder,
+    /// put between every three digits of large counts, nothing by default
+    pub thousands: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum Clock {
+    #[default]
+    #[serde(rename = "24h")]
+    TwentyFour,
+    /// with am and pm
+    #[serde(rename = "12h")]
+    Twelve,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateOrder {
+    /// `2026-10-16`
+    #[default]
+    Iso,
+    /// `16.10.2026`
+    Dmy,
+    /// `10/16/2026`
+    Mdy,
+}
+
+/// Time played, `mm:ss` or `h:mm:ss` past an hour, the same everywhere.
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m:02}:{s:02}")
+    }
+}
+
+/// `n` with the thousands separator in between.
+pub fn format_count(locale: &Locale, n: usize) -> String {
+    let digits = n.to_string();
+    if locale.thousands.is_empty() {
+        return digits;
+    }
+    let mut out = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(&locale.thousands);
+        }
+        out.push(digit);
+    }
+    out
+}
+
+pub fn format_date(locale: &Locale, date: Date) -> String {
+    let (year, month, day) = date.civil();
+    match locale.dates {
+        DateOrder::Iso => format!("{year:04}-{month:02}-{day:02}"),
+        DateOrder::Dmy => format!("{day:02}.{month:02}.{year:04}"),
+        DateOrder::Mdy => format!("{month:02}/{day:02}/{year:04}"),
+    }
+}
+
+/// A moment as its date and time of day in the local time zone, `unix`
+/// seconds since the Unix epoch. In UTC, and said so, where the local
+/// time zone can't be told.
+pub fn format_moment(locale: &Locale, unix: u64) -> String {
+    let offset = utc_offset(unix);
+    let local = unix.saturating_add_signed(offset.unwrap_or(0));
+    let (hour, minute) = (local / 3600 % 24, local / 60 % 60);
+    let time = match locale.clock {
+        Clock::TwentyFour => format!("{hour:02}:{minute:02}"),
+        Clock::Twelve => {
+            let half = if hour < 12 { "am" } else { "pm" };
+            format!("{}:{minute:02} {half}", (hour + 11) % 12 + 1)
+        }
+    };
+    let zone = if offset.is_some() { "" } else { " UTC" };
+    format!("{} {time}{zone}", format_date(locale, Date::of(local)))
+}
+
+/// How many seconds the local time zone is ahead of UTC at `unix`.
+#[cfg(unix)]
+#[allow(
+    clippy::useless_conversion,
+    reason = "`c_long` is `i32` on 32-bit targets"
+)]
+fn utc_offset(unix: u64) -> Option<i64> {
+    let time = libc::time_t::try_from(unix).ok()?;
+    // SAFETY: `tm` is plain data, all zeroes is a valid value for it, and
+    // `localtime_r` only writes to it
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let filled = unsafe { libc::localtime_r(&time, &mut tm) };
+    (!filled.is_null()).then(|| i64::from(tm.tm_gmtoff))
+}
+
+#[cfg(not(unix))]
+fn utc_offset(_unix: u64) -> Option<i64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_each_locale_its_own_way() {
+        let iso = Locale::default();
+        let dmy = Locale {
+            clock: Clock::Twelve,
+            dates: DateOrder::Dmy,
+            thousands: ",".to_string(),
+        };
+        let date: Date = "2026-10-16".parse().unwrap();
+        assert_eq!(format_count(&iso, 1_234_567), "1234567");
+        assert_eq!(format_count(&dmy, 1_234_567), "1,234,567");
+        assert_eq!(format_count(&dmy, 123), "123");
+        assert_eq!(format_date(&iso, date), "2026-10-16");
+        assert_eq!(format_date(&dmy, date), "16.10.2026");
+
+        // 13:05 that day in the time zone the tests run in
+        let utc = date.end() - 86_400 + 13 * 3600 + 5 * 60;
+        let offset = utc_offset(utc);
+        let local = utc.saturating_add_signed(-offset.unwrap_or(0));
+        let zone = if offset.is_some() { "" } else { " UTC" };
+        assert_eq!(
+            format_moment(&iso, local),
+            format!("2026-10-16 13:05{zone}")
+        );
+        assert_eq!(
+            format_moment(&dmy, local),
+            format!("16.10.2026 1:05 pm{zone}")
+        );
+    }
+}
@@ -1,9 +1,289 @@
-use rudoku::{App, Result};
+use std::{fs, path::PathBuf};
+
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, builder::PossibleValuesParser};
+use color_eyre::eyre::WrapErr;
+use rudoku::{
+    App, BoardSize, Config, Difficulty, FormatRegistry, GameMode, GenerationJob, Prefs, Result,
+    Rng, SavedGame, Skill, Stats, Technique, Variant,
+    analyze::ReportFormat,
+    formats,
+    generator::{self, Mask},
+    import::{CapabilityReport, Imported},
+    walkthrough::WalkthroughFormat,
+    wordoku::{self, WordPlace},
+};
+
+#[derive(Parser)]
+#[command(version, about = "Sudoku in the terminal")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Play a letter puzzle spelling this 9 letter word
+    #[arg(long, value_name = "WORD")]
+    wordoku: Option<String>,
+    /// Play the board from a shared code, picking up where it was left if
+    /// the code has the digits and notes written so far
+    #[arg(long, value_name = "CODE", conflicts_with = "wordoku")]
+    code: Option<String>,
+    /// Play a puzzle from a file: f-puzzles (.json, .fpuzzles), .sdk, .sdm
+    /// or .txt
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["code", "wordoku"])]
+    import: Option<PathBuf>,
+    /// Play an 81 character puzzle, `.` or `0` for blanks, or the puzzle in
+    /// a file as with --import
+    #[arg(long, value_name = "FILE|STRING", conflicts_with_all = ["code", "wordoku", "import"])]
+    puzzle: Option<String>,
+    /// Play a puzzle from an exchange code and check the finished board
+    /// against the author's solution
+    #[arg(long, value_name = "CODE", conflicts_with_all = ["code", "wordoku", "import", "puzzle"])]
+    exchange: Option<String>,
+    /// Play a new puzzle whose clues draw the shape in this file: nine
+    /// lines, `.` for a blank and any other character for a clue
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["code", "wordoku", "import", "puzzle", "exchange"])]
+    mask: Option<PathBuf>,
+    /// How many grids to try before giving up on the mask
+    #[arg(long, value_name = "N", default_value_t = 2000, requires = "mask")]
+    mask_attempts: u32,
+    /// Play a new puzzle of this difficulty, the config's own tiers
+    /// included
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["code", "wordoku", "import", "puzzle", "exchange", "mask"])]
+    difficulty: Option<String>,
+    /// Play new puzzles with the extra houses of this variant
+    #[arg(long, value_enum, value_name = "VARIANT", conflicts_with_all = ["code", "wordoku", "import", "puzzle", "exchange", "mask", "needs", "up_to"])]
+    variant: Option<Variant>,
+    /// Play a new puzzle on a board of this size instead of 9x9
+    #[arg(long, value_enum, value_name = "SIZE", conflicts_with_all = ["code", "wordoku", "import", "puzzle", "exchange", "mask", "needs", "up_to", "difficulty", "variant"])]
+    size: Option<BoardSize>,
+    /// Play a new puzzle that takes this technique somewhere
+    #[arg(long, value_enum, value_name = "TECHNIQUE", conflicts_with_all = ["code", "wordoku", "import", "puzzle", "exchange", "mask", "difficulty"])]
+    needs: Option<Technique>,
+    /// Play a new puzzle that takes nothing harder than this technique,
+    /// by default the one it needs
+    #[arg(long, value_enum, value_name = "TECHNIQUE", conflicts_with_all = ["code", "wordoku", "import", "puzzle", "exchange", "mask", "difficulty"])]
+    up_to: Option<Technique>,
+    /// How many puzzles to rate before giving up on the techniques
+    #[arg(long, value_name = "N", default_value_t = 2000)]
+    technique_attempts: u32,
+    /// Race a bot of this skill through the puzzle
+    #[arg(long, value_enum, value_name = "SKILL")]
+    race: Option<Skill>,
+    /// Which assists are allowed
+    #[arg(long, value_enum, default_value_t)]
+    game_mode: GameMode,
+    /// Where the wordoku's word is hidden
+    #[arg(long, value_enum, default_value_t, requires = "wordoku")]
+    word_place: WordPlace,
+    /// Start searching for a puzzle with at least this rating in the
+    /// background. The search carries on across restarts until it succeeds.
+    #[arg(long, value_name = "RATING")]
+    generate_hard: Option<u32>,
+    /// Print the final board to stdout when the game exits
+    #[arg(long)]
+    print_on_exit: bool,
+    /// Also print the solution
+    #[arg(long, requires = "print_on_exit")]
+    with_solution: bool,
+    /// Also print a code for sharing the game, with the digits and notes
+    /// written so far
+    #[arg(long, requires = "print_on_exit")]
+    with_code: bool,
+    /// Also print an exchange code: the puzzle rated 1-5 stars with a hash
+    /// of the solution instead of the solution itself
+    #[arg(
+        long,
+        value_name = "STARS",
+        requires = "print_on_exit",
+        value_parser = clap::value_parser!(u8).range(1..=5)
+    )]
+    with_exchange: Option<u8>,
+    /// Show input and draw latencies, and write them as JSON to the file
+    /// if one is given
+    #[arg(long, hide = true, value_name = "JSON", num_args = 0..=1)]
+    diagnostics: Option<Option<PathBuf>>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Rate every puzzle in a file (one 81 character puzzle per line)
+    Analyze {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t)]
+        format: ReportFormat,
+        /// where to write the report, defaults to <file>.report.<format>
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Walk through solving a puzzle by logic, placement by placement,
+    /// which shows why its solution is the only one
+    Explain {
+        /// an 81 character puzzle, or a file as with --import
+        #[arg(value_name = "FILE|STRING")]
+        puzzle: String,
+        #[arg(long, value_enum, default_value_t)]
+        format: WalkthroughFormat,
+        /// where to write the walkthrough, printed when not given
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check the files the game keeps, move ones in an older format to the
+    /// current one and rebuild the library, and say how big they are
+    Doctor {
+        /// also make the repairs that lose something, after asking
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Host games over SSH for anyone to play with `ssh -p <port> <host>`,
+    /// each in a game of their own and with stats of their own, told apart
+    /// by the key they log in with
+    #[cfg(feature = "ssh")]
+    Serve {
+        #[arg(long, default_value = "0.0.0.0:2222")]
+        listen: std::net::SocketAddr,
+        /// the server's private key, as `ssh-keygen -t ed25519` makes them
+        #[arg(long, value_name = "FILE")]
+        host_key: PathBuf,
+    },
+    /// Host games over plain telnet, drawn in ASCII, for BBS doors and
+    /// terminals too old for SSH
+    Telnet {
+        #[arg(long, default_value = "0.0.0.0:2323")]
+        listen: std::net::SocketAddr,
+    },
+}
 
 fn main() -> Result {
     color_eyre::install()?;
+    rudoku::install_panic_hook();
+    // the difficulties in the config are choices of --difficulty, a broken
+    // config is reported once it's loaded for real
+    let _ = Config::load();
+    let names: Vec<&str> = Difficulty::all()
+        .into_iter()
+        .map(Difficulty::label)
+        .collect();
+    let matches = Cli::command()
+        .mut_arg("difficulty", |arg| {
+            arg.value_parser(PossibleValuesParser::new(names))
+        })
+        .get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    match cli.command {
+        Some(Command::Analyze {
+            file,
+            format,
+            output,
+        }) => return rudoku::analyze::run(&file, format, output),
+        Some(Command::Explain {
+            puzzle,
+            format,
+            output,
+        }) => return rudoku::walkthrough::run(&puzzle, format, output),
+        Some(Command::Doctor { fix }) => return rudoku::doctor::run(fix),
+        #[cfg(feature = "ssh")]
+        Some(Command::Serve { listen, host_key }) => return rudoku::ssh::serve(listen, &host_key),
+        Some(Command::Telnet { listen }) => return rudoku::telnet::serve(listen),
+        None => {}
+    }
+
+    let mut app = App::with_config_file(Config::load())
+        .with_prefs(Prefs::load())
+        .with_stats(Stats::load())
+        .with_game_mode(cli.game_mode);
+    // a puzzle asked for on the command line wins over the saved game
+    let asked = cli.game_mode == GameMode::Daily
+        || cli.code.is_some()
+        || cli.exchange.is_some()
+        || cli.mask.is_some()
+        || cli.import.is_some()
+        || cli.puzzle.is_some()
+        || cli.difficulty.is_some()
+        || cli.variant.is_some()
+        || cli.size.is_some()
+        || cli.needs.is_some()
+        || cli.up_to.is_some()
+        || cli.wordoku.is_some();
+    if !asked {
+        app = app.with_saved(SavedGame::load()).with_menu();
+    }
+    if let Some(variant) = cli.variant {
+        app = app.with_variant(variant);
+    }
+    if let Some(size) = cli.size {
+        app = app.with_size(size);
+    }
+    if let Some(difficulty) = cli.difficulty.as_deref().and_then(Difficulty::named) {
+        app = app.with_difficulty(difficulty);
+    }
+    if let Some(code) = &cli.code {
+        app = app.with_game(formats::parse_game_code(code)?);
+    }
+    if let Some(code) = &cli.exchange {
+        app = app.with_exchange(formats::Exchange::parse(code)?);
+    }
+    if let Some(path) = &cli.mask {
+        let mask: Mask = fs::read_to_string(path)
+            .wrap_err_with(|| format!("reading {}", path.display()))?
+            .parse()?;
+        let puzzle = generator::from_mask(&mask, cli.mask_attempts, &mut Rng::from_time())?;
+        app = app.with_puzzle(puzzle);
+    }
+    if cli.needs.is_some() || cli.up_to.is_some() {
+        let up_to = cli.up_to.or(cli.needs).unwrap_or(Technique::Swordfish);
+        let puzzle = generator::with_techniques(
+            cli.needs,
+            up_to,
+            cli.technique_attempts,
+            &mut Rng::from_time(),
+        )?;
+        app = app.with_puzzle(puzzle);
+    }
+    if let Some(path) = &cli.import {
+        let imported = FormatRegistry::default().import(path)?;
+        app = app.with_import(imported);
+    }
+    if let Some(puzzle) = &cli.puzzle {
+        let path = PathBuf::from(puzzle);
+        if path.exists() {
+            let imported = FormatRegistry::default().import(&path)?;
+            app = app.with_import(imported);
+        } else {
+            let board = puzzle
+                .parse()
+                .wrap_err_with(|| format!("{puzzle:?} is neither a file nor a puzzle"))?;
+            app = app.with_import(Imported {
+                board,
+                report: CapabilityReport::default(),
+            });
+        }
+    }
+    if let Some(target) = cli.generate_hard {
+        GenerationJob::new(target, Rng::from_time()).save()?;
+    }
+    app = app.with_generation(GenerationJob::load());
+    if let Some(word) = cli.wordoku {
+        let (puzzle, symbols) = wordoku::generate(&word, cli.word_place, &mut Rng::from_time())?;
+        app = app.with_puzzle(puzzle).with_symbols(symbols);
+    }
+    if let Some(skill) = cli.race {
+        app = app.with_race(skill);
+    }
+    if cli.diagnostics.is_some() {
+        app = app.with_diagnostics();
+    }
     let terminal = ratatui::init();
-    let app_result = App::default().run(terminal);
+    let app_result = app.run(terminal);
     ratatui::restore();
+    if let Some(Some(path)) = &cli.diagnostics {
+        app.save_diagnostics(path)?;
+    }
+    if let Some(certificate) = app.certificate() {
+        println!("{certificate}");
+    }
+    if cli.print_on_exit {
+        print!(
+            "{}",
+            app.exit_report(cli.with_solution, cli.with_code, cli.with_exchange)
+        );
+    }
     app_result
 }
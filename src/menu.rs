@@ -0,0 +1,236 @@
+use std::path::{Path, PathBuf};
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+
+use crate::{
+    charset::GlyphSet,
+    daily::Archive,
+    difficulty::Difficulty,
+    duel::Duel,
+    form::{Field, Form, FormEvent},
+    sized::{BoardSize, SizedBoard},
+};
+
+/// What fills the terminal: the menus, or the board with everything that
+/// opens over it.
+pub enum Screen {
+    Menu(MainMenu),
+    /// the menu it was opened from waits to be gone back to
+    Settings(Form, MainMenu),
+    Game,
+    /// a hot-seat race, which leaves the game as it was
+    Duel(Box<Duel>),
+    /// a 6x6 or 16x16 board, which leaves the game as it was too
+    Sized(SizedBoard),
+    /// the daily puzzles, over the menu they were opened from
+    Daily(Archive, MainMenu),
+    Stats(MainMenu),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    NewGame,
+    Continue,
+    Daily,
+    HotSeat,
+    ChessClock,
+    OtherSize,
+    Import,
+    Stats,
+    Settings,
+    Quit,
+}
+
+impl MenuItem {
+    fn label(self) -> &'static str {
+        match self {
+            MenuItem::NewGame => "New game",
+            MenuItem::Continue => "Continue",
+            MenuItem::Daily => "Daily puzzle",
+            MenuItem::HotSeat => "Hot seat",
+            MenuItem::ChessClock => "Chess clock",
+            MenuItem::OtherSize => "Other size",
+            MenuItem::Import => "Import",
+            MenuItem::Stats => "Statistics",
+            MenuItem::Settings => "Settings",
+            MenuItem::Quit => "Quit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuEvent {
+    NewGame(Difficulty),
+    Continue,
+    Daily,
+    /// a hot-seat game, in turns on a chess clock when `true`
+    HotSeat(Difficulty, bool),
+    Sized(BoardSize),
+    Import(PathBuf),
+    Stats,
+    Settings,
+    Quit,
+}
+
+/// The first screen, with the difficulty of a new game picked right on
+/// its line.
+pub struct MainMenu {
+    items: Vec<MenuItem>,
+    selected: usize,
+    pub difficulty: Difficulty,
+    /// the board size picked on the other size line
+    pub size: BoardSize,
+    /// the file of a puzzle to import, asked for over the menu
+    pub import: Option<Form>,
+}
+
+impl MainMenu {
+    /// Starts on Continue when there's a game to go back to.
+    pub fn new(can_continue: bool, difficulty: Difficulty) -> Self {
+        let items = [
+            MenuItem::Continue,
+            MenuItem::NewGame,
+            MenuItem::Daily,
+            MenuItem::HotSeat,
+            MenuItem::ChessClock,
+            MenuItem::OtherSize,
+            MenuItem::Import,
+            MenuItem::Stats,
+            MenuItem::Settings,
+            MenuItem::Quit,
+        ]
+        .into_iter()
+        .filter(|item| can_continue || *item != MenuItem::Continue)
+        .collect();
+        Self {
+            items,
+            selected: 0,
+            difficulty,
+            size: BoardSize::default(),
+            import: None,
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let count = self.items.len() as isize;
+        self.selected = (self.selected as isize + delta).rem_euclid(count) as usize;
+    }
+
+    fn change_difficulty(&mut self, harder: bool) {
+        let next = match harder {
+            true => self.difficulty.harder(),
+            false => self.difficulty.easier(),
+        };
+        self.difficulty = next.unwrap_or(self.difficulty);
+    }
+
+    fn open_import(&mut self) {
+        let file = Field::text("file", "").validate(|file| match Path::new(file).is_file() {
+            true => Ok(()),
+            false => Err("no such file".into()),
+        });
+        self.import = Some(Form::new(vec![file], &["Play", "Cancel"]));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<MenuEvent> {
+        if let Some(form) = &mut self.import {
+            match form.handle_key(key) {
+                FormEvent::Submitted(0) => {
+                    let file = form.field("file").map_or("", Field::text_value);
+                    let path = PathBuf::from(file);
+                    self.import = None;
+                    return Some(MenuEvent::Import(path));
+                }
+                FormEvent::Submitted(_) | FormEvent::Cancelled => self.import = None,
+                FormEvent::Pending => {}
+            }
+            return None;
+        }
+        let item = self.items[self.selected];
+        let leveled = matches!(
+            item,
+            MenuItem::NewGame | MenuItem::HotSeat | MenuItem::ChessClock
+        );
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => self.move_by(1),
+            KeyCode::Left | KeyCode::Char('h') if leveled => self.change_difficulty(false),
+            KeyCode::Right | KeyCode::Char('l') if leveled => self.change_difficulty(true),
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Right | KeyCode::Char('l')
+                if item == MenuItem::OtherSize =>
+            {
+                self.size = self.size.other();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return Some(MenuEvent::Quit),
+            KeyCode::Enter | KeyCode::Char(' ') => match item {
+                MenuItem::NewGame => return Some(MenuEvent::NewGame(self.difficulty)),
+                MenuItem::Continue => return Some(MenuEvent::Continue),
+                MenuItem::Daily => return Some(MenuEvent::Daily),
+                MenuItem::HotSeat => return Some(MenuEvent::HotSeat(self.difficulty, false)),
+                MenuItem::ChessClock => return Some(MenuEvent::HotSeat(self.difficulty, true)),
+                MenuItem::OtherSize => return Some(MenuEvent::Sized(self.size)),
+                MenuItem::Import => self.open_import(),
+                MenuItem::Stats => return Some(MenuEvent::Stats),
+                MenuItem::Settings => return Some(MenuEvent::Settings),
+                MenuItem::Quit => return Some(MenuEvent::Quit),
+            },
+            _ => {}
+        }
+        None
+    }
+
+    /// Lines of the menu, for sizing the box around it.
+    pub fn height(&self) -> u16 {
+        self.items.len() as u16 + 2
+    }
+}
+
+pub struct MenuView<'a> {
+    pub menu: &'a MainMenu,
+    pub style: Style,
+    pub selected_style: Style,
+    pub glyphs: GlyphSet,
+}
+
+impl Widget for MenuView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let menu = self.menu;
+        let mut lines: Vec<Line> = menu
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, &item)| {
+                let text = match item {
+                    MenuItem::NewGame | MenuItem::HotSeat | MenuItem::ChessClock => {
+                        format!(" {:<10} < {} >", item.label(), menu.difficulty)
+                    }
+                    MenuItem::OtherSize => {
+                        format!(" {:<10} < {} >", item.label(), menu.size.label())
+                    }
+                    _ => format!(" {}", item.label()),
+                };
+                let style = match i == menu.selected {
+                    true => self.selected_style,
+                    false => self.style,
+                };
+                Line::styled(
+                    format!("{text:<width$}", width = area.width as usize),
+                    style,
+                )
+            })
+            .collect();
+        let [up, down, left, right] = self.glyphs.arrows;
+        lines.push(Line::default());
+        lines.push(Line::from(format!(
+            " {up}/{down} move  {left}/{right} level  Enter pick"
+        )));
+        Paragraph::new(lines).style(self.style).render(area, buf);
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// Pencil marks, one bitmask per cell with bit `d` set for digit `d`.
+/// Kept apart from the board so they never affect validation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notes([[u16; 9]; 9]);
+
+impl Notes {
+    pub fn get(&self, row: usize, col: usize) -> u16 {
+        self.0[row][col]
+    }
+
+    pub fn has(&self, row: usize, col: usize, digit: u8) -> bool {
+        self.0[row][col] & (1 << digit) != 0
+    }
+
+    pub fn toggle(&mut self, row: usize, col: usize, digit: u8) {
+        self.0[row][col] ^= 1 << digit;
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, marks: u16) {
+        self.0[row][col] = marks;
+    }
+
+    pub fn clear_cell(&mut self, row: usize, col: usize) {
+        self.0[row][col] = 0;
+    }
+}
+
+/// What to do with a note for a digit that is already placed in the
+/// cell's row, column or box.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteConflicts {
+    #[default]
+    Allow,
+    /// refuse to add the note
+    Prevent,
+    /// keep the note but draw it in red
+    Flag,
+}
+
+/// Where the marks of notes mode go in a cell big enough to spread them
+/// out. Center marks always go in the middle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteLayout {
+    /// each digit in its place on a phone keypad
+    #[default]
+    Keypad,
+    /// in the corners from the lowest digit up, then along the edges, as
+    /// competition software does
+    Corner,
+}
+
+pub fn digits(mask: u16) -> impl Iterator<Item = u8> {
+    (1..=9).filter(move |d| mask & (1 << d) != 0)
+}
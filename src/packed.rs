@@ -0,0 +1,381 @@
+use color_eyre::eyre::{Result, bail};
+
+use crate::{formats::crc16, notes::Notes, soduko::BoardState, variant::Variant};
+
+const PROGRESS: u8 = 1;
+const NOTES: u8 = 2;
+/// two bits for the variant, 0 for classic so older codes read as before
+const VARIANT: u8 = 0b1100;
+const VARIANTS: [Variant; 3] = [Variant::Classic, Variant::X, Variant::Hyper];
+/// 81 bits, one per cell
+const BITMAP: usize = 11;
+
+/// A board packed into as few bytes as it takes, for codes that get typed,
+/// put in links or sent over the wire:
+///
+/// - a flags byte saying whether progress and notes follow, and the
+///   variant
+/// - a bitmap of the givens, then their digits four bits each
+/// - with progress, four bits for each other cell, 0 when it's empty
+/// - with notes, a varint count of noted cells, then for each a varint of
+///   the cells skipped since the last and a varint of its marks
+///
+/// A bare puzzle of 25 givens comes to 25 bytes and even a full grid to 53,
+/// 36 and 74 characters once [`seal`] and [`to_text`] add a checksum and
+/// base64.
+#[derive(Debug, Clone, Copy)]
+pub struct Packed {
+    pub puzzle: BoardState,
+    /// the digits written so far over the puzzle
+    pub board: Option<BoardState>,
+    pub notes: Notes,
+}
+
+impl Packed {
+    pub fn puzzle(puzzle: BoardState) -> Self {
+        Self {
+            puzzle,
+            board: None,
+            notes: Notes::default(),
+        }
+    }
+
+    pub fn game(puzzle: BoardState, board: BoardState, notes: Notes) -> Self {
+        Self {
+            puzzle,
+            board: Some(board),
+            notes,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let noted: Vec<usize> = (0..81)
+            .filter(|&pos| self.notes.get(pos / 9, pos % 9) != 0)
+            .collect();
+        let mut flags = 0;
+        if self.board.is_some() {
+            flags |= PROGRESS;
+        }
+        if !noted.is_empty() {
+            flags |= NOTES;
+        }
+        let variant = VARIANTS.iter().position(|&v| v == self.puzzle.variant());
+        flags |= (variant.unwrap_or_default() as u8) << VARIANT.trailing_zeros();
+        let mut out = vec![flags];
+        let mut bitmap = [0u8; BITMAP];
+        for pos in (0..81).filter(|&pos| digit(&self.puzzle, pos) != 0) {
+            bitmap[pos / 8] |= 1 << (pos % 8);
+        }
+        out.extend(bitmap);
+        let givens = (0..81)
+            .map(|pos| digit(&self.puzzle, pos))
+            .filter(|&d| d != 0);
+        nibbles(&mut out, givens);
+        if let Some(board) = &self.board {
+            let written = (0..81)
+                .filter(|&pos| digit(&self.puzzle, pos) == 0)
+                .map(|pos| digit(board, pos));
+            nibbles(&mut out, written);
+        }
+        if !noted.is_empty() {
+            varint(&mut out, noted.len() as u32);
+            let mut next = 0;
+            for pos in noted {
+                varint(&mut out, (pos - next) as u32);
+                varint(&mut out, (self.notes.get(pos / 9, pos % 9) >> 1) as u32);
+                next = pos + 1;
+            }
+        }
+        out
+    }
+
+    /// Reads what [`encode`](Self::encode) wrote, returning it with the bytes
+    /// after it.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, &[u8])> {
+        let mut reader = Reader(bytes);
+        let flags = reader.byte()?;
+        if flags & !(PROGRESS | NOTES | VARIANT) != 0 {
+            bail!("code is corrupted: unknown flags {flags:#04x}");
+        }
+        let Some(&variant) = VARIANTS.get(((flags & VARIANT) >> VARIANT.trailing_zeros()) as usize)
+        else {
+            bail!("code is corrupted: unknown variant");
+        };
+        let bitmap = reader.take(BITMAP)?;
+        let given = |pos: usize| bitmap[pos / 8] & (1 << (pos % 8)) != 0;
+        if bitmap[BITMAP - 1] >> 1 != 0 {
+            bail!("code is corrupted: the givens run past the board");
+        }
+        let given_count = (0..81).filter(|&pos| given(pos)).count();
+        let mut puzzle = BoardState::empty(variant);
+        let givens = reader.nibbles(given_count)?;
+        for (pos, d) in (0..81).filter(|&pos| given(pos)).zip(givens) {
+            if d == 0 || d > 9 {
+                bail!("code is corrupted: {d} is not a given");
+            }
+            puzzle.set_pos(pos, d.into());
+        }
+        let board = match flags & PROGRESS != 0 {
+            true => {
+                let mut board = puzzle;
+                let written = reader.nibbles(81 - given_count)?;
+                for (pos, d) in (0..81).filter(|&pos| !given(pos)).zip(written) {
+                    if d > 9 {
+                        bail!("code is corrupted: {d} is not a digit");
+                    }
+                    board.set_pos(pos, d.into());
+                }
+                Some(board)
+            }
+            false => None,
+        };
+        let mut notes = Notes::default();
+        if flags & NOTES != 0 {
+            let mut pos = 0;
+            for _ in 0..reader.varint()? {
+                pos += reader.varint()? as usize;
+                let marks = reader.varint()?;
+                if pos >= 81 || marks >= 1 << 9 {
+                    bail!("code is corrupted: notes out of range");
+                }
+                for d in crate::notes::digits((marks as u16) << 1) {
+                    notes.toggle(pos / 9, pos % 9, d);
+                }
+                pos += 1;
+            }
+        }
+        Ok((
+            Self {
+                puzzle,
+                board,
+                notes,
+            },
+            reader.0,
+        ))
+    }
+}
+
+fn digit(board: &BoardState, pos: usize) -> u8 {
+    board[pos / 9][pos % 9].map_or(0, |d| d.get())
+}
+
+/// Two digits to a byte, the first in the low half.
+fn nibbles(out: &mut Vec<u8>, digits: impl Iterator<Item = u8>) {
+    let digits: Vec<u8> = digits.collect();
+    out.extend(
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] | pair.get(1).map_or(0, |d| d << 4)),
+    );
+}
+
+/// LEB128: seven bits a byte, the high bit set while more follow.
+fn varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            bail!("code is truncated");
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn nibbles(&mut self, count: usize) -> Result<Vec<u8>> {
+        let bytes = self.take(count.div_ceil(2))?;
+        Ok(bytes
+            .iter()
+            .flat_map(|byte| [byte & 0xf, byte >> 4])
+            .take(count)
+            .collect())
+    }
+
+    fn varint(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+        for shift in (0..32).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("code is corrupted: a number runs on too long")
+    }
+}
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// The bytes in URL safe base64 without padding, safe in a link or a
+/// file name.
+pub fn to_text(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+pub fn from_text(text: &str) -> Result<Vec<u8>> {
+    let mut sixes = Vec::with_capacity(text.len());
+    for c in text.trim().bytes() {
+        match ALPHABET.iter().position(|&a| a == c) {
+            Some(six) => sixes.push(six as u32),
+            None => bail!("code is corrupted: {:?} doesn't belong in it", c as char),
+        }
+    }
+    if sixes.len() % 4 == 1 {
+        bail!("code is truncated");
+    }
+    let mut bytes = Vec::with_capacity(sixes.len() * 3 / 4);
+    for chunk in sixes.chunks(4) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &six)| n | six << (18 - 6 * i));
+        bytes.extend(&n.to_be_bytes()[1..chunk.len()]);
+    }
+    Ok(bytes)
+}
+
+/// Appends a CRC-16 of everything before it.
+pub fn seal(bytes: &mut Vec<u8>) {
+    let crc = crc16(bytes);
+    bytes.extend(crc.to_be_bytes());
+}
+
+/// Checks that the first `len` bytes are followed by their CRC-16 and
+/// nothing else, telling a cut off code from a mangled one.
+pub fn unseal(bytes: &[u8], len: usize) -> Result<()> {
+    if bytes.len() < len + 2 {
+        bail!("code is truncated: the checksum is missing");
+    }
+    if bytes.len() > len + 2 {
+        bail!("code is corrupted: there's more after the checksum");
+    }
+    if crc16(&bytes[..len]).to_be_bytes() != bytes[len..] {
+        bail!("code is corrupted: the checksum doesn't match");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        formats::{self, code},
+        generator,
+        rng::Rng,
+    };
+
+    /// A game off a random grid: about half the cells given, some of the
+    /// rest written, right or wrong, and random notes.
+    fn random_game(rng: &mut Rng) -> Packed {
+        let solution = generator::solved_grid(rng);
+        let mut puzzle = BoardState::default();
+        let mut board = BoardState::default();
+        let mut notes = Notes::default();
+        for pos in 0..81 {
+            match rng.below(4) {
+                0 | 1 => {
+                    puzzle.set_pos(pos, digit(&solution, pos).into());
+                    board.set_pos(pos, digit(&solution, pos).into());
+                }
+                2 => board.set_pos(pos, (rng.below(9) as u8 + 1).into()),
+                _ => {
+                    for _ in 0..rng.below(4) {
+                        notes.toggle(pos / 9, pos % 9, rng.below(9) as u8 + 1);
+                    }
+                }
+            }
+        }
+        Packed::game(puzzle, board, notes)
+    }
+
+    #[test]
+    fn random_games_round_trip() {
+        let mut rng = Rng::new(1018);
+        for _ in 0..200 {
+            let game = random_game(&mut rng);
+            let text = formats::game_code(&game);
+            let read = formats::parse_game_code(&text).unwrap();
+            assert_eq!(code(&read.puzzle), code(&game.puzzle));
+            assert_eq!(read.board.map(|b| code(&b)), game.board.map(|b| code(&b)));
+            assert_eq!(read.notes, game.notes);
+        }
+    }
+
+    #[test]
+    fn bare_puzzle_stays_under_sixty_bytes() {
+        let mut rng = Rng::new(60);
+        let puzzle = generator::dig(generator::solved_grid(&mut rng), &mut rng);
+        let mut bytes = Packed::puzzle(puzzle).encode();
+        seal(&mut bytes);
+        assert!(bytes.len() < 60, "{} bytes", bytes.len());
+        let full = generator::solved_grid(&mut rng);
+        assert!(Packed::puzzle(full).encode().len() < 60);
+    }
+
+    #[test]
+    fn text_round_trips_every_length() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for len in 0..bytes.len() {
+            assert_eq!(from_text(&to_text(&bytes[..len])).unwrap(), &bytes[..len]);
+        }
+    }
+
+    #[test]
+    fn random_bytes_never_panic() {
+        let mut rng = Rng::new(991);
+        for _ in 0..5000 {
+            let len = rng.below(80);
+            let mut bytes: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+            // mostly valid flags, so the fuzzing gets past the first byte
+            if let Some(flags) = bytes.first_mut() {
+                *flags &= PROGRESS | NOTES | VARIANT;
+            }
+            let _ = Packed::decode(&bytes);
+            let _ = formats::parse_game_code(&to_text(&bytes));
+        }
+    }
+
+    #[test]
+    fn every_cut_off_code_is_refused() {
+        let mut rng = Rng::new(3);
+        let text = formats::game_code(&random_game(&mut rng));
+        for len in 0..text.len() {
+            assert!(formats::parse_game_code(&text[..len]).is_err(), "{len}");
+        }
+    }
+
+    #[test]
+    fn variant_survives_packing() {
+        let mut rng = Rng::new(7);
+        for variant in VARIANTS {
+            let puzzle = generator::variant_grid(variant, &mut rng);
+            let bytes = Packed::puzzle(puzzle).encode();
+            let (game, rest) = Packed::decode(&bytes).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(game.puzzle.variant(), variant);
+            assert_eq!(code(&game.puzzle), code(&puzzle));
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::keymap::Action;
+
+/// What can be typed after `:`, with what it does.
+const COMMANDS: &[(&str, &str)] = &[
+    (":w", "save the game"),
+    (":q", "quit, asking to save first if anything isn't"),
+    (":wq", "save and quit"),
+    (":q!", "quit without saving"),
+];
+
+/// A fuzzy searchable list of the actions the current mode offers. Starting
+/// with `:` turns it into a command line instead.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    /// (keys, action) for every entry, in keymap order
+    entries: Vec<(String, Action)>,
+    query: String,
+    /// indices into `entries` matching the query, best first
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+pub enum PaletteEvent {
+    Run(Action),
+    /// a line typed after `:`, without it
+    Command(String),
+    Close,
+}
+
+impl Palette {
+    pub fn new(entries: Vec<(String, Action)>) -> Self {
+        let mut palette = Self {
+            entries,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        palette.find_matches();
+        palette
+    }
+
+    /// Types `text` into the query, as if typed by the player.
+    pub fn type_in(&mut self, text: &str) {
+        self.query.push_str(text);
+        self.find_matches();
+    }
+
+    fn is_command(&self) -> bool {
+        self.query.starts_with(':')
+    }
+
+    /// Every key goes to the palette while it's open: typing filters,
+    /// arrows pick, Enter runs and Esc closes.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<PaletteEvent> {
+        match key.code {
+            KeyCode::Esc => return Some(PaletteEvent::Close),
+            KeyCode::Enter if self.is_command() => {
+                return Some(PaletteEvent::Command(self.query[1..].trim().to_string()));
+            }
+            KeyCode::Enter => {
+                let action = self.matches.get(self.selected).map(|&i| self.entries[i].1);
+                return Some(action.map_or(PaletteEvent::Close, PaletteEvent::Run));
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1))
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.find_matches();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.find_matches();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn find_matches(&mut self) {
+        if self.is_command() {
+            self.matches.clear();
+            self.selected = 0;
+            return;
+        }
+        let mut scored: Vec<(i32, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, action))| {
+                Some((fuzzy_score(&self.query, action.description())?, i))
+            })
+            .collect();
+        // stable, so equal scores keep keymap order
+        scored.sort_by_key(|(score, _)| -score);
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+}
+
+/// Scores `text` against `query` when the query's characters appear in it
+/// in order, ignoring case. Runs of adjacent characters and matches at the
+/// start of a word score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let mut score = 0;
+    let mut from = 0;
+    let mut last: Option<usize> = None;
+    for q in query.chars().map(|c| c.to_ascii_lowercase()) {
+        if q == ' ' {
+            continue;
+        }
+        let i = from + text[from..].iter().position(|&c| c == q)?;
+        score += 1;
+        if last.is_some_and(|last| last + 1 == i) {
+            score += 4;
+        }
+        if i == 0 || text[i - 1] == ' ' {
+            score += 3;
+        }
+        last = Some(i);
+        from = i + 1;
+    }
+    Some(score)
+}
+
+pub struct PaletteView<'a> {
+    pub palette: &'a Palette,
+    pub style: Style,
+    pub selected_style: Style,
+}
+
+impl Widget for PaletteView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let palette = self.palette;
+        let mut lines = vec![Line::from(format!("> {}", palette.query))];
+        if palette.is_command() {
+            lines.extend(
+                COMMANDS
+                    .iter()
+                    .map(|(command, what)| Line::from(format!(" {command:<4} {what}"))),
+            );
+        }
+        let height = area.height.saturating_sub(1) as usize;
+        let scroll = palette.selected.saturating_sub(height.saturating_sub(1));
+        for (n, &i) in palette.matches.iter().enumerate().skip(scroll).take(height) {
+            let (keys, action) = &palette.entries[i];
+            let width = area.width as usize;
+            let description = action.description();
+            let gap = width.saturating_sub(description.width() + keys.width() + 2);
+            let text = format!(" {description}{:gap$}{keys} ", "");
+            let style = match n == palette.selected {
+                true => self.selected_style,
+                false => self.style,
+            };
+            lines.push(Line::styled(text, style));
+        }
+        if palette.matches.is_empty() && !palette.is_command() {
+            lines.push(Line::from(" no matching action"));
+        }
+        Paragraph::new(lines).style(self.style).render(area, buf);
+    }
+}
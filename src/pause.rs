@@ -0,0 +1,115 @@
+use std::{fs, path::PathBuf};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+use serde::Deserialize;
+
+use crate::{rng::Rng, soduko::BoardState, symbols::Symbols};
+
+/// What hides the board while the game is paused.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "cover", rename_all = "lowercase")]
+pub enum PauseCover {
+    #[default]
+    Blank,
+    /// ascii art read from a text file
+    Art { path: PathBuf },
+    /// the board with every value swapped for a random one
+    Blur,
+}
+
+impl PauseCover {
+    /// The name in the config.
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseCover::Blank => "blank",
+            PauseCover::Art { .. } => "art",
+            PauseCover::Blur => "blur",
+        }
+    }
+
+    /// Builds the cover when the game is paused, so art files are read and
+    /// random digits rolled once per pause instead of every frame.
+    pub fn strategy(&self, board: &BoardState, symbols: &Symbols) -> Box<dyn Cover> {
+        match self {
+            PauseCover::Blank => Box::new(Blank),
+            PauseCover::Art { path } => match fs::read_to_string(path) {
+                Ok(art) => Box::new(Art(art.lines().map(str::to_string).collect())),
+                Err(err) => Box::new(Art(vec![
+                    format!("could not read {}", path.display()),
+                    err.to_string(),
+                ])),
+            },
+            PauseCover::Blur => Box::new(Blur::new(board, symbols)),
+        }
+    }
+}
+
+pub trait Cover {
+    fn render(&self, area: Rect, buf: &mut Buffer, style: Style);
+}
+
+fn render_centered(lines: Vec<Line>, area: Rect, buf: &mut Buffer, style: Style) {
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let area = Rect {
+        y: area.y + top,
+        height: area.height - top,
+        ..area
+    };
+    Paragraph::new(lines)
+        .centered()
+        .style(style)
+        .render(area, buf);
+}
+
+pub struct Blank;
+
+impl Cover for Blank {
+    fn render(&self, area: Rect, buf: &mut Buffer, style: Style) {
+        render_centered(vec![Line::from("paused")], area, buf, style);
+    }
+}
+
+pub struct Art(Vec<String>);
+
+impl Cover for Art {
+    fn render(&self, area: Rect, buf: &mut Buffer, style: Style) {
+        let lines = self.0.iter().map(|l| Line::from(l.as_str())).collect();
+        render_centered(lines, area, buf, style);
+    }
+}
+
+/// Same shape as the real board so the screen doesn't jump, but the values
+/// are noise.
+pub struct Blur(Vec<String>);
+
+impl Blur {
+    fn new(board: &BoardState, symbols: &Symbols) -> Self {
+        let mut rng = Rng::from_time();
+        let lines = board
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell.is_some() {
+                        true => symbols.symbol(rng.below(9) as u8 + 1),
+                        false => symbols.blank(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("   ")
+            })
+            .collect();
+        Self(lines)
+    }
+}
+
+impl Cover for Blur {
+    fn render(&self, area: Rect, buf: &mut Buffer, style: Style) {
+        let lines = self.0.iter().map(|l| Line::from(l.as_str())).collect();
+        render_centered(lines, area, buf, style);
+    }
+}
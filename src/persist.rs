@@ -0,0 +1,203 @@
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{WrapErr, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::state_dir, difficulty::Difficulty, notes::Notes, replay::Move, rules::GameMode,
+    soduko::BoardState, variant::Variant,
+};
+
+/// Writes `contents` next to `path` and renames it over, so being killed
+/// mid write never leaves a broken file behind.
+pub(crate) fn write_replacing(path: &Path, contents: &str) -> color_eyre::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, contents).wrap_err_with(|| format!("writing {}", tmp.display()))?;
+    fs::rename(&tmp, path).wrap_err_with(|| format!("writing {}", path.display()))
+}
+
+/// The game in progress, written after every move so it can be picked up
+/// again after the terminal closes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    /// the givens in the 81 character format
+    pub puzzle: String,
+    pub board: String,
+    pub notes: Notes,
+    /// saves from before center marks have none
+    #[serde(default)]
+    pub center: Notes,
+    /// seconds played, not counting pauses
+    pub seconds: u64,
+    /// cells changed as they were before, newest last
+    pub history: Vec<Undo>,
+    pub difficulty: Difficulty,
+    pub hints: u32,
+    pub mistakes: u32,
+    pub revealed: [[bool; 9]; 9],
+    #[serde(default)]
+    pub highlights: [[u8; 9]; 9],
+    #[serde(default)]
+    pub submissions: u32,
+    /// the progress curve so far, see [`GameRecord::curve`]
+    ///
+    /// [`GameRecord::curve`]: crate::stats::GameRecord::curve
+    #[serde(default)]
+    pub curve: Vec<u32>,
+    /// saves from before variants are classic
+    #[serde(default)]
+    pub variant: Variant,
+    /// saves from before recordings can't be replayed
+    #[serde(default)]
+    pub moves: Vec<Move>,
+    /// saves from before game modes are casual
+    #[serde(default)]
+    pub game_mode: GameMode,
+    /// the [`Exchange::code`] of a puzzle played from one
+    ///
+    /// [`Exchange::code`]: crate::formats::Exchange::code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange: Option<String>,
+    /// stretches of `history` undone together
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bulk: Vec<Range<usize>>,
+    /// guesses marked, the latest last
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guesses: Vec<SavedGuess>,
+}
+
+/// A cell as it was before a change, for undo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Undo {
+    pub row: usize,
+    pub col: usize,
+    /// 0 for empty
+    pub digit: u8,
+    /// saves from before undo kept reveals and notes have `[row, col,
+    /// digit]` entries, read with none
+    #[serde(default)]
+    pub revealed: bool,
+    #[serde(default)]
+    pub notes: u16,
+    #[serde(default)]
+    pub center: u16,
+    /// the color, see [`SavedGame::highlights`]
+    #[serde(default)]
+    pub highlight: u8,
+}
+
+/// A marked guess, the game as it was when it was marked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGuess {
+    pub board: String,
+    pub notes: Notes,
+    pub center: Notes,
+    pub revealed: [[bool; 9]; 9],
+    /// length of the undo history at the time
+    pub history: usize,
+    pub cell: (usize, usize),
+}
+
+impl SavedGame {
+    pub fn path() -> Option<PathBuf> {
+        Some(state_dir()?.join("game.json"))
+    }
+
+    pub fn puzzle(&self) -> color_eyre::Result<BoardState> {
+        self.puzzle
+            .parse::<BoardState>()?
+            .with_variant(self.variant)
+    }
+
+    pub fn board(&self) -> color_eyre::Result<BoardState> {
+        self.board.parse::<BoardState>()?.with_variant(self.variant)
+    }
+
+    /// The undo history, an error when an entry is off the board, which
+    /// undoing it would trip over.
+    pub fn history(&self) -> color_eyre::Result<Vec<Undo>> {
+        if let Some(undo) = self
+            .history
+            .iter()
+            .find(|undo| undo.row >= 9 || undo.col >= 9 || undo.digit > 9)
+        {
+            bail!(
+                "the undo history has {} at ({}, {})",
+                undo.digit,
+                undo.row,
+                undo.col
+            );
+        }
+        let len = self.history.len();
+        if let Some(bulk) = self
+            .bulk
+            .iter()
+            .find(|bulk| bulk.start > bulk.end || bulk.end > len)
+        {
+            bail!("a change of many cells runs past the undo history: {bulk:?}");
+        }
+        if self
+            .guesses
+            .iter()
+            .any(|guess| guess.history > len || guess.cell.0 >= 9 || guess.cell.1 >= 9)
+        {
+            bail!("a guess is marked past the undo history or off the board");
+        }
+        if let Some(m) = self
+            .moves
+            .iter()
+            .find(|m| m.row >= 9 || m.col >= 9 || m.digit > 9)
+        {
+            bail!("the recording has {} at ({}, {})", m.digit, m.row, m.col);
+        }
+        Ok(self.history.clone())
+    }
+
+    /// The board of a marked guess, under the rules of the saved game.
+    pub fn guess_board(&self, guess: &SavedGuess) -> color_eyre::Result<BoardState> {
+        guess
+            .board
+            .parse::<BoardState>()?
+            .with_variant(self.variant)
+    }
+
+    pub fn load() -> color_eyre::Result<Option<Self>> {
+        let Some(path) = Self::path() else {
+            return Ok(None);
+        };
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text =
+            fs::read_to_string(&path).wrap_err_with(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&text)
+            .map(Some)
+            .wrap_err_with(|| format!("parsing {}", path.display()))
+    }
+
+    /// Writes next to the save and renames over it, so closing the terminal
+    /// mid write never loses the game.
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        write_replacing(&path, &serde_json::to_string(self)?)
+    }
+
+    /// Forgets the save once its game is over.
+    pub fn remove() -> color_eyre::Result<()> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                fs::remove_file(&path).wrap_err_with(|| format!("removing {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
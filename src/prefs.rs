@@ -0,0 +1,65 @@
+use std::{fs, path::PathBuf};
+
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{config::state_dir, theme::Theme};
+
+/// Choices made inside the app that should survive a restart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Prefs {
+    /// the theme's name, empty for the first one
+    #[serde(deserialize_with = "theme_name")]
+    pub theme: String,
+    /// show the stats panel next to the board
+    pub hud: bool,
+    /// leave repeated digits unmarked
+    pub hide_conflicts: bool,
+    /// leave the cells holding the selected cell's digit unmarked
+    pub hide_same_digits: bool,
+}
+
+/// Reads the theme by name, or by position as it was stored before themes
+/// had names.
+fn theme_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Index(usize),
+        Name(String),
+    }
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Index(index) => Theme::builtin_name(index).to_string(),
+        Stored::Name(name) => name,
+    })
+}
+
+impl Prefs {
+    pub fn path() -> Option<PathBuf> {
+        Some(state_dir()?.join("prefs.toml"))
+    }
+
+    pub fn load() -> color_eyre::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text =
+            fs::read_to_string(&path).wrap_err_with(|| format!("reading {}", path.display()))?;
+        toml::from_str(&text).wrap_err_with(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).wrap_err_with(|| format!("creating {}", dir.display()))?;
+        }
+        fs::write(&path, toml::to_string(self)?)
+            .wrap_err_with(|| format!("writing {}", path.display()))
+    }
+}
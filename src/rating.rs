@@ -0,0 +1,101 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hints::{self, Technique},
+    soduko::BoardState,
+};
+
+/// How hard a puzzle is for a person, by the hardest technique it takes to
+/// solve by logic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Grade {
+    /// naked and hidden singles
+    Singles,
+    /// pointing pairs, box/line reductions and naked pairs
+    Pairs,
+    /// x-wings and swordfish
+    Fish,
+    /// more than the techniques the hints know, so trial and error
+    Guessing,
+}
+
+impl Grade {
+    fn of(technique: Technique) -> Self {
+        match technique {
+            Technique::NakedSingle | Technique::HiddenSingle => Grade::Singles,
+            Technique::PointingPair | Technique::BoxLineReduction | Technique::NakedPair => {
+                Grade::Pairs
+            }
+            Technique::XWing | Technique::Swordfish => Grade::Fish,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Grade::Singles => "singles",
+            Grade::Pairs => "pairs",
+            Grade::Fish => "fish",
+            Grade::Guessing => "guessing",
+        }
+    }
+}
+
+impl Display for Grade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Solves `board` one hint at a time: how many steps each technique took,
+/// and whether they got all the way. `None` when it doesn't have exactly
+/// one solution, which no amount of logic gets to.
+pub fn breakdown(board: &BoardState) -> Option<(BTreeMap<Technique, usize>, bool)> {
+    if board.count_solutions(2) != 1 {
+        return None;
+    }
+    let mut board = *board;
+    let mut used = BTreeMap::new();
+    while board.clue_count() < 81 {
+        let Some(step) = hints::next_step(&board) else {
+            return Some((used, false));
+        };
+        for &technique in step.after.iter().chain([&step.technique]) {
+            *used.entry(technique).or_default() += 1;
+        }
+        board.set(step.row as u8, step.col as u8, step.digit.into());
+    }
+    Some((used, true))
+}
+
+/// The grade of a [`breakdown`].
+pub fn grade(used: &BTreeMap<Technique, usize>, solved: bool) -> Grade {
+    match solved {
+        true => used
+            .keys()
+            .map(|&technique| Grade::of(technique))
+            .fold(Grade::Singles, Grade::max),
+        false => Grade::Guessing,
+    }
+}
+
+/// Grades `board` by the hardest technique solving it takes.
+pub fn rate(board: &BoardState) -> Option<Grade> {
+    let (used, solved) = breakdown(board)?;
+    Some(grade(&used, solved))
+}
+
+/// The techniques solving `board` takes, `None` when logic alone doesn't
+/// solve it. The hints go for the easiest technique that helps, so each
+/// of these is needed somewhere.
+pub fn techniques(board: &BoardState) -> Option<BTreeSet<Technique>> {
+    match breakdown(board)? {
+        (used, true) => Some(used.into_keys().collect()),
+        (_, false) => None,
+    }
+}
@@ -0,0 +1,138 @@
+use std::io::Write;
+
+use ratatui::{
+    Terminal, TerminalOptions, Viewport,
+    backend::CrosstermBackend,
+    crossterm::{
+        cursor::{Hide, Show},
+        event::{KeyCode, KeyEvent, KeyModifiers},
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::Rect,
+};
+
+use crate::{App, Config, Prefs, SavedGame, Stats, config, events::Events};
+
+/// Runs one remote player's app, with the files of their profile, drawing
+/// to `out` until the app quits or the connection drops. The app is
+/// sandboxed, the player has no business with the host's files.
+pub(crate) fn play(
+    config: Config,
+    profile: &str,
+    mut out: impl Write,
+    events: Events,
+    size: Rect,
+) -> color_eyre::Result<()> {
+    config::set_profile(profile);
+    let mut app = App::with_config(config)
+        .with_prefs(Prefs::load())
+        .with_stats(Stats::load())
+        .sandboxed()
+        .with_saved(SavedGame::load())
+        .with_menu();
+    execute!(out, EnterAlternateScreen, Hide)?;
+    let options = TerminalOptions {
+        viewport: Viewport::Fixed(size),
+    };
+    let mut terminal = Terminal::with_options(CrosstermBackend::new(out), options)?;
+    let result = app.event_loop(&mut terminal, events);
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, Show)?;
+    result
+}
+
+/// The keys in what a terminal sends when typed on. Only what terminals send
+/// without being asked for more: no mouse, no bracketed paste and no kitty
+/// keyboard protocol, so a lone Esc followed quickly by `[` or `O` is taken
+/// for the start of a sequence.
+pub(crate) fn parse_keys(data: &[u8]) -> Vec<KeyEvent> {
+    let text = String::from_utf8_lossy(data);
+    let mut chars = text.chars().peekable();
+    let mut keys = vec![];
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' => match chars.next() {
+                None => KeyCode::Esc.into(),
+                Some('[' | 'O') => match escape(&mut chars) {
+                    Some(key) => key,
+                    None => continue,
+                },
+                Some(c) => KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT),
+            },
+            // telnet sends a return as CR LF or CR NUL
+            '\r' => {
+                chars.next_if(|&c| c == '\n' || c == '\0');
+                KeyCode::Enter.into()
+            }
+            '\n' => KeyCode::Enter.into(),
+            '\t' => KeyCode::Tab.into(),
+            '\x7f' | '\x08' => KeyCode::Backspace.into(),
+            '\0' => KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL),
+            '\x01'..='\x1a' => {
+                let letter = (c as u8 - 1 + b'a') as char;
+                KeyEvent::new(KeyCode::Char(letter), KeyModifiers::CONTROL)
+            }
+            c if c.is_control() => continue,
+            c if c.is_uppercase() => KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT),
+            c => KeyCode::Char(c).into(),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// The key in an escape sequence after its `ESC [` or `ESC O`, as xterm and
+/// its descendants send them: a number, modifiers after a `;`, then a letter
+/// or `~`.
+fn escape(chars: &mut impl Iterator<Item = char>) -> Option<KeyEvent> {
+    let mut params = String::new();
+    let last = chars.find(|&c| {
+        let param = c.is_ascii_digit() || c == ';';
+        if param {
+            params.push(c);
+        }
+        !param
+    })?;
+    let mut params = params.split(';');
+    let number: u8 = params.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+    // one more than shift 1, alt 2 and ctrl 4 added up
+    let bits = params
+        .next()
+        .and_then(|m| m.parse::<u8>().ok())
+        .unwrap_or(1)
+        .saturating_sub(1);
+    let mut modifiers = KeyModifiers::NONE;
+    for (bit, modifier) in [
+        (1, KeyModifiers::SHIFT),
+        (2, KeyModifiers::ALT),
+        (4, KeyModifiers::CONTROL),
+    ] {
+        if bits & bit != 0 {
+            modifiers |= modifier;
+        }
+    }
+    let code = match last {
+        'A' => KeyCode::Up,
+        'B' => KeyCode::Down,
+        'C' => KeyCode::Right,
+        'D' => KeyCode::Left,
+        'H' => KeyCode::Home,
+        'F' => KeyCode::End,
+        'Z' => KeyCode::BackTab,
+        'P'..='S' => KeyCode::F(last as u8 - b'P' + 1),
+        '~' => match number {
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            11..=15 => KeyCode::F(number - 10),
+            17..=21 => KeyCode::F(number - 11),
+            23 | 24 => KeyCode::F(number - 12),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
@@ -0,0 +1,169 @@
+use std::{
+    num::NonZeroU8,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{formats, notes::Notes, soduko::BoardState};
+
+/// fastest a replay goes, times real time
+const MAX_SPEED: u32 = 64;
+
+/// A cell as an edit left it, digit and notes, and how far into the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    pub ms: u64,
+    pub row: u8,
+    pub col: u8,
+    /// 0 for erasing
+    pub digit: u8,
+    /// recordings from before notes were recorded have none
+    #[serde(default)]
+    pub notes: u16,
+    #[serde(default)]
+    pub center: u16,
+}
+
+/// Every edit of a cell over a game, in order.
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    pub moves: Vec<Move>,
+}
+
+impl Recording {
+    /// Picks up the recording of a saved game.
+    pub fn resumed(moves: Vec<Move>) -> Self {
+        Self { moves }
+    }
+
+    /// Records the cell at (row, col) as it is on `board` and in the notes,
+    /// `at` into the game.
+    pub fn record(
+        &mut self,
+        at: Duration,
+        (row, col): (usize, usize),
+        board: &BoardState,
+        notes: &Notes,
+        center: &Notes,
+    ) {
+        self.moves.push(Move {
+            ms: at.as_millis() as u64,
+            row: row as u8,
+            col: col as u8,
+            digit: board[row][col].map_or(0, NonZeroU8::get),
+            notes: notes.get(row, col),
+            center: center.get(row, col),
+        });
+    }
+
+    /// The moves as JSON, with the puzzle they were made on.
+    pub fn json(&self, puzzle: &BoardState) -> serde_json::Result<String> {
+        #[derive(Serialize)]
+        struct Export<'a> {
+            puzzle: String,
+            moves: &'a [Move],
+        }
+        serde_json::to_string_pretty(&Export {
+            puzzle: formats::portable_code(puzzle),
+            moves: &self.moves,
+        })
+    }
+}
+
+/// A recorded game played back from the puzzle, as fast as the player
+/// likes.
+#[derive(Debug)]
+pub struct Replay {
+    moves: Vec<Move>,
+    /// the next move to make
+    next: usize,
+    /// how far into the game the replay is
+    at: Duration,
+    last_step: Instant,
+    speed: u32,
+    pub paused: bool,
+    /// the board and notes as the game ended, put back once the replay
+    /// stops
+    pub ending: (BoardState, Notes, Notes),
+}
+
+impl Replay {
+    pub fn new(recording: &Recording, ending: (BoardState, Notes, Notes)) -> Self {
+        Self {
+            moves: recording.moves.clone(),
+            next: 0,
+            at: Duration::ZERO,
+            last_step: Instant::now(),
+            speed: 1,
+            paused: false,
+            ending,
+        }
+    }
+
+    /// The moves that came due since the last step, in order.
+    pub fn step(&mut self) -> Vec<Move> {
+        let now = Instant::now();
+        if !self.paused {
+            self.at += (now - self.last_step) * self.speed;
+        }
+        self.last_step = now;
+        let due = self.moves[self.next..]
+            .iter()
+            .take_while(|m| m.ms <= self.at.as_millis() as u64)
+            .copied()
+            .collect::<Vec<_>>();
+        self.next += due.len();
+        due
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next == self.moves.len()
+    }
+
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2).min(MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2).max(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::App;
+
+    #[test]
+    fn the_recorded_moves_replay_to_the_board() {
+        let mut app = App::new();
+        let mut empty = (0..81)
+            .map(|i| (i / 9, i % 9))
+            .filter(|&(r, col)| app.data[r][col].is_none());
+        let (first, second) = (empty.next().unwrap(), empty.next().unwrap());
+        app.cursor.select(first.0, first.1);
+        app.write(4);
+        app.cursor.select(second.0, second.1);
+        app.toggle_note(7, false);
+        app.toggle_note(2, true);
+        app.write(5);
+        app.undo();
+
+        let (mut board, mut notes, mut center) = (app.puzzle, Notes::default(), Notes::default());
+        for m in &app.recording.moves {
+            let (r, col) = (m.row as usize, m.col as usize);
+            board[r][col] = m.digit.into();
+            notes.set(r, col, m.notes);
+            center.set(r, col, m.center);
+        }
+        assert_eq!(app.recording.moves.len(), 5);
+        assert_eq!(board, app.data);
+        assert_eq!((notes, center), (app.notes, app.center));
+        assert!(notes.has(second.0, second.1, 7) && center.has(second.0, second.1, 2));
+    }
+}
@@ -0,0 +1,116 @@
+use std::{collections::BTreeSet, fmt::Write as _, time::Duration};
+
+use crate::{
+    coach::CoachSummary, difficulty::Difficulty, formats, hints::Technique, locale::format_elapsed,
+    soduko::BoardState, symbols::Symbols,
+};
+
+/// Bars of the progress sparkline, lowest first, plain ASCII so it pastes
+/// anywhere.
+const BARS: [char; 8] = ['_', '.', ',', '-', '~', '=', '*', '#'];
+
+/// How a game went, for pasting into notes or a forum post as Markdown.
+pub struct GameReport {
+    pub difficulty: Difficulty,
+    pub puzzle: BoardState,
+    pub board: BoardState,
+    /// `false` while the board isn't filled in correctly yet
+    pub solved: bool,
+    pub elapsed: Duration,
+    /// the player's fastest win on the difficulty, in seconds
+    pub best: Option<u64>,
+    pub mistakes: u32,
+    pub hints: u32,
+    pub coach: Option<CoachSummary>,
+    /// what solving the puzzle by logic takes, `None` when logic doesn't
+    pub techniques: Option<BTreeSet<Technique>>,
+    /// seconds in by which each tenth of the empty cells was filled
+    pub curve: Vec<u32>,
+    pub symbols: Symbols,
+}
+
+impl GameReport {
+    pub fn markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Sudoku, {}", self.difficulty);
+        let _ = writeln!(out);
+        let outcome = match self.solved {
+            true => "solved in",
+            false => "unsolved after",
+        };
+        let _ = writeln!(
+            out,
+            "`{}` {outcome} {}.",
+            formats::portable_code(&self.puzzle),
+            format_elapsed(self.elapsed)
+        );
+        let _ = writeln!(out);
+        let _ = writeln!(out, "| | |");
+        let _ = writeln!(out, "|---|---|");
+        let _ = writeln!(out, "| time | {} |", format_elapsed(self.elapsed));
+        let target = Duration::from_secs(self.difficulty.target_seconds());
+        let _ = writeln!(out, "| target | {} |", format_elapsed(target));
+        if let Some(best) = self.best {
+            let best = format_elapsed(Duration::from_secs(best));
+            let _ = writeln!(out, "| best on {} | {best} |", self.difficulty);
+        }
+        let _ = writeln!(out, "| mistakes | {} |", self.mistakes);
+        let _ = writeln!(out, "| hints | {} |", self.hints);
+        if let Some(coach) = self.coach {
+            let _ = writeln!(out, "| placements | {coach} |");
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## Techniques");
+        let _ = writeln!(out);
+        match &self.techniques {
+            Some(techniques) => {
+                for technique in techniques {
+                    let _ = writeln!(out, "- {}", technique.name());
+                }
+            }
+            None => {
+                let _ = writeln!(
+                    out,
+                    "More than the hints know, logic alone doesn't solve it."
+                );
+            }
+        }
+        if !self.curve.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "## Progress");
+            let _ = writeln!(out);
+            let _ = writeln!(out, "Time spent on each tenth of the empty cells:");
+            let _ = writeln!(out);
+            let _ = writeln!(out, "```");
+            let _ = writeln!(out, "{}", sparkline(&self.curve));
+            let _ = writeln!(out, "```");
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## Final board");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "```");
+        out.push_str(&formats::grid(&self.board, &self.symbols));
+        let _ = writeln!(out, "```");
+        out
+    }
+}
+
+/// The time each tenth took, one bar each, with the longest beside it.
+fn sparkline(curve: &[u32]) -> String {
+    let mut before = 0;
+    let spans: Vec<u32> = curve
+        .iter()
+        .map(|&at| {
+            let span = at.saturating_sub(before);
+            before = at;
+            span
+        })
+        .collect();
+    let longest = spans.iter().copied().max().unwrap_or(0).max(1);
+    let bars: String = spans
+        .iter()
+        .map(|&span| BARS[(span as usize * (BARS.len() - 1)).div_ceil(longest as usize)])
+        .collect();
+    let longest = format_elapsed(Duration::from_secs(u64::from(longest)));
+    format!("{bars}  longest {longest}")
+}
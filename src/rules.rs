@@ -0,0 +1,154 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{keymap::Action, notes::NoteConflicts, soduko::BoardState};
+
+/// Ways to play, differing in which assists are allowed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    #[default]
+    Casual,
+    /// the day's puzzle, the same for everyone, no hints
+    Daily,
+    /// games in a row, no hints and no taking moves back
+    Streak,
+    /// against the clock, no assists at all
+    Blitz,
+    /// contest conditions: no hints or checking, and the answer only
+    /// counts once it's submitted
+    Competition,
+}
+
+impl GameMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Casual => "casual",
+            GameMode::Daily => "daily",
+            GameMode::Streak => "streak",
+            GameMode::Blitz => "blitz",
+            GameMode::Competition => "competition",
+        }
+    }
+
+    pub fn rules(self) -> RuleSet {
+        match self {
+            GameMode::Casual => RuleSet::default(),
+            GameMode::Daily => RuleSet {
+                hints: false,
+                ..RuleSet::default()
+            },
+            GameMode::Streak => RuleSet {
+                undo: false,
+                hints: false,
+                ..RuleSet::default()
+            },
+            GameMode::Blitz => RuleSet {
+                undo: false,
+                hints: false,
+                conflict_highlighting: false,
+                ..RuleSet::default()
+            },
+            GameMode::Competition => RuleSet {
+                hints: false,
+                conflict_highlighting: false,
+                submit: true,
+                ..RuleSet::default()
+            },
+        }
+    }
+}
+
+/// What filling in the last cell does, the `completion` config option.
+/// Competition games always wait for a submit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Completion {
+    /// the game ends as soon as the board is full and right
+    #[default]
+    Auto,
+    /// the game ends on submitting the answer, which says whether it's
+    /// right
+    Manual,
+}
+
+/// Assists a game mode allows. The action dispatcher asks here before
+/// running an action, so modes never need checks of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleSet {
+    pub undo: bool,
+    /// reveal, preview, solve and the coach
+    pub hints: bool,
+    /// checking the board and flashing conflicts
+    pub conflict_highlighting: bool,
+    /// the game ends on a submitted answer instead of as soon as the board
+    /// is full
+    pub submit: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            undo: true,
+            hints: true,
+            conflict_highlighting: true,
+            submit: false,
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn allows(&self, action: Action) -> bool {
+        (action.info().available)(self)
+    }
+
+    /// The cells to draw as conflicting, none when the mode keeps that to
+    /// itself or the player `hidden` them.
+    pub fn conflicts(&self, board: &BoardState, hidden: bool) -> [[bool; 9]; 9] {
+        match self.conflict_highlighting && !hidden {
+            true => board.conflicts(),
+            false => [[false; 9]; 9],
+        }
+    }
+
+    /// Whether the digit just written at (row, col) flashes as a conflict.
+    pub fn flashes(&self, board: &BoardState, row: usize, col: usize) -> bool {
+        self.conflict_highlighting && board.has_conflict(row, col)
+    }
+
+    /// Whether a note for `d` at (row, col) is turned away, for a digit the
+    /// board already rules out there.
+    pub fn refuses_note(
+        &self,
+        board: &BoardState,
+        (row, col): (usize, usize),
+        d: u8,
+        note_conflicts: NoteConflicts,
+    ) -> bool {
+        note_conflicts == NoteConflicts::Prevent
+            && self.conflict_highlighting
+            && board.candidates(row, col) & (1 << d) == 0
+    }
+
+    /// The notes among `marks` at (row, col) to draw flagged, those for
+    /// digits the board rules out there.
+    pub fn flagged_notes(
+        &self,
+        board: &BoardState,
+        (row, col): (usize, usize),
+        marks: u16,
+        note_conflicts: NoteConflicts,
+    ) -> u16 {
+        match note_conflicts {
+            NoteConflicts::Flag if self.conflict_highlighting => {
+                marks & !board.candidates(row, col) as u16
+            }
+            _ => 0,
+        }
+    }
+
+    /// Whether a wrong submission points out the cells that are wrong.
+    pub fn points_out_mistakes(&self) -> bool {
+        self.conflict_highlighting
+    }
+}
@@ -0,0 +1,171 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+/// Long read-only text with `/` search, match highlighting and scrolling.
+#[derive(Debug, Default, Clone)]
+pub struct SearchableText {
+    lines: Vec<String>,
+    scroll: usize,
+    query: String,
+    /// true while the query is being typed
+    searching: bool,
+    /// (line, byte start, byte end) of every match
+    matches: Vec<(usize, usize, usize)>,
+    current: usize,
+}
+
+impl SearchableText {
+    pub fn new(lines: Vec<String>) -> Self {
+        Self {
+            lines,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.searching
+    }
+
+    pub fn start_search(&mut self) {
+        self.searching = true;
+        self.query.clear();
+        self.matches.clear();
+    }
+
+    /// Keys while typing the query. Enter keeps the query, Esc drops it.
+    pub fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Esc => {
+                self.searching = false;
+                self.query.clear();
+                self.matches.clear();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.find_matches();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.find_matches();
+            }
+            _ => {}
+        }
+    }
+
+    /// Case insensitive unless the query has an upper case letter, like vim's smartcase.
+    fn find_matches(&mut self) {
+        self.matches.clear();
+        self.current = 0;
+        if self.query.is_empty() {
+            return;
+        }
+        let smartcase = self.query.chars().any(char::is_uppercase);
+        let query = match smartcase {
+            true => self.query.clone(),
+            false => self.query.to_ascii_lowercase(),
+        };
+        for (i, line) in self.lines.iter().enumerate() {
+            let haystack = match smartcase {
+                true => line.clone(),
+                // only ascii is lowered so byte offsets stay valid
+                false => line.to_ascii_lowercase(),
+            };
+            self.matches.extend(
+                haystack
+                    .match_indices(&query)
+                    .map(|(start, m)| (i, start, start + m.len())),
+            );
+        }
+        self.jump();
+    }
+
+    pub fn next_match(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+            self.jump();
+        }
+    }
+
+    pub fn previous_match(&mut self) {
+        if !self.matches.is_empty() {
+            let count = self.matches.len();
+            self.current = (self.current + count - 1) % count;
+            self.jump();
+        }
+    }
+
+    fn jump(&mut self) {
+        if let Some(&(line, _, _)) = self.matches.get(self.current) {
+            self.scroll = line.saturating_sub(2);
+        }
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.lines.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+
+    fn prompt(&self) -> String {
+        if self.searching {
+            format!("/{}", self.query)
+        } else if self.query.is_empty() {
+            "/ search".to_string()
+        } else if self.matches.is_empty() {
+            format!("/{} no matches", self.query)
+        } else {
+            format!(
+                "/{} match {}/{}",
+                self.query,
+                self.current + 1,
+                self.matches.len()
+            )
+        }
+    }
+}
+
+pub struct SearchView<'a> {
+    pub text: &'a SearchableText,
+    pub style: Style,
+    pub match_style: Style,
+    pub current_style: Style,
+}
+
+impl Widget for SearchView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let text = self.text;
+        let height = area.height.saturating_sub(1) as usize;
+        let mut lines: Vec<Line> = Vec::new();
+        for (i, line) in text.lines.iter().enumerate().skip(text.scroll).take(height) {
+            let mut spans = Vec::new();
+            let mut pos = 0;
+            for (m, &(_, start, end)) in text
+                .matches
+                .iter()
+                .enumerate()
+                .filter(|(_, (l, _, _))| *l == i)
+            {
+                spans.push(Span::raw(&line[pos..start]));
+                let style = match m == text.current {
+                    true => self.current_style,
+                    false => self.match_style,
+                };
+                spans.push(Span::styled(&line[start..end], style));
+                pos = end;
+            }
+            spans.push(Span::raw(&line[pos..]));
+            lines.push(Line::from(spans));
+        }
+        while lines.len() < height {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(text.prompt()));
+        Paragraph::new(lines).style(self.style).render(area, buf);
+    }
+}
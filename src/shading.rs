@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+/// Which cells get the alternate background color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shading {
+    /// every other row
+    #[default]
+    Rows,
+    /// checkerboard of 3x3 boxes
+    Boxes,
+    Plain,
+}
+
+impl Shading {
+    pub const ALL: [Shading; 3] = [Shading::Rows, Shading::Boxes, Shading::Plain];
+
+    /// The name in the config.
+    pub fn label(self) -> &'static str {
+        match self {
+            Shading::Rows => "rows",
+            Shading::Boxes => "boxes",
+            Shading::Plain => "plain",
+        }
+    }
+
+    pub fn is_alt(self, row: usize, col: usize) -> bool {
+        match self {
+            Shading::Rows => row % 2 == 1,
+            Shading::Boxes => (row / 3 + col / 3) % 2 == 1,
+            Shading::Plain => false,
+        }
+    }
+}
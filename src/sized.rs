@@ -0,0 +1,157 @@
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+use crate::{cursor::Cursor, generator, rng::Rng, soduko::BoardState};
+
+/// Boards other than 9x9. They're played on a screen of their own, without
+/// the notes, hints and stats of a normal game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BoardSize {
+    /// 2x3 boxes
+    #[default]
+    #[value(name = "6")]
+    Six,
+    /// 4x4 boxes, with A-G for 10-16
+    #[value(name = "16")]
+    Sixteen,
+}
+
+impl BoardSize {
+    pub fn label(self) -> &'static str {
+        match self {
+            BoardSize::Six => "6x6",
+            BoardSize::Sixteen => "16x16",
+        }
+    }
+
+    /// The other size, for flipping between them in the menu.
+    pub fn other(self) -> Self {
+        match self {
+            BoardSize::Six => BoardSize::Sixteen,
+            BoardSize::Sixteen => BoardSize::Six,
+        }
+    }
+
+    /// A new puzzle of this size. Digging a 16x16 one takes a while, this
+    /// is for a [`Worker`] to do.
+    ///
+    /// [`Worker`]: crate::worker::Worker
+    pub fn puzzle(self, rng: &mut Rng) -> SizedPuzzle {
+        match self {
+            BoardSize::Six => SizedPuzzle::Six(generator::generate_sized(rng)),
+            BoardSize::Sixteen => SizedPuzzle::Sixteen(Box::new(generator::generate_sized(rng))),
+        }
+    }
+}
+
+/// A puzzle of either size, not played yet.
+#[derive(Debug, Clone)]
+pub enum SizedPuzzle {
+    Six(BoardState<6>),
+    Sixteen(Box<BoardState<16>>),
+}
+
+/// A [`SizedGame`] of either size.
+pub enum SizedBoard {
+    Six(SizedGame<6>),
+    Sixteen(Box<SizedGame<16>>),
+}
+
+impl SizedBoard {
+    pub fn new(puzzle: SizedPuzzle) -> Self {
+        match puzzle {
+            SizedPuzzle::Six(puzzle) => SizedBoard::Six(SizedGame::new(puzzle)),
+            SizedPuzzle::Sixteen(puzzle) => SizedBoard::Sixteen(Box::new(SizedGame::new(*puzzle))),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<SizedEvent> {
+        match self {
+            SizedBoard::Six(game) => game.handle_key(key),
+            SizedBoard::Sixteen(game) => game.handle_key(key),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizedEvent {
+    /// the board is filled in correctly, in this time
+    Solved(Duration),
+    Leave,
+}
+
+/// A game on an `N` by `N` board. Values past 9 are typed as the letters
+/// A-G, which none of the movement keys use.
+pub struct SizedGame<const N: usize> {
+    pub puzzle: BoardState<N>,
+    pub board: BoardState<N>,
+    pub cursor: Cursor,
+    started: Instant,
+    /// how long it took, once solved
+    pub solved: Option<Duration>,
+}
+
+impl<const N: usize> SizedGame<N> {
+    pub fn new(puzzle: BoardState<N>) -> Self {
+        Self {
+            puzzle,
+            board: puzzle,
+            cursor: Cursor::sized(N),
+            started: Instant::now(),
+            solved: None,
+        }
+    }
+
+    /// Time since the game started, stopped once it's solved.
+    pub fn elapsed(&self) -> Duration {
+        self.solved.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// What the keys are, for drawing under the board.
+    pub fn key_hint(&self) -> &'static str {
+        match N {
+            6 => "arrows/hjkl move  Tab next empty  1-6 write  x erase  Esc leave",
+            _ => "arrows/hjkl move  Tab next empty  1-9 A-G write  x erase  Esc leave",
+        }
+    }
+
+    /// Cells filled, out of how many the puzzle left empty.
+    pub fn progress(&self) -> (usize, usize) {
+        let clues = self.puzzle.clue_count();
+        (self.board.clue_count() - clues, N * N - clues)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<SizedEvent> {
+        let (row, col) = self.cursor.cell();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Some(SizedEvent::Leave),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor.previous_row(),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor.next_row(),
+            KeyCode::Left | KeyCode::Char('h') => self.cursor.previous_column(),
+            KeyCode::Right | KeyCode::Char('l') => self.cursor.next_column(),
+            KeyCode::Tab | KeyCode::BackTab => {
+                let board = &self.board;
+                let forward = key.code == KeyCode::Tab;
+                self.cursor.jump(forward, |r, col| board[r][col].is_none());
+            }
+            // the givens stay put, and so does a solved board
+            _ if self.puzzle[row][col].is_some() || self.solved.is_some() => {}
+            KeyCode::Char('0' | 'x') | KeyCode::Backspace | KeyCode::Delete => {
+                self.board[row][col] = 0.into();
+            }
+            KeyCode::Char(c) => {
+                let d = c.to_digit(17).filter(|&d| d >= 1 && d as usize <= N)?;
+                self.board[row][col] = (d as u8).into();
+                if self.board.clue_count() == N * N && self.board.check() {
+                    let time = self.started.elapsed();
+                    self.solved = Some(time);
+                    return Some(SizedEvent::Solved(time));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
@@ -2,11 +2,14 @@ use std::{
     fmt::{Display, Formatter},
     num::NonZeroU8,
     ops::{Deref, DerefMut},
+    str::FromStr,
 };
 
-use itertools::Itertools;
+use color_eyre::eyre::{Report, bail};
 
-#[derive(Default, Debug, Clone, Copy)]
+use crate::{solver::Solver, variant::Variant};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CellState(Option<NonZeroU8>);
 impl Deref for CellState {
     type Target = Option<NonZeroU8>;
@@ -26,89 +29,217 @@ impl From<u8> for CellState {
     fn from(value: u8) -> Self {
         match value {
             0 => Self(None),
-            v @ 1..=9 => Self(NonZeroU8::new(v)),
-            10.. => panic!("max value in soduku is 9"),
+            v @ 1..=16 => Self(NonZeroU8::new(v)),
+            17.. => panic!("max value in soduku is 16"),
         }
     }
 }
 
+/// 10-16 are the letters A-G, for 16x16 boards.
 impl Display for CellState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.0 {
-            Some(v) => write!(f, "{}", v),
+        match self.0.and_then(|v| char::from_digit(v.get().into(), 17)) {
+            Some(v) => write!(f, "{}", v.to_ascii_uppercase()),
             None => write!(f, "·"),
         }
     }
 }
 
-type Soduko9 = [CellState; 9];
+/// What a cell holds from the player's side: a clue of the puzzle, a value
+/// they entered, pencil marks or nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    Given,
+    Entered,
+    Note,
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum House {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+    /// 0 runs from the top left corner, 1 from the top right
+    Diagonal(usize),
+    /// the extra boxes of a hyper sudoku, in reading order
+    Window(usize),
+}
+
+/// Rows and columns of a box on a board `size` cells across: square where
+/// the size is, 2x3 on a 6x6 board.
+pub const fn box_dims(size: usize) -> (usize, usize) {
+    let rows = size.isqrt();
+    (rows, size / rows)
+}
+
+impl House {
+    /// The houses of a 9x9 board.
+    pub fn all() -> impl Iterator<Item = House> {
+        Self::all_in(9)
+    }
+
+    /// The rows, columns and boxes of a board `size` cells across.
+    pub fn all_in(size: usize) -> impl Iterator<Item = House> {
+        (0..size)
+            .map(House::Row)
+            .chain((0..size).map(House::Column))
+            .chain((0..size).map(House::Box))
+    }
+
+    /// The cells of the house on a 9x9 board, where every house fits.
+    pub fn cells(self) -> impl Iterator<Item = (usize, usize)> {
+        self.walk(9)
+    }
+
+    /// The cells of the house on a board `size` cells across, see
+    /// [`Self::fits`].
+    pub fn cells_in(self, size: usize) -> Result<impl Iterator<Item = (usize, usize)>, Report> {
+        self.fits(size)?;
+        Ok(self.walk(size))
+    }
+
+    /// The houses of the variants are laid out for 9x9 boards and fit no
+    /// other size, see [`Variant::region_mask`]. Rows, columns and boxes fit
+    /// any board.
+    pub fn fits(self, size: usize) -> Result<(), Report> {
+        if let House::Diagonal(_) | House::Window(_) = self
+            && size != 9
+        {
+            bail!("{self} only fits on a 9x9 board, not a {size}x{size} one");
+        }
+        Ok(())
+    }
+
+    fn walk(self, size: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (rows, cols) = box_dims(size);
+        let stacks = size / cols;
+        (0..size).map(move |i| match self {
+            House::Row(r) => (r, i),
+            House::Column(c) => (i, c),
+            House::Box(b) => (b / stacks * rows + i / cols, b % stacks * cols + i % cols),
+            House::Diagonal(0) => (i, i),
+            House::Diagonal(_) => (i, 8 - i),
+            House::Window(w) => (w / 2 * 4 + 1 + i / 3, w % 2 * 4 + 1 + i % 3),
+        })
+    }
+
+    pub fn contains(self, row: usize, col: usize) -> bool {
+        self.cells().any(|cell| cell == (row, col))
+    }
+}
+
+impl Display for House {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            House::Row(r) => write!(f, "row {}", r + 1),
+            House::Column(c) => write!(f, "column {}", c + 1),
+            House::Box(b) => write!(f, "box {}", b + 1),
+            House::Diagonal(d) => write!(f, "diagonal {}", d + 1),
+            House::Window(w) => write!(f, "window {}", w + 1),
+        }
+    }
+}
+
+/// Why a partially filled board can no longer be completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadEnd {
+    Conflict,
+    NoCandidates { row: usize, col: usize },
+    NoPlaceFor { digit: u8, house: House },
+}
+
+impl Display for DeadEnd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeadEnd::Conflict => write!(f, "a digit is repeated"),
+            DeadEnd::NoCandidates { row, col } => {
+                write!(f, "r{}c{} has no candidates left", row + 1, col + 1)
+            }
+            DeadEnd::NoPlaceFor { digit, house } => {
+                write!(f, "{digit} no longer fits anywhere in {house}")
+            }
+        }
+    }
+}
+
+/// The cells of an `N` by `N` board, and the variant whose houses they're
+/// checked against. Boards are 9x9 unless said otherwise, 6x6 and 16x16
+/// ones have no variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardState<const N: usize = 9> {
+    cells: [[CellState; N]; N],
+    variant: Variant,
+}
 
-#[derive(Default, Debug, Clone, Copy)]
-pub struct BoardState([Soduko9; 9]);
+impl<const N: usize> Default for BoardState<N> {
+    fn default() -> Self {
+        Self {
+            cells: [[CellState::default(); N]; N],
+            variant: Variant::Classic,
+        }
+    }
+}
 
-impl Deref for BoardState {
-    type Target = [Soduko9; 9];
+impl<const N: usize> Deref for BoardState<N> {
+    type Target = [[CellState; N]; N];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.cells
     }
 }
 
-impl DerefMut for BoardState {
+impl<const N: usize> DerefMut for BoardState<N> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.cells
     }
 }
 
 fn unique(data: &[CellState]) -> bool {
-    for n in (1..=9).map(NonZeroU8::new) {
-        if data.iter().filter(|v| ***v == n).count() > 1 {
+    let mut seen = 0u32;
+    for n in data.iter().filter_map(|cell| cell.0) {
+        if seen & (1 << n.get()) != 0 {
             return false;
         }
+        seen |= 1 << n.get();
     }
     true
 }
 
-impl BoardState {
-    fn square(&self, row: usize, col: usize) -> Soduko9 {
-        let rows = row..(row + 3);
-        let data: Vec<_> = rows
-            .flat_map(|row| {
-                let row = self.0[row];
-                row.into_iter().skip(col).take(3)
-            })
-            .collect();
-        data.try_into().unwrap()
-    }
-    fn check_boxes(&self) -> bool {
-        for row in 0..3 {
-            for col in 0..3 {
-                let data = self.square(row * 3, col * 3);
-                if !unique(&data) {
-                    return false;
-                }
-            }
+impl<const N: usize> BoardState<N> {
+    /// Bitmask of every digit the board takes, bit `d` for digit `d`.
+    pub const ALL: u32 = (1 << (N + 1)) - 2;
+
+    /// The same cells under the rules of `variant`, an error when one of
+    /// its houses doesn't fit the board.
+    pub fn with_variant(mut self, variant: Variant) -> Result<Self, Report> {
+        for house in variant.regions() {
+            house.fits(N)?;
         }
-        true
+        self.variant = variant;
+        Ok(self)
     }
 
-    fn column(&self, column: usize) -> Soduko9 {
-        (0..9)
-            .map(|row| self.0[row][column])
-            .collect_array()
-            .unwrap()
-    }
-    fn check_columns(&self) -> bool {
-        (0..9).all(|col| unique(&self.column(col)))
+    pub fn check(&self) -> bool {
+        self.houses().all(|house| {
+            house.cells_in(N).is_ok_and(|cells| {
+                let data: Vec<_> = cells.map(|(r, c)| self.cells[r][c]).collect();
+                unique(&data)
+            })
+        })
     }
-    fn check_rows(&self) -> bool {
-        (0..9).all(|i| unique(&self.0[i]))
+
+    pub fn variant(&self) -> Variant {
+        self.variant
     }
-    pub fn check(&self) -> bool {
-        self.check_rows() && self.check_columns() && self.check_boxes()
+
+    /// Rows, columns and boxes, then the variant's houses.
+    pub fn houses(&self) -> impl Iterator<Item = House> {
+        House::all_in(N).chain(self.variant.regions().iter().copied())
     }
+
     fn next_cell(&self) -> Option<usize> {
-        self.0
+        self.cells
             .iter()
             .flatten()
             .enumerate()
@@ -118,44 +249,210 @@ impl BoardState {
             })
             .next()
     }
+    /// True if the digit at (row, col) is repeated in one of its houses.
+    pub fn has_conflict(&self, row: usize, col: usize) -> bool {
+        let Some(n) = self.cells[row][col].0 else {
+            return false;
+        };
+        self.house_cells(row, col)
+            .any(|(r, c)| (r, c) != (row, col) && self.cells[r][c].0 == Some(n))
+    }
+
+    /// Every cell whose digit is repeated in one of its houses.
+    pub fn conflicts(&self) -> [[bool; N]; N] {
+        let mut conflicts = [[false; N]; N];
+        for (row, line) in conflicts.iter_mut().enumerate() {
+            for (col, conflict) in line.iter_mut().enumerate() {
+                *conflict = self.has_conflict(row, col);
+            }
+        }
+        conflicts
+    }
+
+    /// Cells of the row, column and box through (row, col), in that order,
+    /// then those of the variant's houses through it. The cell itself shows
+    /// up once per house.
+    fn house_cells(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (box_rows, box_cols) = box_dims(N);
+        let (box_row, box_col) = (row / box_rows * box_rows, col / box_cols * box_cols);
+        let row_cells = (0..N).map(move |c| (row, c));
+        let col_cells = (0..N).map(move |r| (r, col));
+        let box_cells = (0..N).map(move |i| (box_row + i / box_cols, box_col + i % box_cols));
+        let region_cells = self
+            .variant
+            .regions()
+            .iter()
+            .filter(move |house| house.contains(row, col))
+            .flat_map(|house| house.cells());
+        row_cells
+            .chain(col_cells)
+            .chain(box_cells)
+            .chain(region_cells)
+    }
+
+    /// Digits not yet placed in the houses of (row, col), as a bitmask with
+    /// bit `d` set for digit `d`. The cell's own digit is ignored.
+    pub fn candidates(&self, row: usize, col: usize) -> u32 {
+        let mut mask = Self::ALL;
+        for (r, c) in self.house_cells(row, col) {
+            if (r, c) != (row, col)
+                && let Some(n) = self.cells[r][c].0
+            {
+                mask &= !(1 << n.get());
+            }
+        }
+        mask
+    }
+
+    /// Finds a reason the board can't be completed, without searching: a
+    /// conflict, an empty cell without candidates, or a digit missing from a
+    /// house with no empty cell left that could take it.
+    pub fn dead_end(&self) -> Option<DeadEnd> {
+        if !self.check() {
+            return Some(DeadEnd::Conflict);
+        }
+        let mut candidates = [[0u32; N]; N];
+        for (row, col) in (0..N * N).map(|i| (i / N, i % N)) {
+            if self.cells[row][col].is_none() {
+                candidates[row][col] = self.candidates(row, col);
+                if candidates[row][col] == 0 {
+                    return Some(DeadEnd::NoCandidates { row, col });
+                }
+            }
+        }
+        for house in self.houses() {
+            let mut placed = 0u32;
+            let mut possible = 0u32;
+            let Ok(cells) = house.cells_in(N) else {
+                return Some(DeadEnd::Conflict);
+            };
+            for (r, c) in cells {
+                match self.cells[r][c].0 {
+                    Some(n) => placed |= 1 << n.get(),
+                    None => possible |= candidates[r][c],
+                }
+            }
+            if let Some(digit) = (1..=N as u8).find(|d| (placed | possible) & (1 << d) == 0) {
+                return Some(DeadEnd::NoPlaceFor { digit, house });
+            }
+        }
+        None
+    }
+
+    pub fn is_dead_end(&self) -> bool {
+        self.dead_end().is_some()
+    }
+
+    /// Cells of every house through (row, col) that is filled without conflicts.
+    pub fn completed_houses(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let cells: Vec<_> = self.house_cells(row, col).collect();
+        cells
+            .chunks(N)
+            .filter(|house| {
+                let data: Vec<_> = house.iter().map(|&(r, c)| self.cells[r][c]).collect();
+                data.iter().all(|cell| cell.is_some()) && unique(&data)
+            })
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// The kind of (row, col) on `board`, with `self` being the puzzle it
+    /// started from.
+    pub fn kind(&self, board: &Self, row: usize, col: usize, has_notes: bool) -> CellKind {
+        match (*self[row][col], *board[row][col]) {
+            (Some(_), _) => CellKind::Given,
+            (None, Some(_)) => CellKind::Entered,
+            (None, None) if has_notes => CellKind::Note,
+            (None, None) => CellKind::Empty,
+        }
+    }
+
     pub fn set(&mut self, row: u8, col: u8, n: CellState) {
-        self.0[row as usize][col as usize] = n;
+        self.cells[row as usize][col as usize] = n;
     }
 
     pub fn set_pos(&mut self, pos: usize, n: CellState) {
-        let row = pos / 9;
-        let col = pos % 9;
-        self.0[row][col] = n;
+        let row = pos / N;
+        let col = pos % N;
+        self.cells[row][col] = n;
     }
 
-    pub fn solve(mut self) -> Option<Self> {
-        if !self.check() {
+    /// The first solution found, `None` if there is none.
+    pub fn solve(self) -> Option<Self> {
+        Solver::new(&self)?.solve()
+    }
+
+    /// Solves by plain backtracking in reading order, adding the number of
+    /// search nodes visited to `nodes`. Slower than [`Self::solve`], but
+    /// the node count is what puzzle ratings are measured in.
+    pub fn solve_counting(self, nodes: &mut u64) -> Option<Self> {
+        self.search(nodes, u64::MAX)
+    }
+
+    /// The search nodes [`Self::solve_counting`] visits, counting no
+    /// further than `cap`, a puzzle that needs more is at least that hard.
+    pub fn nodes_up_to(self, cap: u64) -> u64 {
+        let mut nodes = 0;
+        self.search(&mut nodes, cap);
+        nodes.min(cap)
+    }
+
+    /// Backtracking for [`Self::solve_counting`], giving up once `nodes`
+    /// reaches `cap`.
+    fn search(mut self, nodes: &mut u64, cap: u64) -> Option<Self> {
+        *nodes += 1;
+        if *nodes >= cap || self.is_dead_end() {
             return None;
         }
         let Some(next_cell) = self.next_cell() else {
             return Some(self);
         };
-        for number in 1..=9 {
+        for number in 1..=N as u8 {
             self.set_pos(next_cell, number.into());
-            if let Some(solution) = self.solve() {
+            if let Some(solution) = self.search(nodes, cap) {
                 return Some(solution);
             }
         }
         None
     }
+
+    /// Solutions found, stopping at `limit`. A limit of 2 is enough to tell
+    /// a proper puzzle from one with several answers.
+    pub fn count_solutions(self, limit: usize) -> usize {
+        Solver::new(&self).map_or(0, |solver| solver.count(limit))
+    }
+
+    pub fn clue_count(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|cell| cell.is_some())
+            .count()
+    }
     pub fn solvable(&self) -> bool {
         self.solve().is_some()
     }
 }
 
-impl Display for BoardState {
+impl BoardState {
+    /// An empty 9x9 board of `variant`, which every variant fits.
+    pub fn empty(variant: Variant) -> Self {
+        Self {
+            variant,
+            ..Self::default()
+        }
+    }
+}
+
+impl<const N: usize> Display for BoardState<N> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let div = "-------------------------------------";
-        for row in self.0 {
+        let div = "-".repeat(4 * N + 1);
+        for row in self.cells {
             writeln!(f, "{div}")?;
             for num in row {
                 let x = match num.0 {
-                    Some(v) => format!("{v}"),
+                    Some(_) => format!("{num}"),
                     None => "-".into(),
                 };
                 write!(f, "| {x} ")?;
@@ -165,3 +462,56 @@ impl Display for BoardState {
         writeln!(f, "{div}")
     }
 }
+
+/// The common 81 character format, row by row, with `.` or `0` for blanks.
+/// Whitespace is ignored so grids split over several lines parse too.
+/// Other sizes take `N * N` cells, with A-G for 10-16.
+impl<const N: usize> FromStr for BoardState<N> {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if cells.len() != N * N {
+            bail!("expected {} cells, found {}", N * N, cells.len());
+        }
+        let mut board = Self::default();
+        for (pos, c) in cells.into_iter().enumerate() {
+            let n = match c {
+                '.' | '0' => 0,
+                c => match c.to_digit(17) {
+                    Some(d) if d as usize <= N => d as u8,
+                    _ => bail!("unexpected {c:?} at cell {}", pos + 1),
+                },
+            };
+            board.set_pos(pos, n.into());
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_houses_only_fit_nine_by_nine() {
+        assert!(House::Window(0).cells_in(16).is_err());
+        assert!(House::Diagonal(1).cells_in(6).is_err());
+        assert!(House::Box(3).cells_in(16).is_ok());
+        assert!(
+            BoardState::<6>::default()
+                .with_variant(Variant::Hyper)
+                .is_err()
+        );
+        assert!(
+            BoardState::<16>::default()
+                .with_variant(Variant::X)
+                .is_err()
+        );
+        assert!(
+            BoardState::<9>::default()
+                .with_variant(Variant::Hyper)
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{
+    soduko::{BoardState, House, box_dims},
+    variant::Variant,
+};
+
+/// A board with the digits placed in every row, column and box kept as
+/// bitmasks, bit `d` for digit `d`, so a cell's candidates are a few ORs
+/// away instead of a scan over its houses. Small enough to copy at every
+/// branch.
+#[derive(Debug, Clone, Copy)]
+pub struct Solver<const N: usize = 9> {
+    cells: [[u8; N]; N],
+    rows: [u32; N],
+    cols: [u32; N],
+    boxes: [u32; N],
+    variant: Variant,
+    /// the variant's own houses, see [`Variant::regions`]
+    regions: [u32; 4],
+}
+
+impl<const N: usize> Solver<N> {
+    const ALL: u32 = BoardState::<N>::ALL;
+
+    fn box_of(pos: usize) -> usize {
+        let (rows, cols) = box_dims(N);
+        pos / N / rows * (N / cols) + pos % N / cols
+    }
+
+    /// `None` when a digit is repeated in a house.
+    pub fn new(board: &BoardState<N>) -> Option<Self> {
+        let mut solver = Self {
+            cells: [[0; N]; N],
+            rows: [0; N],
+            cols: [0; N],
+            boxes: [0; N],
+            variant: board.variant(),
+            regions: [0; 4],
+        };
+        for pos in 0..N * N {
+            if let Some(d) = *board[pos / N][pos % N] {
+                let d = d.get();
+                if solver.candidates(pos) & (1 << d) == 0 {
+                    return None;
+                }
+                solver.place(pos, d);
+            }
+        }
+        Some(solver)
+    }
+
+    fn candidates(&self, pos: usize) -> u32 {
+        let mut used = self.rows[pos / N] | self.cols[pos % N] | self.boxes[Self::box_of(pos)];
+        let mut regions = self.variant.region_mask(pos / N, pos % N);
+        while regions != 0 {
+            used |= self.regions[regions.trailing_zeros() as usize];
+            regions &= regions - 1;
+        }
+        !used & Self::ALL
+    }
+
+    fn place(&mut self, pos: usize, d: u8) {
+        let bit = 1 << d;
+        self.cells[pos / N][pos % N] = d;
+        self.rows[pos / N] |= bit;
+        self.cols[pos % N] |= bit;
+        self.boxes[Self::box_of(pos)] |= bit;
+        let mut regions = self.variant.region_mask(pos / N, pos % N);
+        while regions != 0 {
+            self.regions[regions.trailing_zeros() as usize] |= bit;
+            regions &= regions - 1;
+        }
+    }
+
+    /// Places naked singles, then hidden singles, until neither is left,
+    /// and picks the empty cell with the fewest candidates to branch on.
+    /// Returns `None` for a contradiction and `Some(None)` for a full board.
+    fn propagate(&mut self) -> Option<Option<(usize, u32)>> {
+        loop {
+            let mut forced = false;
+            let mut branch: Option<(usize, u32)> = None;
+            for pos in 0..N * N {
+                if self.cells[pos / N][pos % N] != 0 {
+                    continue;
+                }
+                let candidates = self.candidates(pos);
+                match candidates.count_ones() {
+                    0 => return None,
+                    1 => {
+                        self.place(pos, candidates.trailing_zeros() as u8);
+                        forced = true;
+                    }
+                    n if branch.is_none_or(|(_, best)| n < best.count_ones()) => {
+                        branch = Some((pos, candidates));
+                    }
+                    _ => {}
+                }
+            }
+            if branch.is_none() {
+                // nothing left to branch on, so every cell is filled
+                return Some(None);
+            }
+            if !forced {
+                forced = self.hidden_singles()?;
+            }
+            if !forced {
+                return Some(branch);
+            }
+        }
+    }
+
+    /// Places every digit that fits only one cell of a house. `None` when a
+    /// digit fits nowhere in a house.
+    fn hidden_singles(&mut self) -> Option<bool> {
+        let mut forced = false;
+        let houses = House::all_in(N).chain(self.variant.regions().iter().copied());
+        for house in houses {
+            let (mut placed, mut once, mut twice) = (0u32, 0u32, 0u32);
+            for (r, c) in house.cells_in(N).ok()? {
+                let pos = r * N + c;
+                match self.cells[r][c] {
+                    0 => {
+                        let candidates = self.candidates(pos);
+                        twice |= once & candidates;
+                        once |= candidates;
+                    }
+                    d => placed |= 1 << d,
+                }
+            }
+            if (placed | once) != Self::ALL {
+                return None;
+            }
+            let singles = once & !twice;
+            if singles == 0 {
+                continue;
+            }
+            for (r, c) in house.cells_in(N).ok()? {
+                let pos = r * N + c;
+                if self.cells[r][c] != 0 {
+                    continue;
+                }
+                let single = self.candidates(pos) & singles;
+                match single.count_ones() {
+                    0 => {}
+                    1 => {
+                        self.place(pos, single.trailing_zeros() as u8);
+                        forced = true;
+                    }
+                    // two digits that each only fit this one cell
+                    _ => return None,
+                }
+            }
+        }
+        Some(forced)
+    }
+
+    pub fn solve(mut self) -> Option<BoardState<N>> {
+        let Some((pos, candidates)) = self.propagate()? else {
+            return self.board();
+        };
+        (1..=N as u8)
+            .filter(|d| candidates & (1 << d) != 0)
+            .find_map(|d| {
+                let mut next = self;
+                next.place(pos, d);
+                next.solve()
+            })
+    }
+
+    /// Solutions found, stopping at `limit`.
+    pub fn count(mut self, limit: usize) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        let Some(branch) = self.propagate() else {
+            return 0;
+        };
+        let Some((pos, candidates)) = branch else {
+            return 1;
+        };
+        let mut count = 0;
+        for d in (1..=N as u8).filter(|d| candidates & (1 << d) != 0) {
+            let mut next = self;
+            next.place(pos, d);
+            count += next.count(limit - count);
+            if count == limit {
+                break;
+            }
+        }
+        count
+    }
+
+    /// `None` only if the variant doesn't fit, which the board it came from
+    /// already checked.
+    fn board(&self) -> Option<BoardState<N>> {
+        let mut board = BoardState::<N>::default();
+        for (pos, &d) in self.cells.iter().flatten().enumerate() {
+            board.set_pos(pos, d.into());
+        }
+        board.with_variant(self.variant).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{difficulty::Difficulty, generator, rng::Rng};
+
+    const EASY: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const HARD: &str =
+        "..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9";
+
+    fn solved(puzzle: &BoardState) -> BoardState {
+        let solution = Solver::new(puzzle)
+            .and_then(Solver::solve)
+            .expect("the puzzle has a solution");
+        for (r, c) in (0..81).map(|i| (i / 9, i % 9)) {
+            if puzzle[r][c].is_some() {
+                assert_eq!(solution[r][c], puzzle[r][c], "given at ({r}, {c}) changed");
+            }
+        }
+        assert_eq!(solution.clue_count(), 81);
+        assert_eq!(solution.variant(), puzzle.variant());
+        assert!(solution.check());
+        solution
+    }
+
+    #[test]
+    fn solutions_keep_the_givens() {
+        for puzzle in [EASY, HARD] {
+            solved(&puzzle.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn counts_no_solution_to_a_conflict_and_several_to_too_few_givens() {
+        let mut conflict: BoardState = EASY.parse().unwrap();
+        // a second 5 in the top row
+        conflict.set(0, 2, 5.into());
+        assert!(Solver::new(&conflict).is_none());
+        assert_eq!(conflict.count_solutions(2), 0);
+
+        assert_eq!(BoardState::<9>::default().count_solutions(2), 2);
+        let mut ambiguous: BoardState = EASY.parse().unwrap();
+        for col in 0..9 {
+            ambiguous.set(0, col, 0.into());
+            ambiguous.set(1, col, 0.into());
+        }
+        assert!(ambiguous.count_solutions(2) >= 2);
+        assert_eq!(EASY.parse::<BoardState>().unwrap().count_solutions(2), 1);
+    }
+
+    #[test]
+    fn solves_the_variants_by_their_own_houses() {
+        for (seed, variant) in [(1, Variant::X), (2, Variant::Hyper)] {
+            let puzzle = generator::generate(Difficulty::default(), variant, &mut Rng::new(seed));
+            assert_eq!(puzzle.variant(), variant);
+            assert_eq!(puzzle.count_solutions(2), 1);
+            // checks the diagonals or the extra boxes too
+            solved(&puzzle);
+        }
+    }
+
+    #[test]
+    fn agrees_with_backtracking() {
+        let generated =
+            generator::generate(Difficulty::default(), Variant::Classic, &mut Rng::new(3));
+        for puzzle in [EASY.parse().unwrap(), generated] {
+            let mut nodes = 0;
+            assert_eq!(
+                puzzle
+                    .solve_counting(&mut nodes)
+                    .map(|board| board.to_string()),
+                Some(solved(&puzzle).to_string())
+            );
+        }
+    }
+}
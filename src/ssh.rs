@@ -0,0 +1,219 @@
+use std::{
+    io::{self, Write},
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, mpsc::Sender},
+    thread,
+    time::Duration,
+};
+
+use color_eyre::eyre::WrapErr;
+use ratatui::layout::Rect;
+use russh::{
+    Channel, ChannelId, Pty,
+    keys::{self, HashAlg, PublicKey},
+    server::{self, Auth, ChannelOpenHandle, Handle, Handler, Msg, Server, Session},
+};
+use tokio::{
+    runtime::Runtime,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+};
+
+use crate::{
+    Config,
+    events::{Event, Events},
+    remote::{self, parse_keys},
+};
+
+/// Hosts games for everyone who connects with `ssh -p <port> <host>`. Each
+/// player gets an app of their own on its own thread, with prefs, stats and
+/// a saved game kept apart by the key they log in with. Logging in takes a
+/// key since the user name alone would let anyone play on as anyone else.
+pub fn serve(listen: SocketAddr, host_key: &Path) -> color_eyre::Result<()> {
+    let key = keys::load_secret_key(host_key, None).wrap_err_with(|| {
+        format!(
+            "reading the host key {0}, make one with `ssh-keygen -t ed25519 -N '' -f {0}`",
+            host_key.display()
+        )
+    })?;
+    let ssh = server::Config {
+        keys: vec![key],
+        inactivity_timeout: Some(Duration::from_secs(60 * 60)),
+        auth_rejection_time: Duration::from_secs(1),
+        auth_rejection_time_initial: Some(Duration::ZERO),
+        nodelay: true,
+        ..Default::default()
+    };
+    let mut host = Host {
+        config: Config::load()?,
+    };
+    eprintln!("serving games on {listen}");
+    Runtime::new()?
+        .block_on(host.run_on_address(Arc::new(ssh), listen))
+        .wrap_err_with(|| format!("listening on {listen}"))
+}
+
+struct Host {
+    /// the host's config, every player plays with
+    config: Config,
+}
+
+impl Server for Host {
+    type Handler = Player;
+
+    fn new_client(&mut self, _: Option<SocketAddr>) -> Player {
+        Player {
+            config: self.config.clone(),
+            profile: String::new(),
+            size: None,
+            input: None,
+        }
+    }
+}
+
+/// One connection, from logging in until the app quits or the player goes.
+struct Player {
+    config: Config,
+    /// named after the player's key
+    profile: String,
+    /// the terminal's size, once a pty was asked for
+    size: Option<Rect>,
+    /// where keys go once the app is running
+    input: Option<Sender<io::Result<Event>>>,
+}
+
+impl Player {
+    fn send(&self, event: Event) {
+        if let Some(input) = &self.input {
+            // a game that quit just doesn't take any more
+            let _ = input.send(Ok(event));
+        }
+    }
+}
+
+impl Handler for Player {
+    type Error = russh::Error;
+
+    async fn auth_publickey(&mut self, _: &str, key: &PublicKey) -> Result<Auth, Self::Error> {
+        let fingerprint = key.fingerprint(HashAlg::Sha256);
+        self.profile = fingerprint.as_bytes()[..8]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        Ok(Auth::Accept)
+    }
+
+    async fn channel_open_session(
+        &mut self,
+        _: Channel<Msg>,
+        reply: ChannelOpenHandle,
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        reply.accept().await;
+        Ok(())
+    }
+
+    async fn pty_request(
+        &mut self,
+        channel: ChannelId,
+        _: &str,
+        width: u32,
+        height: u32,
+        _: u32,
+        _: u32,
+        _: &[(Pty, u32)],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.size = Some(Rect::new(0, 0, width as u16, height as u16));
+        session.channel_success(channel)
+    }
+
+    async fn shell_request(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let mut out = Remote::connect(session.handle(), channel);
+        let Some(size) = self.size else {
+            let _ = out.write_all(b"rudoku needs a terminal, connect with ssh -t\r\n");
+            let _ = out.flush();
+            return session.channel_success(channel);
+        };
+        let (input, events) = Events::channel();
+        self.input = Some(input);
+        let config = self.config.clone();
+        let profile = self.profile.clone();
+        thread::spawn(move || {
+            if let Err(err) = remote::play(config, &profile, out, events, size) {
+                eprintln!("game of {profile} ended: {err:#}");
+            }
+        });
+        session.channel_success(channel)
+    }
+
+    async fn data(
+        &mut self,
+        _: ChannelId,
+        data: &[u8],
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        for key in parse_keys(data) {
+            self.send(Event::Key(key));
+        }
+        Ok(())
+    }
+
+    async fn window_change_request(
+        &mut self,
+        _: ChannelId,
+        width: u32,
+        height: u32,
+        _: u32,
+        _: u32,
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.send(Event::Resize(width as u16, height as u16));
+        Ok(())
+    }
+}
+
+/// The player's end of the connection, written to like a terminal. What's
+/// written goes out on flush, once a frame, and the connection closes once
+/// this is dropped.
+struct Remote {
+    out: UnboundedSender<Vec<u8>>,
+    buf: Vec<u8>,
+}
+
+impl Remote {
+    fn connect(handle: Handle, channel: ChannelId) -> Self {
+        let (out, mut frames) = unbounded_channel::<Vec<u8>>();
+        tokio::spawn(async move {
+            while let Some(frame) = frames.recv().await {
+                if handle.data(channel, frame).await.is_err() {
+                    return;
+                }
+            }
+            let _ = handle.exit_status_request(channel, 0).await;
+            let _ = handle.eof(channel).await;
+            let _ = handle.close(channel).await;
+        });
+        Self { out, buf: vec![] }
+    }
+}
+
+impl Write for Remote {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.out
+            .send(std::mem::take(&mut self.buf))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the player left"))
+    }
+}
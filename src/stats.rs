@@ -0,0 +1,411 @@
+use std::{fs, path::PathBuf};
+
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::Curve, coach::CoachSummary, config::state_dir, difficulty::Difficulty, formats, persist,
+};
+
+/// Games looked at when suggesting a difficulty change.
+const SUGGESTION_WINDOW: usize = 5;
+/// Recent wins the bot's pace is taken from, and how many it needs.
+const CURVE_WINDOW: usize = 10;
+const CURVE_MIN: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub difficulty: Difficulty,
+    pub won: bool,
+    pub seconds: u64,
+    pub hints: u32,
+    /// placements by quality, for games played with the coach on
+    #[serde(default)]
+    pub coach: Option<CoachSummary>,
+    /// the givens as a [`formats::portable_code`], to find a puzzle again
+    #[serde(default)]
+    pub puzzle: Option<String>,
+    /// seconds into the game by which each tenth of the empty cells was
+    /// filled, for games played since this was recorded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub curve: Vec<u32>,
+    /// the player's own words about the puzzle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// the player's collections the puzzle is in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// hidden from the library until restored or purged
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// won before the event the puzzle came with ended
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub event: bool,
+}
+
+/// A puzzle brought into the library from a collection, before it's played.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryPuzzle {
+    /// the givens as a [`formats::portable_code`]
+    pub puzzle: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// when the event the puzzle came with ends, in seconds since the Unix
+    /// epoch. Cleared once it has, leaving a regular puzzle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suggestion {
+    Harder(Difficulty),
+    Easier(Difficulty),
+}
+
+impl Suggestion {
+    pub fn difficulty(self) -> Difficulty {
+        match self {
+            Suggestion::Harder(d) | Suggestion::Easier(d) => d,
+        }
+    }
+
+    pub fn message(self) -> String {
+        match self {
+            Suggestion::Harder(d) => {
+                format!("You're breezing through these. Try {d} puzzles?")
+            }
+            Suggestion::Easier(d) => {
+                format!("These have been tough lately. Try {d} puzzles for a while?")
+            }
+        }
+    }
+}
+
+/// Games on a difficulty, or on all of them, added up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub played: usize,
+    pub won: usize,
+    /// fastest win, in seconds
+    pub best: Option<u64>,
+    /// mean time of the wins, in seconds
+    pub average: Option<u64>,
+}
+
+impl Summary {
+    /// Percent of the games played that were won.
+    pub fn win_rate(&self) -> Option<u32> {
+        (self.played > 0).then(|| (self.won * 100 / self.played) as u32)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub games: Vec<GameRecord>,
+    /// number of games recorded when a suggestion was last answered, so the
+    /// player isn't asked again before a fresh window of games
+    pub suggested_at: usize,
+    /// puzzles imported into the library, played or not
+    pub library: Vec<LibraryPuzzle>,
+}
+
+impl Stats {
+    pub fn path() -> Option<PathBuf> {
+        Some(state_dir()?.join("stats.json"))
+    }
+
+    pub fn load() -> color_eyre::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text =
+            fs::read_to_string(&path).wrap_err_with(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&text).wrap_err_with(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self) -> color_eyre::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        persist::write_replacing(&path, &serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn record(&mut self, game: GameRecord) {
+        self.games.push(game);
+    }
+
+    /// Looks at the last few games on `current`: mostly quick, hint free
+    /// wins suggest going up, mostly losses or heavy hint use going down.
+    pub fn suggestion(&self, current: Difficulty) -> Option<Suggestion> {
+        if self.games.len() < self.suggested_at + SUGGESTION_WINDOW {
+            return None;
+        }
+        let recent: Vec<_> = self
+            .games
+            .iter()
+            .rev()
+            .filter(|game| game.difficulty == current)
+            .take(SUGGESTION_WINDOW)
+            .collect();
+        if recent.len() < SUGGESTION_WINDOW {
+            return None;
+        }
+        let n = recent.len() as f64;
+        let win_rate = recent.iter().filter(|g| g.won).count() as f64 / n;
+        let hints = recent.iter().map(|g| g.hints as f64).sum::<f64>() / n;
+        let mut times: Vec<u64> = recent.iter().filter(|g| g.won).map(|g| g.seconds).collect();
+        times.sort_unstable();
+        let median_time = times.get(times.len() / 2).copied();
+
+        let fast = median_time.is_some_and(|t| t <= current.target_seconds());
+        if win_rate >= 0.8 && hints < 1.0 && fast {
+            current.harder().map(Suggestion::Harder)
+        } else if win_rate < 0.4 || hints >= 3.0 {
+            current.easier().map(Suggestion::Easier)
+        } else {
+            None
+        }
+    }
+
+    /// The median progress curve of the last few wins on `difficulty`,
+    /// point by point, once there are enough of them.
+    pub fn curve(&self, difficulty: Difficulty) -> Option<Curve> {
+        let curves: Vec<Curve> = self
+            .games
+            .iter()
+            .rev()
+            .filter(|game| game.won && game.difficulty == difficulty)
+            .filter_map(|game| Curve::try_from(game.curve.as_slice()).ok())
+            .take(CURVE_WINDOW)
+            .collect();
+        if curves.len() < CURVE_MIN {
+            return None;
+        }
+        let mut median = [0; 10];
+        for (i, point) in median.iter_mut().enumerate() {
+            let mut times: Vec<u32> = curves.iter().map(|curve| curve[i]).collect();
+            times.sort_unstable();
+            *point = times[times.len() / 2];
+        }
+        Some(median)
+    }
+
+    /// Percent of past wins on `difficulty` that took longer than
+    /// `seconds`, or nothing before the first win.
+    pub fn faster_than(&self, difficulty: Difficulty, seconds: u64) -> Option<u32> {
+        let wins: Vec<u64> = self
+            .games
+            .iter()
+            .filter(|game| game.won && game.difficulty == difficulty)
+            .map(|game| game.seconds)
+            .collect();
+        if wins.is_empty() {
+            return None;
+        }
+        let slower = wins.iter().filter(|&&time| time > seconds).count();
+        Some((slower * 100 / wins.len()) as u32)
+    }
+
+    /// The latest note written about `puzzle`.
+    pub fn note(&self, puzzle: &str) -> Option<&str> {
+        self.games
+            .iter()
+            .rev()
+            .filter(|game| game.puzzle.as_deref() == Some(puzzle))
+            .find_map(|game| game.note.as_deref())
+    }
+
+    /// Puts `note` on every recorded game of `puzzle`.
+    pub fn set_note(&mut self, puzzle: &str, note: Option<String>) {
+        for game in &mut self.games {
+            if game.puzzle.as_deref() == Some(puzzle) {
+                game.note = note.clone();
+            }
+        }
+    }
+
+    /// The tags on the latest recorded game of `puzzle`, or on its import.
+    pub fn tags(&self, puzzle: &str) -> Vec<String> {
+        let game = self
+            .games
+            .iter()
+            .rev()
+            .find(|game| game.puzzle.as_deref() == Some(puzzle));
+        match game {
+            Some(game) => game.tags.clone(),
+            None => self
+                .imported(puzzle)
+                .map_or_else(Vec::new, |imported| imported.tags.clone()),
+        }
+    }
+
+    fn imported(&self, puzzle: &str) -> Option<&LibraryPuzzle> {
+        self.library
+            .iter()
+            .find(|imported| imported.puzzle == puzzle)
+    }
+
+    /// Replaces the tags on every recorded game of `puzzle` and its import.
+    pub fn set_tags(&mut self, puzzle: &str, tags: &[String]) {
+        for game in &mut self.games {
+            if game.puzzle.as_deref() == Some(puzzle) {
+                game.tags = tags.to_vec();
+            }
+        }
+        for imported in &mut self.library {
+            if imported.puzzle == puzzle {
+                imported.tags = tags.to_vec();
+            }
+        }
+    }
+
+    /// Moves every recorded game of `puzzle` and its import in or out of
+    /// the archive.
+    pub fn set_archived(&mut self, puzzle: &str, archived: bool) {
+        for game in &mut self.games {
+            if game.puzzle.as_deref() == Some(puzzle) {
+                game.archived = archived;
+            }
+        }
+        for imported in &mut self.library {
+            if imported.puzzle == puzzle {
+                imported.archived = archived;
+            }
+        }
+    }
+
+    /// Adds puzzles to the library, each with when its event ends if it
+    /// came with one, skipping ones already in it. Returns how many were
+    /// new.
+    pub fn import(&mut self, puzzles: Vec<(String, Option<u64>)>, tags: &[String]) -> usize {
+        let before = self.library.len();
+        for (puzzle, expires) in puzzles {
+            if self.imported(&puzzle).is_none() {
+                self.library.push(LibraryPuzzle {
+                    puzzle,
+                    tags: tags.to_vec(),
+                    archived: false,
+                    expires,
+                });
+            }
+        }
+        self.library.len() - before
+    }
+
+    /// When the event `puzzle` came with ends, if it hasn't by `now`.
+    pub fn event_end(&self, puzzle: &str, now: u64) -> Option<u64> {
+        self.imported(puzzle)?.expires.filter(|&end| now < end)
+    }
+
+    /// Turns the event puzzles whose event ended by `now` into regular
+    /// ones. Returns how many did.
+    pub fn expire(&mut self, now: u64) -> usize {
+        let mut expired = 0;
+        for imported in &mut self.library {
+            if imported.expires.is_some_and(|end| end <= now) {
+                imported.expires = None;
+                expired += 1;
+            }
+        }
+        expired
+    }
+
+    /// Merges library entries for the same puzzle into the first, and
+    /// brings the suggestion window back within the games recorded.
+    /// Returns how many entries went.
+    pub fn rebuild_library(&mut self) -> usize {
+        let before = self.library.len();
+        let mut kept: Vec<LibraryPuzzle> = Vec::with_capacity(before);
+        for imported in self.library.drain(..) {
+            match kept.iter_mut().find(|kept| kept.puzzle == imported.puzzle) {
+                Some(kept) => {
+                    for tag in imported.tags {
+                        if !kept.tags.contains(&tag) {
+                            kept.tags.push(tag);
+                        }
+                    }
+                    kept.archived &= imported.archived;
+                    kept.expires = kept.expires.max(imported.expires);
+                }
+                None => kept.push(imported),
+            }
+        }
+        self.library = kept;
+        self.suggested_at = self.suggested_at.min(self.games.len());
+        before - self.library.len()
+    }
+
+    /// Drops the games and library puzzles whose puzzle doesn't read as
+    /// one. Returns how many went.
+    pub fn drop_unreadable(&mut self) -> usize {
+        let reads = |puzzle: &str| formats::parse_share_code(puzzle).is_ok();
+        let before = self.games.len() + self.library.len();
+        self.games
+            .retain(|game| game.puzzle.as_deref().is_none_or(reads));
+        self.library.retain(|imported| reads(&imported.puzzle));
+        self.suggested_at = self.suggested_at.min(self.games.len());
+        before - self.games.len() - self.library.len()
+    }
+
+    /// Games and library puzzles whose puzzle doesn't read as one.
+    pub fn unreadable(&self) -> usize {
+        self.clone().drop_unreadable()
+    }
+
+    /// Drops the archived games and import of `puzzle`. The suggestion
+    /// window is counted in games, so it moves back with them.
+    pub fn purge(&mut self, puzzle: &str) {
+        self.library
+            .retain(|imported| !(imported.archived && imported.puzzle == puzzle));
+        let before = self.games.len();
+        self.games
+            .retain(|game| !(game.archived && game.puzzle.as_deref() == Some(puzzle)));
+        let purged = before - self.games.len();
+        self.suggested_at = self.suggested_at.saturating_sub(purged);
+    }
+
+    /// Totals for the games on `difficulty`, or on every difficulty with
+    /// `None`.
+    pub fn summary(&self, difficulty: Option<Difficulty>) -> Summary {
+        let games: Vec<&GameRecord> = self
+            .games
+            .iter()
+            .filter(|game| difficulty.is_none_or(|d| game.difficulty == d))
+            .collect();
+        let wins: Vec<u64> = games
+            .iter()
+            .filter(|game| game.won)
+            .map(|game| game.seconds)
+            .collect();
+        Summary {
+            played: games.len(),
+            won: wins.len(),
+            best: wins.iter().copied().min(),
+            average: (!wins.is_empty()).then(|| wins.iter().sum::<u64>() / wins.len() as u64),
+        }
+    }
+
+    /// Wins in a row up to the latest game.
+    pub fn streak(&self) -> usize {
+        self.games.iter().rev().take_while(|game| game.won).count()
+    }
+
+    /// The most wins ever in a row.
+    pub fn best_streak(&self) -> usize {
+        self.games
+            .split(|game| !game.won)
+            .map(<[GameRecord]>::len)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn answered_suggestion(&mut self) {
+        self.suggested_at = self.games.len();
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+
+use crate::{
+    difficulty::Difficulty,
+    locale::{Locale, format_count, format_elapsed},
+    stats::{Stats, Summary},
+};
+
+/// The statistics screen: games, wins and times on each difficulty, and
+/// the winning streak.
+pub struct StatsView<'a> {
+    pub stats: &'a Stats,
+    pub locale: &'a Locale,
+    pub style: Style,
+    pub heading_style: Style,
+}
+
+fn row(locale: &Locale, label: &str, summary: Summary) -> String {
+    let time = |seconds: Option<u64>| {
+        seconds.map_or("-".to_string(), |s| format_elapsed(Duration::from_secs(s)))
+    };
+    let rate = summary
+        .win_rate()
+        .map_or("-".to_string(), |rate| format!("{rate}%"));
+    format!(
+        " {label:<11}{:>6}{:>6}{:>8}{:>9}",
+        format_count(locale, summary.played),
+        rate,
+        time(summary.best),
+        time(summary.average)
+    )
+}
+
+impl Widget for StatsView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let stats = self.stats;
+        let mut lines = vec![Line::styled(
+            format!(
+                " {:<11}{:>6}{:>6}{:>8}{:>9}",
+                "", "played", "won", "best", "average"
+            ),
+            self.heading_style,
+        )];
+        lines.extend(
+            Difficulty::all()
+                .into_iter()
+                .map(|d| Line::from(row(self.locale, d.label(), stats.summary(Some(d))))),
+        );
+        lines.push(Line::styled(
+            row(self.locale, "all", stats.summary(None)),
+            self.heading_style,
+        ));
+        lines.push(Line::default());
+        lines.push(Line::from(format!(
+            " streak {}, best {}",
+            format_count(self.locale, stats.streak()),
+            format_count(self.locale, stats.best_streak())
+        )));
+        lines.push(Line::default());
+        lines.push(Line::from(" Esc back"));
+        Paragraph::new(lines).style(self.style).render(area, buf);
+    }
+}
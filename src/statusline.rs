@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+use serde::Deserialize;
+
+use crate::{charset::GlyphSet, coach::MoveQuality, keymap::Mode, locale::format_elapsed};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Segment {
+    Mode,
+    Puzzle,
+    Timer,
+    Hints,
+    Guesses,
+    Modified,
+    Coach,
+    Race,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StatusLineConfig {
+    pub segments: Vec<Segment>,
+}
+
+impl Default for StatusLineConfig {
+    fn default() -> Self {
+        Self {
+            segments: vec![
+                Segment::Mode,
+                Segment::Puzzle,
+                Segment::Timer,
+                Segment::Hints,
+                Segment::Guesses,
+                Segment::Modified,
+                Segment::Coach,
+                Segment::Race,
+            ],
+        }
+    }
+}
+
+/// What the status line knows about the running game. Segments whose data
+/// is missing are left out.
+pub struct Status<'a> {
+    pub mode: Mode,
+    pub puzzle: Option<&'a str>,
+    pub difficulty: Option<&'a str>,
+    pub elapsed: Duration,
+    pub hints_used: u32,
+    pub hint_budget: Option<u32>,
+    /// guesses marked and not yet rolled back
+    pub guesses: usize,
+    pub modified: bool,
+    /// the coach's verdict on the last placement
+    pub coach: Option<MoveQuality>,
+    /// cells the bot being raced filled out of all, or when it finished
+    pub race: Option<(usize, usize, Option<Duration>)>,
+}
+
+pub struct StatusLine<'a> {
+    pub segments: &'a [Segment],
+    pub status: Status<'a>,
+    pub style: Style,
+    pub mode_style: Style,
+    pub glyphs: GlyphSet,
+}
+
+impl StatusLine<'_> {
+    fn segment(&self, segment: Segment) -> Option<Span<'static>> {
+        let status = &self.status;
+        let text = match segment {
+            Segment::Mode => {
+                return Some(Span::styled(
+                    format!(" {} ", status.mode.label()),
+                    self.mode_style.add_modifier(Modifier::BOLD),
+                ));
+            }
+            Segment::Puzzle => match (status.puzzle, status.difficulty) {
+                (Some(id), Some(difficulty)) => format!("{id} ({difficulty})"),
+                (Some(id), None) => id.to_string(),
+                (None, Some(difficulty)) => difficulty.to_string(),
+                (None, None) => return None,
+            },
+            Segment::Timer => format_elapsed(status.elapsed),
+            Segment::Hints => match (status.hints_used, status.hint_budget) {
+                (0, None) => return None,
+                (used, None) => format!("hints {used}"),
+                (used, Some(budget)) => format!("hints {used}/{budget}"),
+            },
+            Segment::Guesses => match status.guesses {
+                0 => return None,
+                depth => format!("guess {depth}"),
+            },
+            Segment::Modified if status.modified => "[+]".to_string(),
+            Segment::Modified => return None,
+            Segment::Coach => status.coach?.label(self.glyphs),
+            Segment::Race => match status.race? {
+                (_, _, Some(finished)) => format!("bot done {}", format_elapsed(finished)),
+                (filled, to_fill, None) => format!("bot {filled}/{to_fill}"),
+            },
+        };
+        Some(Span::raw(format!(" {text} ")))
+    }
+}
+
+impl Widget for StatusLine<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut spans = Vec::new();
+        for span in self.segments.iter().filter_map(|s| self.segment(*s)) {
+            if !spans.is_empty() {
+                spans.push(Span::raw(self.glyphs.separator));
+            }
+            spans.push(span);
+        }
+        Line::from(spans).style(self.style).render(area, buf);
+    }
+}
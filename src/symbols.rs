@@ -0,0 +1,164 @@
+use color_eyre::eyre::{Result, bail};
+use serde::Deserialize;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{charset::GlyphSet, soduko::CellState};
+
+/// Built in ways to show the values 1-9. They only change the look, the
+/// values are still typed as digits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolSet {
+    #[default]
+    Digits,
+    /// white pieces then the black queen, rook and bishop
+    Chess,
+    /// one die for 1-6, two for 7-9
+    Dice,
+    Emoji,
+    /// Arabic-Indic digits
+    Arabic,
+    /// Chinese and Japanese numerals, two columns wide
+    Kanji,
+}
+
+impl SymbolSet {
+    fn shown(self) -> [&'static str; 9] {
+        match self {
+            SymbolSet::Digits => ["1", "2", "3", "4", "5", "6", "7", "8", "9"],
+            SymbolSet::Chess => ["♔", "♕", "♖", "♗", "♘", "♙", "♛", "♜", "♝"],
+            SymbolSet::Dice => ["⚀", "⚁", "⚂", "⚃", "⚄", "⚅", "⚅⚀", "⚅⚁", "⚅⚂"],
+            SymbolSet::Emoji => ["🍎", "🍊", "🍋", "🍐", "🍇", "🍓", "🍒", "🥝", "🍑"],
+            SymbolSet::Arabic => ["١", "٢", "٣", "٤", "٥", "٦", "٧", "٨", "٩"],
+            SymbolSet::Kanji => ["一", "二", "三", "四", "五", "六", "七", "八", "九"],
+        }
+    }
+
+    pub fn symbols(self) -> Symbols {
+        Symbols {
+            shown: self.shown().map(str::to_string).to_vec(),
+            keys: DIGIT_KEYS.to_vec(),
+            blank: GlyphSet::UNICODE.dot,
+        }
+    }
+}
+
+const DIGIT_KEYS: [char; 9] = ['1', '2', '3', '4', '5', '6', '7', '8', '9'];
+
+/// What the values 1-9 look like on screen and which keys enter them, or
+/// the values of a board of another size.
+///
+/// The board itself always stores 1-9, or up to 16. Everything that shows
+/// or reads a value goes through here, so letter boards and picture themes
+/// only differ in this table. A shown symbol may be any string, including
+/// ones more than a column wide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbols {
+    shown: Vec<String>,
+    keys: Vec<char>,
+    blank: &'static str,
+}
+
+impl Default for Symbols {
+    fn default() -> Self {
+        SymbolSet::Digits.symbols()
+    }
+}
+
+/// `c` in upper case, or as it is when that takes more than one letter,
+/// like `ß`.
+fn upper(c: char) -> char {
+    let mut upper = c.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(u), None) => u,
+        _ => c,
+    }
+}
+
+impl Symbols {
+    /// Nine distinct letters, one per value in order, shown and typed as
+    /// themselves. Letters are kept in upper case.
+    pub fn letters(word: &str) -> Result<Self> {
+        let letters: Vec<char> = word.trim().chars().map(upper).collect();
+        if letters.len() != 9 {
+            bail!("{word:?} has {} letters, a wordoku needs 9", letters.len());
+        }
+        if let Some(c) = letters.iter().find(|c| !c.is_alphabetic()) {
+            bail!("{c:?} in {word:?} is not a letter");
+        }
+        for (i, c) in letters.iter().enumerate() {
+            if letters[..i].contains(c) {
+                bail!("{c:?} appears twice in {word:?}, the letters must be distinct");
+            }
+        }
+        Ok(Self {
+            shown: letters.iter().copied().map(String::from).collect(),
+            keys: letters,
+            blank: GlyphSet::UNICODE.dot,
+        })
+    }
+
+    /// The values of a board `size` cells across, 1-9 and then A-G for
+    /// 10-16, shown and typed as themselves.
+    pub fn sized(size: u8) -> Self {
+        let keys: Vec<char> = (1..=size)
+            .filter_map(|d| char::from_digit(d.into(), 17))
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        Self {
+            shown: keys.iter().copied().map(String::from).collect(),
+            keys,
+            blank: GlyphSet::UNICODE.dot,
+        }
+    }
+
+    /// Draws the empty cell with `glyphs`. Pictures the terminal can't show
+    /// go back to digits, letters stay as they're typed.
+    pub fn with_glyphs(mut self, glyphs: GlyphSet) -> Self {
+        self.blank = glyphs.dot;
+        if glyphs.is_ascii() && self.is_digits() && !self.shown.iter().all(|s| s.is_ascii()) {
+            self.shown = SymbolSet::Digits.shown().map(str::to_string).to_vec();
+        }
+        self
+    }
+
+    /// True unless the values are typed as letters.
+    pub fn is_digits(&self) -> bool {
+        self.keys == DIGIT_KEYS
+    }
+
+    /// The symbol for value `d`, 1..=9 on a 9x9 board.
+    pub fn symbol(&self, d: u8) -> &str {
+        &self.shown[d as usize - 1]
+    }
+
+    /// The value a typed character stands for, ignoring case.
+    pub fn value(&self, c: char) -> Option<u8> {
+        let c = upper(c);
+        self.keys.iter().position(|&k| k == c).map(|i| i as u8 + 1)
+    }
+
+    /// The [`GlyphSet`]'s dot for an empty cell.
+    pub fn cell(&self, cell: CellState) -> &str {
+        cell.map_or(self.blank, |d| self.symbol(d.get()))
+    }
+
+    pub fn blank(&self) -> &str {
+        self.blank
+    }
+
+    /// Like [`Symbols::cell`], but a symbol wider than `width` columns
+    /// falls back to the plain digit.
+    pub fn cell_within(&self, cell: CellState, width: usize) -> &str {
+        let symbol = self.cell(cell);
+        match *cell {
+            Some(d) if symbol.width() > width => SymbolSet::Digits.shown()[d.get() as usize - 1],
+            _ => symbol,
+        }
+    }
+
+    /// Columns taken by the widest symbol.
+    pub fn width(&self) -> usize {
+        self.shown.iter().map(|s| s.width()).max().unwrap_or(1)
+    }
+}
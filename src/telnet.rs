@@ -0,0 +1,290 @@
+use std::{
+    io::{self, BufWriter, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+        mpsc::Sender,
+    },
+    thread,
+    time::Duration,
+};
+
+use color_eyre::eyre::WrapErr;
+use ratatui::{crossterm::event::KeyCode, layout::Rect};
+
+use crate::{
+    Config,
+    charset::Charset,
+    events::{Event, Events},
+    remote::{self, parse_keys},
+};
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+/// the client telling its window size
+const NAWS: u8 = 31;
+
+/// Subnegotiations longer than this are cut short, the only one read is
+/// the window size, which takes five bytes.
+const MAX_SUB: usize = 64;
+/// Players served at once, anyone past that is told to come back later.
+const MAX_SESSIONS: usize = 32;
+/// How long a player gets to type their name.
+const NAME_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_NAME: usize = 16;
+
+/// What a client that never tells its size is taken to have.
+const SIZE: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: 80,
+    height: 24,
+};
+
+/// Hosts games over plain telnet, for BBS doors and terminals too old for
+/// SSH. Everything is drawn in ASCII, and clients that won't leave line mode
+/// still play, a line of keys at a time. Nobody logs in, so players are told
+/// apart by their address and the name they give, which keeps players behind
+/// one router apart. Games are sandboxed like the SSH ones.
+pub fn serve(listen: SocketAddr) -> color_eyre::Result<()> {
+    let mut config = Config::load()?;
+    config.charset = Charset::Ascii;
+    let listener = TcpListener::bind(listen).wrap_err_with(|| format!("listening on {listen}"))?;
+    eprintln!("serving games on {listen}");
+    let sessions = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("taking a connection: {err}");
+                continue;
+            }
+        };
+        let Some(slot) = Slot::take(&sessions) else {
+            let _ = stream.write_all(b"too many players right now, try again later\r\n");
+            let _ = stream.shutdown(Shutdown::Both);
+            continue;
+        };
+        let config = config.clone();
+        thread::spawn(move || {
+            if let Err(err) = session(config, stream) {
+                eprintln!("telnet game ended: {err:#}");
+            }
+            drop(slot);
+        });
+    }
+    Ok(())
+}
+
+/// One of the [`MAX_SESSIONS`], given back when dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(sessions: &Arc<AtomicUsize>) -> Option<Self> {
+        sessions
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |taken| {
+                (taken < MAX_SESSIONS).then_some(taken + 1)
+            })
+            .ok()
+            .map(|_| Self(Arc::clone(sessions)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn session(config: Config, stream: TcpStream) -> color_eyre::Result<()> {
+    let peer = stream.peer_addr()?;
+    let mut out = BufWriter::new(stream.try_clone()?);
+    // the server echoing and no go aheads is what puts clients in character
+    // mode
+    out.write_all(&[IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD, IAC, DO, NAWS])?;
+    out.flush()?;
+    let (input, events) = Events::channel();
+    let reader = stream.try_clone()?;
+    thread::spawn(move || read(reader, input));
+    let Some(name) = ask_name(&mut out, &events)? else {
+        let _ = stream.shutdown(Shutdown::Both);
+        return Ok(());
+    };
+    let profile = format!("telnet-{}-{name}", peer.ip()).replace(':', "-");
+    let result = remote::play(config, &profile, out, events, SIZE);
+    // stops the reader too
+    let _ = stream.shutdown(Shutdown::Both);
+    result
+}
+
+/// The name the player types before the game starts, echoed back as they
+/// type it and kept to letters, digits, `-` and `_`. None when they hang up
+/// or take too long.
+fn ask_name(out: &mut impl Write, events: &Events) -> color_eyre::Result<Option<String>> {
+    out.write_all(b"rudoku over telnet\r\nyour name: ")?;
+    out.flush()?;
+    let mut name = String::new();
+    loop {
+        let key = match events.next(NAME_TIMEOUT) {
+            Ok(Event::Key(key)) => key,
+            Ok(Event::Tick) | Err(_) => return Ok(None),
+            Ok(_) => continue,
+        };
+        match key.code {
+            KeyCode::Enter => break,
+            KeyCode::Backspace if name.pop().is_some() => out.write_all(b"\x08 \x08")?,
+            KeyCode::Char(c)
+                if name.len() < MAX_NAME && (c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                name.push(c);
+                out.write_all(&[c as u8])?;
+            }
+            _ => {}
+        }
+        out.flush()?;
+    }
+    if name.is_empty() {
+        name.push_str("guest");
+    }
+    Ok(Some(name.to_lowercase()))
+}
+
+/// Hands what the client types to the app until either side hangs up.
+fn read(mut stream: TcpStream, input: Sender<io::Result<Event>>) {
+    let mut telnet = Telnet::default();
+    let mut buf = [0; 1024];
+    loop {
+        let n = match stream.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        for event in telnet.feed(&buf[..n]) {
+            if input.send(Ok(event)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+enum State {
+    #[default]
+    Data,
+    /// after an IAC
+    Command,
+    /// after a WILL, WONT, DO or DONT, waiting for the option
+    Option(u8),
+    /// inside a subnegotiation
+    Sub,
+    /// after an IAC inside a subnegotiation
+    SubCommand,
+}
+
+/// The telnet protocol taken out of what the client sends, leaving the keys.
+#[derive(Debug, Default)]
+struct Telnet {
+    state: State,
+    /// the client turned down character mode, so lines come in whole with
+    /// the return that sent them
+    line_mode: bool,
+    /// the player's name was sent, it ends with a return even in line mode
+    named: bool,
+    /// the subnegotiation so far, up to [`MAX_SUB`] bytes
+    sub: Vec<u8>,
+}
+
+impl Telnet {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        let mut data = vec![];
+        let mut events = vec![];
+        for &byte in bytes {
+            self.state = match (self.state, byte) {
+                (State::Data, IAC) => State::Command,
+                (State::Data, byte) => {
+                    data.push(byte);
+                    State::Data
+                }
+                // a doubled IAC is a 255 byte
+                (State::Command, IAC) => {
+                    data.push(IAC);
+                    State::Data
+                }
+                (State::Command, WILL | WONT | DO | DONT) => State::Option(byte),
+                (State::Command, SB) => {
+                    self.sub.clear();
+                    State::Sub
+                }
+                (State::Command, _) => State::Data,
+                (State::Option(verb), option) => {
+                    if verb == DONT && matches!(option, ECHO | SUPPRESS_GO_AHEAD) {
+                        self.line_mode = true;
+                    }
+                    State::Data
+                }
+                (State::Sub, IAC) => State::SubCommand,
+                (State::Sub, byte) | (State::SubCommand, byte @ IAC) => {
+                    if self.sub.len() < MAX_SUB {
+                        self.sub.push(byte);
+                    }
+                    State::Sub
+                }
+                (State::SubCommand, SE) => {
+                    if let [NAWS, w1, w2, h1, h2] = self.sub[..] {
+                        let width = u16::from_be_bytes([w1, w2]);
+                        let height = u16::from_be_bytes([h1, h2]);
+                        if width > 0 && height > 0 {
+                            events.push(Event::Resize(width, height));
+                        }
+                    }
+                    State::Data
+                }
+                (State::SubCommand, _) => State::Data,
+            };
+        }
+        let mut keys = parse_keys(&data);
+        // a return after the keys only sent them, a lone one is a key itself
+        if self.line_mode
+            && self.named
+            && keys.len() > 1
+            && keys.last().is_some_and(|key| key.code == KeyCode::Enter)
+        {
+            keys.pop();
+        }
+        self.named |= keys.iter().any(|key| key.code == KeyCode::Enter);
+        events.extend(keys.into_iter().map(Event::Key));
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_window_size() {
+        let mut telnet = Telnet::default();
+        let events = telnet.feed(&[IAC, SB, NAWS, 0, 100, 0, 30, IAC, SE, b'a']);
+        assert!(matches!(events[0], Event::Resize(100, 30)));
+        assert!(matches!(&events[1], Event::Key(key) if key.code == KeyCode::Char('a')));
+    }
+
+    #[test]
+    fn endless_subnegotiation_stays_small() {
+        let mut telnet = Telnet::default();
+        telnet.feed(&[IAC, SB]);
+        for _ in 0..1000 {
+            telnet.feed(&[0; 1024]);
+        }
+        assert_eq!(telnet.sub.len(), MAX_SUB);
+        let events = telnet.feed(&[IAC, SE, b'x']);
+        assert_eq!(events.len(), 1);
+    }
+}
@@ -0,0 +1,240 @@
+use std::str::FromStr;
+
+use ratatui::style::{Color, palette::tailwind};
+use serde::{Deserialize, Deserializer};
+
+use crate::color;
+
+/// The built in dark themes, by the tailwind palette they're drawn from.
+const PALETTES: [(&str, tailwind::Palette); 4] = [
+    ("blue", tailwind::BLUE),
+    ("emerald", tailwind::EMERALD),
+    ("red", tailwind::RED),
+    ("indigo", tailwind::INDIGO),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableColors {
+    pub buffer_bg: Color,
+    pub header_bg: Color,
+    pub header_fg: Color,
+    pub row_fg: Color,
+    pub selected_row_style_fg: Color,
+    pub selected_column_style_fg: Color,
+    pub selected_cell_style_fg: Color,
+    pub normal_row_color: Color,
+    pub alt_row_color: Color,
+    pub footer_border_color: Color,
+    /// the accent of a board checked and found right
+    pub success: Color,
+    /// the accent of a board checked and found wrong
+    pub error: Color,
+}
+
+impl TableColors {
+    pub const fn new(color: &tailwind::Palette) -> Self {
+        Self {
+            buffer_bg: tailwind::SLATE.c950,
+            header_bg: color.c900,
+            header_fg: tailwind::SLATE.c200,
+            row_fg: tailwind::SLATE.c200,
+            selected_row_style_fg: color.c400,
+            selected_column_style_fg: color.c400,
+            selected_cell_style_fg: color.c600,
+            normal_row_color: tailwind::SLATE.c950,
+            alt_row_color: tailwind::SLATE.c900,
+            footer_border_color: color.c400,
+            success: tailwind::EMERALD.c400,
+            error: tailwind::RED.c400,
+        }
+    }
+
+    /// Dark text on a pale board.
+    pub const fn light(color: &tailwind::Palette) -> Self {
+        Self {
+            buffer_bg: tailwind::SLATE.c50,
+            header_bg: color.c600,
+            header_fg: tailwind::SLATE.c50,
+            row_fg: tailwind::SLATE.c900,
+            selected_row_style_fg: color.c600,
+            selected_column_style_fg: color.c600,
+            selected_cell_style_fg: color.c700,
+            normal_row_color: tailwind::SLATE.c50,
+            alt_row_color: tailwind::SLATE.c200,
+            footer_border_color: color.c600,
+            success: tailwind::EMERALD.c600,
+            error: tailwind::RED.c600,
+        }
+    }
+
+    /// White on black with yellow for everything that needs finding.
+    pub const fn high_contrast() -> Self {
+        const BLACK: Color = Color::Rgb(0, 0, 0);
+        const WHITE: Color = Color::Rgb(255, 255, 255);
+        const YELLOW: Color = Color::Rgb(255, 230, 0);
+        Self {
+            buffer_bg: BLACK,
+            header_bg: YELLOW,
+            header_fg: BLACK,
+            row_fg: WHITE,
+            selected_row_style_fg: YELLOW,
+            selected_column_style_fg: YELLOW,
+            selected_cell_style_fg: YELLOW,
+            normal_row_color: BLACK,
+            alt_row_color: Color::Rgb(45, 45, 45),
+            footer_border_color: WHITE,
+            success: Color::Rgb(0, 255, 0),
+            error: Color::Rgb(255, 0, 0),
+        }
+    }
+
+    pub fn lerp(&self, to: &Self, t: f32) -> Self {
+        Self {
+            buffer_bg: color::lerp(self.buffer_bg, to.buffer_bg, t),
+            header_bg: color::lerp(self.header_bg, to.header_bg, t),
+            header_fg: color::lerp(self.header_fg, to.header_fg, t),
+            row_fg: color::lerp(self.row_fg, to.row_fg, t),
+            selected_row_style_fg: color::lerp(
+                self.selected_row_style_fg,
+                to.selected_row_style_fg,
+                t,
+            ),
+            selected_column_style_fg: color::lerp(
+                self.selected_column_style_fg,
+                to.selected_column_style_fg,
+                t,
+            ),
+            selected_cell_style_fg: color::lerp(
+                self.selected_cell_style_fg,
+                to.selected_cell_style_fg,
+                t,
+            ),
+            normal_row_color: color::lerp(self.normal_row_color, to.normal_row_color, t),
+            alt_row_color: color::lerp(self.alt_row_color, to.alt_row_color, t),
+            footer_border_color: color::lerp(self.footer_border_color, to.footer_border_color, t),
+            success: color::lerp(self.success, to.success, t),
+            error: color::lerp(self.error, to.error, t),
+        }
+    }
+
+    /// These colors with `accent` wherever the theme puts its own.
+    pub fn accented(mut self, accent: Color) -> Self {
+        self.header_bg = accent;
+        self.selected_row_style_fg = accent;
+        self.selected_column_style_fg = accent;
+        self.selected_cell_style_fg = accent;
+        self.footer_border_color = accent;
+        self
+    }
+}
+
+/// A theme of the player's own from the `[[themes]]` config array. The
+/// accent goes wherever the built in themes use their palette, the rest
+/// comes from the dark or light base unless given:
+///
+/// ```toml
+/// [[themes]]
+/// name = "sunset"
+/// accent = "#ff7f50"
+/// light = true
+/// alt_row = "#ffe4d6"
+/// success = "green"
+/// error = "#c0392b"
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ThemeConfig {
+    pub name: String,
+    #[serde(deserialize_with = "color")]
+    pub accent: Color,
+    #[serde(default)]
+    pub light: bool,
+    #[serde(default, deserialize_with = "optional_color")]
+    pub background: Option<Color>,
+    #[serde(default, deserialize_with = "optional_color")]
+    pub text: Option<Color>,
+    #[serde(default, deserialize_with = "optional_color")]
+    pub alt_row: Option<Color>,
+    /// the accent of a board checked and found right
+    #[serde(default, deserialize_with = "optional_color")]
+    pub success: Option<Color>,
+    /// the accent of a board checked and found wrong
+    #[serde(default, deserialize_with = "optional_color")]
+    pub error: Option<Color>,
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Color::from_str(&name).map_err(|_| serde::de::Error::custom(format!("unknown color {name:?}")))
+}
+
+fn optional_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    color(deserializer).map(Some)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub colors: TableColors,
+}
+
+impl Theme {
+    fn from_config(config: &ThemeConfig) -> Self {
+        let mut colors = match config.light {
+            true => TableColors::light(&tailwind::SLATE),
+            false => TableColors::new(&tailwind::SLATE),
+        }
+        .accented(config.accent);
+        if let Some(background) = config.background {
+            colors.buffer_bg = background;
+            colors.normal_row_color = background;
+        }
+        if let Some(text) = config.text {
+            colors.row_fg = text;
+        }
+        if let Some(alt_row) = config.alt_row {
+            colors.alt_row_color = alt_row;
+        }
+        if let Some(success) = config.success {
+            colors.success = success;
+        }
+        if let Some(error) = config.error {
+            colors.error = error;
+        }
+        Self {
+            name: config.name.clone(),
+            colors,
+        }
+    }
+
+    /// The built in themes, then the player's. A theme of the player's
+    /// with a built in name takes its place.
+    pub fn all(config: &[ThemeConfig]) -> Vec<Self> {
+        let mut themes: Vec<Self> = PALETTES
+            .iter()
+            .map(|(name, palette)| Self {
+                name: name.to_string(),
+                colors: TableColors::new(palette),
+            })
+            .collect();
+        themes.push(Self {
+            name: "light".into(),
+            colors: TableColors::light(&tailwind::BLUE),
+        });
+        themes.push(Self {
+            name: "high-contrast".into(),
+            colors: TableColors::high_contrast(),
+        });
+        for theme in config.iter().map(Self::from_config) {
+            match themes.iter_mut().find(|t| t.name == theme.name) {
+                Some(existing) => *existing = theme,
+                None => themes.push(theme),
+            }
+        }
+        themes
+    }
+
+    /// The name of the built in theme prefs used to store by position.
+    pub fn builtin_name(index: usize) -> &'static str {
+        PALETTES[index % PALETTES.len()].0
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style, palette::tailwind},
+    widgets::{BorderType, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::charset::GlyphSet;
+
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const TOAST_WIDTH: u16 = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Error,
+}
+
+/// A short message shown in the top right corner for a few seconds.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub kind: ToastKind,
+    shown: Instant,
+}
+
+impl Toast {
+    pub fn info(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ToastKind::Info,
+            shown: Instant::now(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ToastKind::Error,
+            shown: Instant::now(),
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.shown.elapsed() >= TOAST_DURATION
+    }
+
+    fn border_color(&self) -> Color {
+        match self.kind {
+            ToastKind::Info => tailwind::SKY.c400,
+            ToastKind::Error => tailwind::RED.c500,
+        }
+    }
+}
+
+/// A toast drawn in the top right corner with the app's [`GlyphSet`].
+pub struct ToastView<'a> {
+    pub toast: &'a Toast,
+    pub glyphs: GlyphSet,
+}
+
+impl Widget for ToastView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = TOAST_WIDTH.min(area.width);
+        let inner_width = width.saturating_sub(2).max(1) as usize;
+        let lines: usize = self
+            .toast
+            .message
+            .lines()
+            .map(|line| line.chars().count().div_ceil(inner_width).max(1))
+            .sum();
+        let height = (lines as u16 + 2).min(area.height);
+        let area = Rect {
+            x: area.right().saturating_sub(width),
+            y: area.y,
+            width,
+            height,
+        };
+        Clear.render(area, buf);
+        Paragraph::new(self.toast.message.as_str())
+            .wrap(Wrap { trim: false })
+            .style(
+                Style::new()
+                    .fg(tailwind::SLATE.c200)
+                    .bg(tailwind::SLATE.c900),
+            )
+            .block(
+                self.glyphs
+                    .block(BorderType::Rounded)
+                    .border_style(Style::new().fg(self.toast.border_color())),
+            )
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::{
+        event::{
+            DisableMouseCapture, EnableMouseCapture, KeyCode, MouseButton, MouseEvent,
+            MouseEventKind,
+        },
+        execute,
+    },
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Paragraph, Widget},
+};
+use serde::Deserialize;
+
+use crate::charset::GlyphSet;
+
+/// How the game is mainly controlled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    #[default]
+    Keyboard,
+    /// for terminals without a hardware keyboard: an on-screen pad, swipes
+    /// to move and a long press for notes mode
+    Touch,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct TouchConfig {
+    /// how long a press is held before it counts as a long press
+    pub long_press_ms: u64,
+    /// how far (in columns) a drag has to go to count as a swipe
+    pub swipe_distance: u16,
+}
+
+impl Default for TouchConfig {
+    fn default() -> Self {
+        Self {
+            long_press_ms: 500,
+            swipe_distance: 4,
+        }
+    }
+}
+
+pub fn set_mouse_capture(on: bool) -> io::Result<()> {
+    match on {
+        true => execute!(io::stdout(), EnableMouseCapture),
+        false => execute!(io::stdout(), DisableMouseCapture),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    Tap {
+        column: u16,
+        row: u16,
+    },
+    LongPress {
+        column: u16,
+        row: u16,
+    },
+    /// the arrow key pointing the way of the swipe
+    Swipe(KeyCode),
+}
+
+/// Turns raw mouse presses and releases into gestures.
+#[derive(Debug, Default)]
+pub struct Gestures {
+    press: Option<(u16, u16, Instant)>,
+}
+
+impl Gestures {
+    pub fn handle(&mut self, event: MouseEvent, config: &TouchConfig) -> Option<Gesture> {
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.press = Some((event.column, event.row, Instant::now()));
+                None
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let (column, row, at) = self.press.take()?;
+                let dx = event.column as i32 - column as i32;
+                // terminal rows are about twice as tall as columns are wide
+                let dy = 2 * (event.row as i32 - row as i32);
+                let distance = config.swipe_distance as i32;
+                if dx.abs().max(dy.abs()) >= distance {
+                    let key = match dx.abs() >= dy.abs() {
+                        true if dx > 0 => KeyCode::Right,
+                        true => KeyCode::Left,
+                        false if dy > 0 => KeyCode::Down,
+                        false => KeyCode::Up,
+                    };
+                    Some(Gesture::Swipe(key))
+                } else if at.elapsed() >= Duration::from_millis(config.long_press_ms) {
+                    Some(Gesture::LongPress { column, row })
+                } else {
+                    Some(Gesture::Tap { column, row })
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+const PAD: [[(&str, KeyCode); 3]; 4] = [
+    [
+        ("1", KeyCode::Char('1')),
+        ("2", KeyCode::Char('2')),
+        ("3", KeyCode::Char('3')),
+    ],
+    [
+        ("4", KeyCode::Char('4')),
+        ("5", KeyCode::Char('5')),
+        ("6", KeyCode::Char('6')),
+    ],
+    [
+        ("7", KeyCode::Char('7')),
+        ("8", KeyCode::Char('8')),
+        ("9", KeyCode::Char('9')),
+    ],
+    [
+        ("erase", KeyCode::Backspace),
+        ("notes", KeyCode::Char('p')),
+        ("check", KeyCode::Enter),
+    ],
+];
+
+/// Large on-screen buttons that press keys, so they follow whatever the
+/// current mode binds those keys to.
+pub struct Pad {
+    pub style: Style,
+    pub border_style: Style,
+    pub glyphs: GlyphSet,
+    /// the key typed for each digit, shown next to it when it isn't the
+    /// digit itself
+    pub digit_keys: [char; 9],
+}
+
+impl Pad {
+    /// Every button with its area, as large as fits up to 9x3 cells.
+    fn buttons(area: Rect) -> impl Iterator<Item = (Rect, &'static str, KeyCode)> {
+        let width = (area.width.saturating_sub(2) / 3).clamp(1, 9);
+        let height = (area.height / 4).clamp(1, 3);
+        let rows = Layout::vertical([Constraint::Length(height); 4])
+            .flex(Flex::Center)
+            .areas::<4>(area);
+        PAD.iter().zip(rows).flat_map(move |(buttons, row)| {
+            let columns = Layout::horizontal([Constraint::Length(width); 3])
+                .flex(Flex::Center)
+                .spacing(1)
+                .areas::<3>(row);
+            buttons
+                .iter()
+                .zip(columns)
+                .map(|(&(label, key), area)| (area, label, key))
+        })
+    }
+
+    pub fn key_at(area: Rect, column: u16, row: u16) -> Option<KeyCode> {
+        Self::buttons(area)
+            .find(|(button, _, _)| button.contains((column, row).into()))
+            .map(|(_, _, key)| key)
+    }
+}
+
+impl Widget for &Pad {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Block::new().style(self.style).render(area, buf);
+        for (button, label, key) in Pad::buttons(area) {
+            let label = match key {
+                KeyCode::Char(c @ '1'..='9') => {
+                    let typed = self.digit_keys[c as usize - '1' as usize];
+                    match typed == c {
+                        true => label.to_string(),
+                        false => format!("{label} {typed}"),
+                    }
+                }
+                _ => label.to_string(),
+            };
+            let paragraph = Paragraph::new(label).centered().style(self.style);
+            if button.height >= 3 {
+                paragraph
+                    .block(
+                        self.glyphs
+                            .block(BorderType::Rounded)
+                            .border_style(self.border_style),
+                    )
+                    .render(button, buf);
+            } else {
+                paragraph
+                    .style(self.border_style.add_modifier(Modifier::REVERSED))
+                    .render(button, buf);
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::soduko::House;
+
+const DIAGONALS: [House; 2] = [House::Diagonal(0), House::Diagonal(1)];
+const WINDOWS: [House; 4] = [
+    House::Window(0),
+    House::Window(1),
+    House::Window(2),
+    House::Window(3),
+];
+
+/// Rules on top of the rows, columns and boxes, each adding houses that
+/// have to hold 1-9 as well.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    #[default]
+    Classic,
+    /// X-Sudoku: both long diagonals
+    X,
+    /// Hyper Sudoku: four more boxes, one cell in from each corner
+    Hyper,
+}
+
+impl Variant {
+    pub fn label(self) -> &'static str {
+        match self {
+            Variant::Classic => "classic",
+            Variant::X => "x-sudoku",
+            Variant::Hyper => "hyper",
+        }
+    }
+
+    /// The houses this variant adds, at most four.
+    pub fn regions(self) -> &'static [House] {
+        match self {
+            Variant::Classic => &[],
+            Variant::X => &DIAGONALS,
+            Variant::Hyper => &WINDOWS,
+        }
+    }
+
+    /// Every house of the variant, rows, columns and boxes first.
+    pub fn houses(self) -> impl Iterator<Item = House> {
+        House::all().chain(self.regions().iter().copied())
+    }
+
+    /// Bit `i` set when (row, col) lies in the `i`th of [`Self::regions`].
+    pub fn region_mask(self, row: usize, col: usize) -> u8 {
+        match self {
+            Variant::Classic => 0,
+            Variant::X => u8::from(row == col) | u8::from(row + col == 8) << 1,
+            Variant::Hyper if row.is_multiple_of(4) || col.is_multiple_of(4) => 0,
+            Variant::Hyper => 1 << (row / 4 * 2 + col / 4),
+        }
+    }
+
+    /// Whether (row, col) lies in one of the added houses, which the board
+    /// shades.
+    pub fn in_region(self, row: usize, col: usize) -> bool {
+        self.region_mask(row, col) != 0
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use color_eyre::eyre::{WrapErr, bail};
+
+use crate::{
+    formats::{self, FormatRegistry},
+    hints::{self, Step},
+    rating::{self, Grade},
+    soduko::BoardState,
+    symbols::Symbols,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum WalkthroughFormat {
+    #[default]
+    Text,
+    Markdown,
+}
+
+/// How a puzzle solves by logic, one placement at a time, which is also
+/// why its solution is the only one: every digit is forced by the ones
+/// before it.
+#[derive(Debug, Clone)]
+pub struct Walkthrough {
+    pub puzzle: BoardState,
+    pub solution: BoardState,
+    pub steps: Vec<Step>,
+    /// where logic ran out, when it did, with the cells still empty
+    pub stuck: Option<BoardState>,
+    pub grade: Grade,
+}
+
+impl Walkthrough {
+    /// `None` when `puzzle` doesn't have exactly one solution, so there's
+    /// nothing to explain.
+    pub fn new(puzzle: BoardState) -> Option<Self> {
+        let grade = rating::rate(&puzzle)?;
+        let solution = puzzle.solve()?;
+        let mut board = puzzle;
+        let mut steps = Vec::new();
+        while board.clue_count() < 81 {
+            let Some(step) = hints::next_step(&board) else {
+                break;
+            };
+            board.set(step.row as u8, step.col as u8, step.digit.into());
+            steps.push(step);
+        }
+        Some(Self {
+            puzzle,
+            solution,
+            steps,
+            stuck: (board.clue_count() < 81).then_some(board),
+            grade,
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let symbols = Symbols::default();
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", formats::portable_code(&self.puzzle));
+        let _ = writeln!(out);
+        out.push_str(&formats::grid(&self.puzzle, &symbols));
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}", self.summary());
+        let _ = writeln!(out);
+        for (i, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(out, "{:>3}. {step}", i + 1);
+        }
+        if let Some(stuck) = &self.stuck {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "{}", self.stuck_note(stuck));
+            let _ = writeln!(out);
+            out.push_str(&formats::grid(stuck, &symbols));
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "solution");
+        let _ = writeln!(out);
+        out.push_str(&formats::grid(&self.solution, &symbols));
+        out
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let symbols = Symbols::default();
+        let mut out = String::new();
+        let _ = writeln!(out, "# `{}`", formats::portable_code(&self.puzzle));
+        let _ = writeln!(out);
+        let _ = writeln!(out, "```\n{}```", formats::grid(&self.puzzle, &symbols));
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}", self.summary());
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## Walkthrough");
+        let _ = writeln!(out);
+        for (i, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(out, "{}. {step}", i + 1);
+        }
+        if let Some(stuck) = &self.stuck {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "{}", self.stuck_note(stuck));
+            let _ = writeln!(out);
+            let _ = writeln!(out, "```\n{}```", formats::grid(stuck, &symbols));
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## Solution");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "```\n{}```", formats::grid(&self.solution, &symbols));
+        out
+    }
+
+    fn summary(&self) -> String {
+        let clues = self.puzzle.clue_count();
+        match self.stuck {
+            None => format!(
+                "{clues} clues. Each of the {} placements below follows from the ones \
+                 before it using {}, so the solution is the only one.",
+                self.steps.len(),
+                match self.grade {
+                    Grade::Singles => "singles alone",
+                    Grade::Pairs => "singles, pairs and box/line reductions",
+                    _ => "singles, pairs and fish",
+                },
+            ),
+            Some(_) => format!(
+                "{clues} clues. The solution is the only one, but logic alone only \
+                 gets {} placements in.",
+                self.steps.len()
+            ),
+        }
+    }
+
+    fn stuck_note(&self, stuck: &BoardState) -> String {
+        format!(
+            "Past here no technique the hints know places anything. The {} cells \
+             left take trial and error:",
+            81 - stuck.clue_count()
+        )
+    }
+}
+
+/// `rudoku explain`: reads the puzzle from a file or as 81 characters and
+/// writes its walkthrough to `output`, or prints it.
+pub fn run(puzzle: &str, format: WalkthroughFormat, output: Option<PathBuf>) -> crate::Result {
+    let path = Path::new(puzzle);
+    let board = match path.exists() {
+        true => FormatRegistry::default().import(path)?.board,
+        false => puzzle
+            .parse()
+            .wrap_err_with(|| format!("{puzzle:?} is neither a file nor a puzzle"))?,
+    };
+    let Some(walkthrough) = Walkthrough::new(board) else {
+        bail!("the puzzle doesn't have exactly one solution");
+    };
+    let report = match format {
+        WalkthroughFormat::Text => walkthrough.to_text(),
+        WalkthroughFormat::Markdown => walkthrough.to_markdown(),
+    };
+    match output {
+        Some(output) => {
+            fs::write(&output, report).wrap_err_with(|| format!("writing {}", output.display()))?;
+            println!("walkthrough written to {}", output.display());
+        }
+        None => print!("{report}"),
+    }
+    Ok(())
+}
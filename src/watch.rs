@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+/// Notices when the config file changes on disk so it can be reloaded live.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ConfigWatcher {
+    _watcher: notify::RecommendedWatcher,
+    rx: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+    paths: Vec<PathBuf>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ConfigWatcher {
+    pub fn new(paths: Vec<PathBuf>) -> Option<Self> {
+        use notify::Watcher;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).ok()?;
+        // editors often replace the file instead of writing it, so watch the
+        // directory and filter by file name
+        for dir in paths.iter().filter_map(|path| path.parent()) {
+            if dir.exists() {
+                watcher
+                    .watch(dir, notify::RecursiveMode::NonRecursive)
+                    .ok()?;
+            }
+        }
+        Some(Self {
+            _watcher: watcher,
+            rx,
+            paths,
+        })
+    }
+
+    /// True if any watched file changed since the last call.
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        for event in self.rx.try_iter().flatten() {
+            let relevant = matches!(
+                event.kind,
+                notify::EventKind::Create(_) | notify::EventKind::Modify(_)
+            );
+            if relevant && event.paths.iter().any(|p| self.paths.contains(p)) {
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct ConfigWatcher;
+
+#[cfg(target_arch = "wasm32")]
+impl ConfigWatcher {
+    pub fn new(_paths: Vec<PathBuf>) -> Option<Self> {
+        None
+    }
+
+    pub fn changed(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,66 @@
+use clap::ValueEnum;
+use color_eyre::eyre::{Result, bail};
+
+use crate::{
+    generator::{dig, relabel, shuffled},
+    rng::Rng,
+    soduko::BoardState,
+    symbols::Symbols,
+};
+
+/// Shuffles tried before giving up on a grid with a distinct diagonal.
+const MAX_ATTEMPTS: usize = 100_000;
+
+/// Where the hidden word reads in the solution.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum WordPlace {
+    /// a random row
+    #[default]
+    Row,
+    /// top left to bottom right
+    Diagonal,
+}
+
+/// A letter puzzle whose solution spells `word` along `place`, and the
+/// symbols to show it with.
+pub fn generate(word: &str, place: WordPlace, rng: &mut Rng) -> Result<(BoardState, Symbols)> {
+    let symbols = Symbols::letters(word)?;
+    let solution = solved_grid(place, rng)?;
+    Ok((dig(solution, rng), symbols))
+}
+
+/// A full grid relabelled so the cells along `place` read 1 to 9, which the
+/// symbols then turn into the word.
+fn solved_grid(place: WordPlace, rng: &mut Rng) -> Result<BoardState> {
+    let base = BoardState::default()
+        .solve()
+        .expect("the empty board is solvable");
+    for _ in 0..MAX_ATTEMPTS {
+        let grid = shuffled(&base, rng);
+        let line: Vec<(usize, usize)> = match place {
+            WordPlace::Row => {
+                let row = rng.below(9);
+                (0..9).map(|col| (row, col)).collect()
+            }
+            WordPlace::Diagonal => (0..9).map(|i| (i, i)).collect(),
+        };
+        let values: Vec<u8> = line
+            .iter()
+            .map(|&(r, c)| grid[r][c].map_or(0, |d| d.get()))
+            .collect();
+        let mut seen = 0u16;
+        for &v in &values {
+            seen |= 1 << v;
+        }
+        if seen.count_ones() != 9 {
+            continue;
+        }
+        // values[i] becomes i + 1
+        let mut labels = [0u8; 10];
+        for (i, &v) in values.iter().enumerate() {
+            labels[v as usize] = i as u8 + 1;
+        }
+        return Ok(relabel(grid, |d| labels[d as usize]));
+    }
+    bail!("found no grid with a distinct diagonal")
+}
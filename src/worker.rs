@@ -0,0 +1,100 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+    },
+    thread,
+};
+
+use crate::{
+    daily::{Archive, Date},
+    difficulty::Difficulty,
+    events::Event,
+    generator::{self, Carving, Symmetry},
+    rng::Rng,
+    sized::{BoardSize, SizedPuzzle},
+    soduko::BoardState,
+    variant::Variant,
+};
+
+/// A puzzle the app is waiting on, and what it's for.
+#[derive(Debug, Clone, Copy)]
+pub enum Work {
+    /// a new game of this difficulty and variant
+    NewGame(Difficulty, Variant),
+    /// a puzzle carved out of the grid the player filled in
+    Carve(BoardState, Difficulty, Symmetry),
+    /// a hot-seat game of this variant, on a chess clock when `true`
+    HotSeat(Difficulty, Variant, bool),
+    /// a 6x6 or 16x16 game
+    Sized(BoardSize),
+    /// the daily puzzles of the month up to this day
+    Daily(Date),
+}
+
+/// What a [`Work`] comes to.
+#[derive(Debug, Clone)]
+pub enum Done {
+    Puzzle(BoardState),
+    Sized(SizedPuzzle),
+    Daily(Vec<(Date, BoardState)>),
+}
+
+impl Work {
+    /// Does the work, a dig at a time for 9x9 puzzles so it can give up
+    /// between digs. `None` when `stop` was set before it finished.
+    pub fn run(self, rng: &mut Rng, stop: &AtomicBool) -> Option<Done> {
+        let mut carving = match self {
+            Work::NewGame(difficulty, variant) | Work::HotSeat(difficulty, variant, _) => {
+                Carving::new(
+                    generator::variant_grid(variant, rng),
+                    difficulty.tier(),
+                    Symmetry::None,
+                )
+            }
+            Work::Carve(solution, difficulty, symmetry) => {
+                Carving::new(solution, difficulty.tier(), symmetry)
+            }
+            Work::Sized(size) => {
+                let puzzle = size.puzzle(rng);
+                return (!stop.load(Ordering::Relaxed)).then_some(Done::Sized(puzzle));
+            }
+            Work::Daily(today) => return Archive::puzzles(today, stop).map(Done::Daily),
+        };
+        while !stop.load(Ordering::Relaxed) {
+            if let Some(puzzle) = carving.step(rng) {
+                return Some(Done::Puzzle(puzzle));
+            }
+        }
+        None
+    }
+}
+
+/// Generates a puzzle off the render thread and sends it to the app as an
+/// [`Event::Worked`] tagged with its id, on a thread of its own which
+/// dropping this stops after the dig being worked on. A puzzle sent just
+/// before that still arrives, the id tells it apart from the one asked for
+/// since.
+pub struct Worker {
+    stop: Arc<AtomicBool>,
+}
+
+impl Worker {
+    pub fn spawn(id: u64, work: Work, mut rng: Rng, done: Sender<Event>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        thread::spawn(move || {
+            if let Some(result) = work.run(&mut rng, &stopped) {
+                let _ = done.send(Event::Worked(id, work, result));
+            }
+        });
+        Self { stop }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}